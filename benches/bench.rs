@@ -1,234 +1,156 @@
-#![feature(test)]
-
-extern crate test;
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use test::Bencher;
-
-    #[test]
-    fn test044_canonize() {
-        // normalize/tests/test044-in.nq
-        let dataset_str = r#"_:b0 <http://example.org/vocab#p> _:b1 .
-_:b0 <http://example.org/vocab#p> _:b2 .
-_:b0 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b0 .
-_:b1 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b0 .
-_:b2 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b5 .
-_:b3 <http://example.org/vocab#p> _:b0 .
-_:b3 <http://example.org/vocab#p> _:b1 .
-_:b3 <http://example.org/vocab#p> _:b5 .
-_:b4 <http://example.org/vocab#p> _:b1 .
-_:b4 <http://example.org/vocab#p> _:b2 .
-_:b4 <http://example.org/vocab#p> _:b5 .
-_:b5 <http://example.org/vocab#p> _:b3 .
-_:b5 <http://example.org/vocab#p> _:b2 .
-_:b5 <http://example.org/vocab#p> _:b4 .
-_:b6 <http://example.org/vocab#p> _:b7 .
-_:b6 <http://example.org/vocab#p> _:b8 .
-_:b6 <http://example.org/vocab#p> _:b9 .
-_:b7 <http://example.org/vocab#p> _:b6 .
-_:b7 <http://example.org/vocab#p> _:b10 .
-_:b7 <http://example.org/vocab#p> _:b11 .
-_:b8 <http://example.org/vocab#p> _:b6 .
-_:b8 <http://example.org/vocab#p> _:b10 .
-_:b8 <http://example.org/vocab#p> _:b11 .
-_:b9 <http://example.org/vocab#p> _:b6 .
-_:b9 <http://example.org/vocab#p> _:b10 .
-_:b9 <http://example.org/vocab#p> _:b11 .
-_:b10 <http://example.org/vocab#p> _:b7 .
-_:b10 <http://example.org/vocab#p> _:b8 .
-_:b10 <http://example.org/vocab#p> _:b9 .
-_:b11 <http://example.org/vocab#p> _:b7 .
-_:b11 <http://example.org/vocab#p> _:b8 .
-_:b11 <http://example.org/vocab#p> _:b9 .
-"#;
-
-        let rdf_dataset = rdf_canonize::nquads::parse_nquads(&dataset_str);
-        let f = rdf_canonize::canonize(&rdf_dataset, "URDNA2015");
-        println!("FFFF {}", f.unwrap());
-    }
-
-    #[test]
-    fn test_merge_canonize() {
-        // merge event
-        let dataset_str = r#"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/webledger#ContinuityMergeEvent> .
-_:b0 <https://w3id.org/security#proof> _:b1 .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmYDcw6hXTZHCYaPyuGLCo8jcNREidQs4ikwKdVyS5uwKA" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQma45eMXmzKBXYwLdU7FvAEW3ekMy4fJjqEQVhYQFgwYAP" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmb6eicGxT6FAAZdxEzam2JpPu8ajiMJYhzPnhgHJJKh8f" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmc6b7weYQEu2NBDK9DB4HBc4bt2qQGbkvkEZBW6ajJ5F7" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmdxvSCwPjTvx3SAN2XHZ4uQpHKpbnHmns9BF8uZASW6Lx" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmePs3zy2fLPEsBXqGn2LPWSGYbzPy7CZTTz1f2ng3ysph" .
-_:b0 <https://w3id.org/webledger#treeHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b2 <http://purl.org/dc/terms/created> "2018-12-21T23:40:20Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> _:b1 .
-_:b2 <http://purl.org/dc/terms/creator> <https://bedrock.localhost:18443/consensus/continuity2017/voters/z6MkkabTusFkLnquxwHwCm28v59UX3P9Pn5scvc7fCaNvWUL> _:b1 .
-_:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Ed25519Signature2018> _:b1 .
-_:b2 <https://w3id.org/security#jws> "eyJhbGciOiJFZERTQSIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..JJ5c7mF7ru9XhPtrNqj1s6J74yqOC0HcNyK_Wa0OcfDaiODZFIJ2dXIrc_qqqvTWynIqJid6yXkKsGAzyi_HDQ" _:b1 .
-"#;
-
-        let rdf_dataset = rdf_canonize::nquads::parse_nquads(&dataset_str);
-        let f = rdf_canonize::canonize(&rdf_dataset, "URDNA2015");
-        println!("FFFF {}", f.unwrap());
-    }
-
-    #[bench]
-    fn bench009_canonize(b: &mut Bencher) {
-        // normalization/tests/test009-urdna2015.nq
-        let dataset_str = r#"<http://example.org/test#chapter> <http://purl.org/dc/elements/1.1/description> "Fun" .
-<http://example.org/test#chapter> <http://purl.org/dc/elements/1.1/title> "Chapter One" .
-<http://example.org/test#jane> <http://example.org/vocab#authored> <http://example.org/test#chapter> .
-<http://example.org/test#jane> <http://xmlns.com/foaf/0.1/name> "Jane" .
-<http://example.org/test#john> <http://xmlns.com/foaf/0.1/name> "John" .
-<http://example.org/test#library> <http://example.org/vocab#contains> <http://example.org/test#book> .
-<http://example.org/test#book> <http://example.org/vocab#contains> <http://example.org/test#chapter> .
-<http://example.org/test#book> <http://purl.org/dc/elements/1.1/contributor> "Writer" .
-<http://example.org/test#book> <http://purl.org/dc/elements/1.1/title> "My Book" .
-"#;
-
-        let rdf_dataset = rdf_canonize::nquads::parse_nquads(&dataset_str);
-
-        b.iter(|| rdf_canonize::canonize(&rdf_dataset, "URDNA2015").unwrap())
-    }
-
-    #[bench]
-    fn bench044_canonize(b: &mut Bencher) {
-        // normalize/tests/test044-urdna2015.nq
-        let dataset_str = r#"_:b0 <http://example.org/vocab#p> _:b1 .
-_:b0 <http://example.org/vocab#p> _:b2 .
-_:b0 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b0 .
-_:b1 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b0 .
-_:b2 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b5 .
-_:b3 <http://example.org/vocab#p> _:b0 .
-_:b3 <http://example.org/vocab#p> _:b1 .
-_:b3 <http://example.org/vocab#p> _:b5 .
-_:b4 <http://example.org/vocab#p> _:b1 .
-_:b4 <http://example.org/vocab#p> _:b2 .
-_:b4 <http://example.org/vocab#p> _:b5 .
-_:b5 <http://example.org/vocab#p> _:b3 .
-_:b5 <http://example.org/vocab#p> _:b2 .
-_:b5 <http://example.org/vocab#p> _:b4 .
-_:b6 <http://example.org/vocab#p> _:b7 .
-_:b6 <http://example.org/vocab#p> _:b8 .
-_:b6 <http://example.org/vocab#p> _:b9 .
-_:b7 <http://example.org/vocab#p> _:b6 .
-_:b7 <http://example.org/vocab#p> _:b10 .
-_:b7 <http://example.org/vocab#p> _:b11 .
-_:b8 <http://example.org/vocab#p> _:b6 .
-_:b8 <http://example.org/vocab#p> _:b10 .
-_:b8 <http://example.org/vocab#p> _:b11 .
-_:b9 <http://example.org/vocab#p> _:b6 .
-_:b9 <http://example.org/vocab#p> _:b10 .
-_:b9 <http://example.org/vocab#p> _:b11 .
-_:b10 <http://example.org/vocab#p> _:b7 .
-_:b10 <http://example.org/vocab#p> _:b8 .
-_:b10 <http://example.org/vocab#p> _:b9 .
-_:b11 <http://example.org/vocab#p> _:b7 .
-_:b11 <http://example.org/vocab#p> _:b8 .
-_:b11 <http://example.org/vocab#p> _:b9 .
-"#;
+// Criterion benchmarks, run with `cargo bench`.
+//
+// The fixtures next to this file are test009 and test044 of the URDNA2015
+// test suite and a merge event of a Web Ledger. Each group measures one stage:
+//
+// - parse: N-Quads parsing alone
+// - first_degree: canonicalization of datasets whose blank nodes all have
+//   unique first degree hashes, so no N-degree hashing is done
+// - n_degree: canonicalization of graphs whose blank nodes share their first
+//   degree hash, like test044, where N-degree hashing dominates. Besides
+//   test044 these are chains, 3-regular graphs, stars and small cliques in
+//   `generated/`, written by `rdf-canonize generate <shape> <size> --seed 0`
+//   (with `--degree 3` for the regular graphs).
+// - serialize: writing an already canonical dataset as N-Quads
+//
+// The synthetic datasets are scaled by their number of credentials or blank
+// nodes.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rdf_canonize::nquads::{self, Dataset};
+use rdf_canonize::{canonize, canonize_dataset};
+
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+
+const FIXTURES: [&str; 3] = ["test009", "test044", "merge"];
+
+fn fixture(name: &str) -> String {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("benches")
+    .join(format!("{}.nq", name));
+  fs::read_to_string(path).unwrap()
+}
 
-        let rdf_dataset = rdf_canonize::nquads::parse_nquads(&dataset_str);
+fn generated(shape: &str, size: usize) -> String {
+  fixture(&format!("generated/{}-{}", shape, size))
+}
 
-        b.iter(|| rdf_canonize::canonize(&rdf_dataset, "URDNA2015").unwrap())
+// `count` credential-like records, each a blank node credential with a blank
+// node subject and a proof in a blank node graph. Every record carries its own
+// literals, so the blank nodes are told apart by their first degree hashes.
+fn credentials(count: usize) -> String {
+  let mut nquads = String::new();
+  for i in 0..count {
+    let credential = format!("_:credential{}", i);
+    let subject = format!("_:subject{}", i);
+    let proof = format!("_:proof{}", i);
+    let signature = format!("_:signature{}", i);
+    for line in &[
+      format!("{} <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .", credential),
+      format!("{} <https://www.w3.org/2018/credentials#issuer> <https://example.org/issuers/{}> .", credential, i % 7),
+      format!("{} <http://schema.org/identifier> \"{}\" .", credential, i),
+      format!("{} <https://www.w3.org/2018/credentials#issuanceDate> \"2020-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime> .", credential),
+      format!("{} <https://www.w3.org/2018/credentials#credentialSubject> {} .", credential, subject),
+      format!("{} <http://schema.org/name> \"Subject {}\" .", subject, i),
+      format!("{} <https://w3id.org/security#proof> {} .", credential, proof),
+      format!("{} <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Ed25519Signature2018> {} .", signature, proof),
+      format!("{} <https://w3id.org/security#jws> \"signature {}\" {} .", signature, i, proof),
+    ] {
+      nquads.push_str(line);
+      nquads.push('\n');
     }
+  }
+  nquads
+}
 
-    #[bench]
-    fn bench_merge_canonize(b: &mut Bencher) {
-        // normalization/tests/test009-urdna2015.nq
-        let dataset_str = r#"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/webledger#ContinuityMergeEvent> .
-_:b0 <https://w3id.org/security#proof> _:b1 .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmYDcw6hXTZHCYaPyuGLCo8jcNREidQs4ikwKdVyS5uwKA" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQma45eMXmzKBXYwLdU7FvAEW3ekMy4fJjqEQVhYQFgwYAP" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmb6eicGxT6FAAZdxEzam2JpPu8ajiMJYhzPnhgHJJKh8f" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmc6b7weYQEu2NBDK9DB4HBc4bt2qQGbkvkEZBW6ajJ5F7" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmdxvSCwPjTvx3SAN2XHZ4uQpHKpbnHmns9BF8uZASW6Lx" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmePs3zy2fLPEsBXqGn2LPWSGYbzPy7CZTTz1f2ng3ysph" .
-_:b0 <https://w3id.org/webledger#treeHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b2 <http://purl.org/dc/terms/created> "2018-12-21T23:40:20Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> _:b1 .
-_:b2 <http://purl.org/dc/terms/creator> <https://bedrock.localhost:18443/consensus/continuity2017/voters/z6MkkabTusFkLnquxwHwCm28v59UX3P9Pn5scvc7fCaNvWUL> _:b1 .
-_:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Ed25519Signature2018> _:b1 .
-_:b2 <https://w3id.org/security#jws> "eyJhbGciOiJFZERTQSIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..JJ5c7mF7ru9XhPtrNqj1s6J74yqOC0HcNyK_Wa0OcfDaiODZFIJ2dXIrc_qqqvTWynIqJid6yXkKsGAzyi_HDQ" _:b1 .
-"#;
-
-        let rdf_dataset = rdf_canonize::nquads::parse_nquads(&dataset_str);
-
-        b.iter(|| rdf_canonize::canonize(&rdf_dataset, "URDNA2015").unwrap())
-    }
+fn parse(c: &mut Criterion) {
+  let mut group = c.benchmark_group("parse");
+  for name in &FIXTURES {
+    let input = fixture(name);
+    group.bench_function(*name, |b| {
+      b.iter(|| nquads::parse_nquads(black_box(&input)))
+    });
+  }
+  for count in &[10, 100, 1000] {
+    let input = credentials(*count);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_with_input(
+      BenchmarkId::new("credentials", count),
+      &input,
+      |b, input| b.iter(|| nquads::parse_nquads(black_box(input))),
+    );
+  }
+  group.finish();
+}
 
-    #[bench]
-    fn bench_merge_parse(b: &mut Bencher) {
-        // normalization/tests/test009-urdna2015.nq
-        let dataset_str = r#"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/webledger#ContinuityMergeEvent> .
-_:b0 <https://w3id.org/security#proof> _:b1 .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmYDcw6hXTZHCYaPyuGLCo8jcNREidQs4ikwKdVyS5uwKA" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQma45eMXmzKBXYwLdU7FvAEW3ekMy4fJjqEQVhYQFgwYAP" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmb6eicGxT6FAAZdxEzam2JpPu8ajiMJYhzPnhgHJJKh8f" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmc6b7weYQEu2NBDK9DB4HBc4bt2qQGbkvkEZBW6ajJ5F7" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmdxvSCwPjTvx3SAN2XHZ4uQpHKpbnHmns9BF8uZASW6Lx" .
-_:b0 <https://w3id.org/webledger#parentHash> "zQmePs3zy2fLPEsBXqGn2LPWSGYbzPy7CZTTz1f2ng3ysph" .
-_:b0 <https://w3id.org/webledger#treeHash> "zQmPkZrQs9dyezAQkVniqkMjm5nP3cdWFBzNsnnFLrsNf9u" .
-_:b2 <http://purl.org/dc/terms/created> "2018-12-21T23:40:20Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> _:b1 .
-_:b2 <http://purl.org/dc/terms/creator> <https://bedrock.localhost:18443/consensus/continuity2017/voters/z6MkkabTusFkLnquxwHwCm28v59UX3P9Pn5scvc7fCaNvWUL> _:b1 .
-_:b2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://w3id.org/security#Ed25519Signature2018> _:b1 .
-_:b2 <https://w3id.org/security#jws> "eyJhbGciOiJFZERTQSIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..JJ5c7mF7ru9XhPtrNqj1s6J74yqOC0HcNyK_Wa0OcfDaiODZFIJ2dXIrc_qqqvTWynIqJid6yXkKsGAzyi_HDQ" _:b1 .
-"#;
+fn first_degree(c: &mut Criterion) {
+  let mut group = c.benchmark_group("first_degree");
+  for name in &["test009", "merge"] {
+    let dataset = nquads::parse_nquads(&fixture(name));
+    group.bench_function(*name, |b| {
+      b.iter(|| canonize(black_box(&dataset), "URDNA2015").unwrap())
+    });
+  }
+  for count in &[10, 100, 1000] {
+    let dataset = nquads::parse_nquads(&credentials(*count));
+    group.throughput(Throughput::Elements(dataset.quads.len() as u64));
+    group.bench_with_input(
+      BenchmarkId::new("credentials", count),
+      &dataset,
+      |b, dataset| b.iter(|| canonize(black_box(dataset), "URDNA2015").unwrap()),
+    );
+  }
+  group.finish();
+}
 
-        b.iter(|| rdf_canonize::nquads::parse_nquads(&dataset_str))
+fn n_degree(c: &mut Criterion) {
+  let mut group = c.benchmark_group("n_degree");
+  let dataset = nquads::parse_nquads(&fixture("test044"));
+  group.bench_function("test044", |b| {
+    b.iter(|| canonize(black_box(&dataset), "URDNA2015").unwrap())
+  });
+  let shapes = [
+    ("chain", &[8, 16, 32][..]),
+    ("regular", &[12, 24][..]),
+    ("star", &[8, 64][..]),
+    ("clique", &[4, 5][..]),
+  ];
+  for (name, sizes) in &shapes {
+    for size in sizes.iter() {
+      let dataset = nquads::parse_nquads(&generated(name, *size));
+      group.bench_with_input(BenchmarkId::new(*name, size), &dataset, |b, dataset| {
+        b.iter(|| canonize(black_box(dataset), "URDNA2015").unwrap())
+      });
     }
+  }
+  group.finish();
+}
 
-    #[bench]
-    fn bench044_parse(b: &mut Bencher) {
-        // normalize/tests/test044-in.nq
-        let dataset_str = r#"_:b0 <http://example.org/vocab#p> _:b1 .
-_:b0 <http://example.org/vocab#p> _:b2 .
-_:b0 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b0 .
-_:b1 <http://example.org/vocab#p> _:b3 .
-_:b1 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b0 .
-_:b2 <http://example.org/vocab#p> _:b4 .
-_:b2 <http://example.org/vocab#p> _:b5 .
-_:b3 <http://example.org/vocab#p> _:b0 .
-_:b3 <http://example.org/vocab#p> _:b1 .
-_:b3 <http://example.org/vocab#p> _:b5 .
-_:b4 <http://example.org/vocab#p> _:b1 .
-_:b4 <http://example.org/vocab#p> _:b2 .
-_:b4 <http://example.org/vocab#p> _:b5 .
-_:b5 <http://example.org/vocab#p> _:b3 .
-_:b5 <http://example.org/vocab#p> _:b2 .
-_:b5 <http://example.org/vocab#p> _:b4 .
-_:b6 <http://example.org/vocab#p> _:b7 .
-_:b6 <http://example.org/vocab#p> _:b8 .
-_:b6 <http://example.org/vocab#p> _:b9 .
-_:b7 <http://example.org/vocab#p> _:b6 .
-_:b7 <http://example.org/vocab#p> _:b10 .
-_:b7 <http://example.org/vocab#p> _:b11 .
-_:b8 <http://example.org/vocab#p> _:b6 .
-_:b8 <http://example.org/vocab#p> _:b10 .
-_:b8 <http://example.org/vocab#p> _:b11 .
-_:b9 <http://example.org/vocab#p> _:b6 .
-_:b9 <http://example.org/vocab#p> _:b10 .
-_:b9 <http://example.org/vocab#p> _:b11 .
-_:b10 <http://example.org/vocab#p> _:b7 .
-_:b10 <http://example.org/vocab#p> _:b8 .
-_:b10 <http://example.org/vocab#p> _:b9 .
-_:b11 <http://example.org/vocab#p> _:b7 .
-_:b11 <http://example.org/vocab#p> _:b8 .
-_:b11 <http://example.org/vocab#p> _:b9 .
-"#;
-
-        b.iter(|| rdf_canonize::nquads::parse_nquads(&dataset_str))
-    }
+fn serialize(c: &mut Criterion) {
+  let mut group = c.benchmark_group("serialize");
+  let serialize =
+    |dataset: &Dataset| -> String { dataset.quads.iter().map(nquads::serialize_quad).collect() };
+  for name in &FIXTURES {
+    let dataset = canonize_dataset(&nquads::parse_nquads(&fixture(name)), "URDNA2015")
+      .unwrap()
+      .dataset;
+    group.bench_function(*name, |b| b.iter(|| serialize(black_box(&dataset))));
+  }
+  for count in &[10, 100, 1000] {
+    let dataset = canonize_dataset(&nquads::parse_nquads(&credentials(*count)), "URDNA2015")
+      .unwrap()
+      .dataset;
+    group.throughput(Throughput::Elements(dataset.quads.len() as u64));
+    group.bench_with_input(
+      BenchmarkId::new("credentials", count),
+      &dataset,
+      |b, dataset| b.iter(|| serialize(black_box(dataset))),
+    );
+  }
+  group.finish();
 }
+
+criterion_group!(benches, parse, first_degree, n_degree, serialize);
+criterion_main!(benches);
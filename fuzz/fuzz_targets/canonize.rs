@@ -0,0 +1,35 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rdf_canonize::{canonize, nquads, relabel};
+
+// Hash N-Degree Quads is exponential in the number of blank nodes that it
+// cannot tell apart, so large inputs would only report slow units.
+const MAX_QUADS: usize = 64;
+
+// The first eight bytes seed the relabeling, the rest is N-Quads. The
+// canonical form is a fixpoint of canonicalization and does not change when
+// the blank nodes of the input are renamed or its quads reordered.
+fuzz_target!(|data: &[u8]| {
+  if data.len() < 8 {
+    return;
+  }
+  let (seed, input) = data.split_at(8);
+  let mut seed_bytes = [0; 8];
+  seed_bytes.copy_from_slice(seed);
+  let dataset = match std::str::from_utf8(input).map(nquads::try_parse_nquads) {
+    Ok(Ok(dataset)) if dataset.quads.len() <= MAX_QUADS => dataset,
+    _ => return,
+  };
+
+  let canonical = canonize(&dataset, "URDNA2015").unwrap();
+  assert_eq!(
+    canonize(&nquads::parse_nquads(&canonical), "URDNA2015").unwrap(),
+    canonical
+  );
+
+  let mut rng = SmallRng::seed_from_u64(u64::from_le_bytes(seed_bytes));
+  relabel::assert_canonize_invariant(&dataset, &mut rng);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdf_canonize::nquads;
+
+// Parsing arbitrary input returns a dataset or an error; it never panics.
+fuzz_target!(|data: &[u8]| {
+  if let Ok(input) = std::str::from_utf8(data) {
+    let _ = nquads::try_parse_nquads(input);
+    for line in input.lines() {
+      let _ = nquads::try_parse_nquad(line);
+    }
+  }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdf_canonize::nquads;
+
+// Every dataset that parses serializes to N-Quads that parse back to the same
+// dataset, and serializing that dataset again gives the same N-Quads.
+fuzz_target!(|data: &[u8]| {
+  let dataset = match std::str::from_utf8(data).map(nquads::try_parse_nquads) {
+    Ok(Ok(dataset)) => dataset,
+    _ => return,
+  };
+  let serialized: String = dataset.quads.iter().map(nquads::serialize_quad).collect();
+  let reparsed = nquads::try_parse_nquads(&serialized)
+    .unwrap_or_else(|e| panic!("{} in serialized dataset:\n{}", e, serialized));
+  assert_eq!(reparsed, dataset, "serialized dataset:\n{}", serialized);
+
+  let again: String = reparsed.quads.iter().map(nquads::serialize_quad).collect();
+  assert_eq!(again, serialized);
+});
@@ -0,0 +1,611 @@
+// A compact binary form of a dataset, encoded as CBOR (RFC 8949) using only
+// the core deterministic encoding: shortest-form unsigned integers,
+// definite-length text strings and arrays, and null. A document is the array
+//
+//   [1, terms, quads]
+//
+// where `1` is the format version and `terms` is a dictionary of every
+// distinct term, in order of first use. Each term is an array starting with
+// its kind:
+//
+//   [0, iri]                                   named node
+//   [1, label]                                 blank node, including `_:`
+//   [2, lexical form, datatype, language, direction]
+//                                              literal
+//   [3, "", subject, predicate, object]        triple term
+//   [4, "@default"]                            default graph
+//
+// `datatype`, `subject`, `predicate` and `object` are indexes of terms that
+// appear earlier in the dictionary. `language` and `direction` are null when
+// absent. `datatype` is null when it is the one implied by the other fields
+// (xsd:string, rdf:langString or rdf:dirLangString), and false for a term
+// without one. Trailing nulls are left out. `quads` is a flat array of term
+// indexes, four per quad (subject, predicate, object, graph), in dataset
+// order.
+//
+// Every dataset has exactly one encoding, and decoding rejects anything else,
+// so two documents are byte-for-byte equal exactly when their datasets are.
+//
+// The canonical N-Quads of a dataset are the N-Quads lines of its quads in
+// sorted order, and `canonize_cbor` stores the quads in that same order. The
+// SHA-256 of the canonical N-Quads can therefore be computed from a canonical
+// document without building the N-Quads text. Each term in the dictionary
+// has one N-Quads form:
+//
+//   named node      `<` iri `>`
+//   blank node      the label
+//   literal         `"` escaped lexical form `"`, then `@` language and
+//                   `--` direction when present, or `^^<` datatype `>` when
+//                   the datatype is not xsd:string or a language string
+//   triple term     `<<( ` subject ` ` predicate ` ` object ` )>>`
+//   default graph   nothing
+//
+// where the lexical form is escaped as in canonical N-Quads (`\\`, `\"`,
+// `\n` and `\r`). The line of a quad is the forms of its subject, predicate,
+// object and graph joined by single spaces, leaving out the graph and its
+// space for the default graph, followed by ` .\n`. The hash is the SHA-256 of
+// the lines of the stored quads in order. `canonical_hash` computes the form
+// of each term once and feeds the lines to the hash piece by piece.
+
+use crate::message_digest::MessageDigest;
+use crate::nquads::{
+  push_object, Dataset, Graph, Object, Predicate, Quad, Subject, Term, TermType, Triple,
+  RDF_DIRLANGSTRING, RDF_LANGSTRING, XSD_STRING,
+};
+
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+const VERSION: u64 = 1;
+
+// CBOR major types
+const UNSIGNED: u8 = 0;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const FALSE: u8 = 0xf4;
+const NULL: u8 = 0xf6;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CborError {
+  // byte offset of the item that could not be decoded
+  pub offset: usize,
+  pub message: String,
+}
+
+impl fmt::Display for CborError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at byte {}", self.message, self.offset)
+  }
+}
+
+impl Error for CborError {}
+
+pub fn serialize_cbor(dataset: &Dataset) -> Vec<u8> {
+  let mut encoder = Encoder {
+    terms: Vec::new(),
+    term_count: 0,
+    index: HashMap::new(),
+  };
+  let mut quads = Vec::with_capacity(dataset.quads.len() * 4);
+  for quad in &dataset.quads {
+    quads.push(encoder.subject(&quad.subject));
+    quads.push(encoder.predicate(&quad.predicate));
+    quads.push(encoder.object(&quad.object));
+    quads.push(encoder.graph(&quad.graph));
+  }
+
+  let mut output = Vec::with_capacity(encoder.terms.len() + quads.len() * 2 + 16);
+  write_head(&mut output, ARRAY, 3);
+  write_head(&mut output, UNSIGNED, VERSION);
+  write_head(&mut output, ARRAY, encoder.term_count);
+  output.append(&mut encoder.terms);
+  write_head(&mut output, ARRAY, quads.len() as u64);
+  for index in quads {
+    write_head(&mut output, UNSIGNED, index);
+  }
+  output
+}
+
+pub fn parse_cbor(input: &[u8]) -> Dataset {
+  try_parse_cbor(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Decodes a document written by `serialize_cbor`. Input that is valid CBOR
+// but not the deterministic encoding of its dataset is rejected.
+pub fn try_parse_cbor(input: &[u8]) -> Result<Dataset, CborError> {
+  let (terms, indexes) = decode(input)?;
+  let quads = indexes
+    .iter()
+    .map(|[subject, predicate, object, graph]| Quad {
+      subject: to_subject(&terms[*subject]),
+      predicate: to_predicate(&terms[*predicate]),
+      object: terms[*object].clone(),
+      graph: to_graph(&terms[*graph]),
+    })
+    .collect();
+
+  // the simplest complete check for a non-deterministic encoding, such as a
+  // long-form integer, an unused or repeated term, or terms out of order
+  let dataset = Dataset { quads };
+  if serialize_cbor(&dataset) != input {
+    return Err(CborError {
+      offset: 0,
+      message: String::from("not the deterministic encoding of its dataset"),
+    });
+  }
+  Ok(dataset)
+}
+
+// Returns the hex-encoded SHA-256 of the N-Quads lines of a document's
+// quads, using the rule in the module comment. For a document from
+// `canonize_cbor` this is the SHA-256 of the canonical N-Quads. Any
+// well-formed document is hashed; use `try_parse_cbor` to also check that
+// it is the deterministic encoding.
+pub fn canonical_hash(input: &[u8]) -> Result<String, CborError> {
+  let (terms, indexes) = decode(input)?;
+  let forms: Vec<String> = terms
+    .iter()
+    .map(|term| {
+      let mut form = String::new();
+      if term.term_type != TermType::DefaultGraph {
+        push_object(&mut form, term);
+      }
+      form
+    })
+    .collect();
+
+  let mut md: MessageDigest<Sha256> = MessageDigest::new();
+  for [subject, predicate, object, graph] in &indexes {
+    md.update(&forms[*subject]);
+    md.update(" ");
+    md.update(&forms[*predicate]);
+    md.update(" ");
+    md.update(&forms[*object]);
+    if !forms[*graph].is_empty() {
+      md.update(" ");
+      md.update(&forms[*graph]);
+    }
+    md.update(" .\n");
+  }
+  Ok(MessageDigest::digest(md))
+}
+
+// Reads the term dictionary of a document and the quads as four indexes
+// into it.
+fn decode(input: &[u8]) -> Result<(Vec<Object>, Vec<[usize; 4]>), CborError> {
+  let mut decoder = Decoder { input, position: 0 };
+  if decoder.array()? != 3 {
+    return Err(decoder.error(0, "expected a 3-element array"));
+  }
+  let start = decoder.position;
+  if decoder.unsigned()? != VERSION {
+    return Err(decoder.error(start, "unsupported format version"));
+  }
+
+  let term_count = decoder.array()?;
+  let mut terms: Vec<Object> = Vec::new();
+  for _ in 0..term_count {
+    let term = decoder.term(&terms)?;
+    terms.push(term);
+  }
+
+  let start = decoder.position;
+  let length = decoder.array()?;
+  if length % 4 != 0 {
+    return Err(decoder.error(start, "quad indexes must come in fours"));
+  }
+  let mut quads = Vec::new();
+  for _ in 0..length / 4 {
+    quads.push([
+      decoder.index(&terms)?,
+      decoder.index(&terms)?,
+      decoder.index(&terms)?,
+      decoder.index(&terms)?,
+    ]);
+  }
+  if decoder.position != input.len() {
+    return Err(decoder.error(decoder.position, "trailing bytes"));
+  }
+  Ok((terms, quads))
+}
+
+struct Encoder {
+  terms: Vec<u8>,
+  term_count: u64,
+  // encoded term -> index in the dictionary
+  index: HashMap<Vec<u8>, u64>,
+}
+
+impl Encoder {
+  fn subject(&mut self, subject: &Subject) -> u64 {
+    self.term(&subject.term_type, &subject.value, &[])
+  }
+
+  fn predicate(&mut self, predicate: &Predicate) -> u64 {
+    self.term(&predicate.term_type, &predicate.value, &[])
+  }
+
+  fn graph(&mut self, graph: &Graph) -> u64 {
+    self.term(&graph.term_type, &graph.value, &[])
+  }
+
+  fn object(&mut self, object: &Object) -> u64 {
+    if let Some(triple) = &object.triple {
+      let fields = [
+        Field::Index(self.subject(&triple.subject)),
+        Field::Index(self.predicate(&triple.predicate)),
+        Field::Index(self.object(&triple.object)),
+      ];
+      return self.term(&object.term_type, &object.value, &fields);
+    }
+
+    let datatype = match &object.datatype {
+      datatype if datatype.as_deref() == implied_datatype(object) => Field::Null,
+      Some(datatype) => Field::Index(self.term(&TermType::NamedNode, datatype, &[])),
+      None => Field::False,
+    };
+    let mut fields = vec![
+      datatype,
+      object.language.as_deref().map_or(Field::Null, Field::Text),
+      object.direction.as_deref().map_or(Field::Null, Field::Text),
+    ];
+    while fields.last() == Some(&Field::Null) {
+      fields.pop();
+    }
+    self.term(&object.term_type, &object.value, &fields)
+  }
+
+  fn term(&mut self, term_type: &TermType, value: &str, fields: &[Field]) -> u64 {
+    let mut entry = Vec::new();
+    write_head(&mut entry, ARRAY, 2 + fields.len() as u64);
+    write_head(&mut entry, UNSIGNED, kind(term_type));
+    write_text(&mut entry, value);
+    for field in fields {
+      match field {
+        Field::Null => entry.push(NULL),
+        Field::False => entry.push(FALSE),
+        Field::Index(index) => write_head(&mut entry, UNSIGNED, *index),
+        Field::Text(text) => write_text(&mut entry, text),
+      }
+    }
+
+    if let Some(index) = self.index.get(&entry) {
+      return *index;
+    }
+    let index = self.term_count;
+    self.terms.extend_from_slice(&entry);
+    self.index.insert(entry, index);
+    self.term_count += 1;
+    index
+  }
+}
+
+#[derive(PartialEq)]
+enum Field<'a> {
+  Null,
+  False,
+  Index(u64),
+  Text(&'a str),
+}
+
+// The datatype of a literal that N-Quads leaves unwritten.
+fn implied_datatype(object: &Object) -> Option<&'static str> {
+  match object.term_type {
+    TermType::Literal if object.direction.is_some() => Some(RDF_DIRLANGSTRING),
+    TermType::Literal if object.language.is_some() => Some(RDF_LANGSTRING),
+    TermType::Literal => Some(XSD_STRING),
+    _ => None,
+  }
+}
+
+fn kind(term_type: &TermType) -> u64 {
+  match term_type {
+    TermType::NamedNode => 0,
+    TermType::BlankNode => 1,
+    TermType::Literal => 2,
+    TermType::Triple => 3,
+    TermType::DefaultGraph => 4,
+    TermType::None => 5,
+  }
+}
+
+fn term_type(kind: u64) -> Option<TermType> {
+  match kind {
+    0 => Some(TermType::NamedNode),
+    1 => Some(TermType::BlankNode),
+    2 => Some(TermType::Literal),
+    3 => Some(TermType::Triple),
+    4 => Some(TermType::DefaultGraph),
+    5 => Some(TermType::None),
+    _ => None,
+  }
+}
+
+fn write_head(output: &mut Vec<u8>, major: u8, argument: u64) {
+  let major = major << 5;
+  if argument < 24 {
+    output.push(major | argument as u8);
+  } else if argument <= u8::MAX as u64 {
+    output.push(major | 24);
+    output.push(argument as u8);
+  } else if argument <= u16::MAX as u64 {
+    output.push(major | 25);
+    output.extend_from_slice(&(argument as u16).to_be_bytes());
+  } else if argument <= u32::MAX as u64 {
+    output.push(major | 26);
+    output.extend_from_slice(&(argument as u32).to_be_bytes());
+  } else {
+    output.push(major | 27);
+    output.extend_from_slice(&argument.to_be_bytes());
+  }
+}
+
+fn write_text(output: &mut Vec<u8>, text: &str) {
+  write_head(output, TEXT, text.len() as u64);
+  output.extend_from_slice(text.as_bytes());
+}
+
+struct Decoder<'a> {
+  input: &'a [u8],
+  position: usize,
+}
+
+impl<'a> Decoder<'a> {
+  fn error(&self, offset: usize, message: &str) -> CborError {
+    CborError {
+      offset,
+      message: message.to_string(),
+    }
+  }
+
+  // Reads the initial byte and argument of a data item of type `major`.
+  fn head(&mut self, major: u8, expected: &str) -> Result<u64, CborError> {
+    let start = self.position;
+    let initial = *self
+      .input
+      .get(start)
+      .ok_or_else(|| self.error(start, &format!("expected {}, found end of input", expected)))?;
+    if initial >> 5 != major {
+      return Err(self.error(start, &format!("expected {}", expected)));
+    }
+    let length = match initial & 0x1f {
+      argument @ 0..=23 => {
+        self.position += 1;
+        return Ok(argument as u64);
+      }
+      24 => 1,
+      25 => 2,
+      26 => 4,
+      27 => 8,
+      _ => return Err(self.error(start, "indefinite lengths are not supported")),
+    };
+    let bytes = self
+      .input
+      .get(start + 1..start + 1 + length)
+      .ok_or_else(|| self.error(start, "unexpected end of input"))?;
+    self.position = start + 1 + length;
+    Ok(
+      bytes
+        .iter()
+        .fold(0, |value, byte| value << 8 | *byte as u64),
+    )
+  }
+
+  fn unsigned(&mut self) -> Result<u64, CborError> {
+    self.head(UNSIGNED, "an unsigned integer")
+  }
+
+  fn array(&mut self) -> Result<u64, CborError> {
+    self.head(ARRAY, "an array")
+  }
+
+  fn text(&mut self) -> Result<String, CborError> {
+    let start = self.position;
+    let length = self.head(TEXT, "a text string")? as usize;
+    let bytes = self
+      .input
+      .get(self.position..self.position.saturating_add(length))
+      .ok_or_else(|| self.error(start, "unexpected end of input"))?;
+    self.position += length;
+    String::from_utf8(bytes.to_vec()).map_err(|_| self.error(start, "invalid UTF-8"))
+  }
+
+  // Reads a field that may be null, returning `None` for null.
+  fn optional<T, F>(&mut self, read: F) -> Result<Option<T>, CborError>
+  where
+    F: FnOnce(&mut Self) -> Result<T, CborError>,
+  {
+    if self.input.get(self.position) == Some(&NULL) {
+      self.position += 1;
+      return Ok(None);
+    }
+    read(self).map(Some)
+  }
+
+  fn index(&mut self, terms: &[Object]) -> Result<usize, CborError> {
+    let start = self.position;
+    let index = self.unsigned()?;
+    if index >= terms.len() as u64 {
+      return Err(self.error(start, &format!("term {} is not defined yet", index)));
+    }
+    Ok(index as usize)
+  }
+
+  fn reference<'t>(&mut self, terms: &'t [Object]) -> Result<&'t Object, CborError> {
+    self.index(terms).map(|index| &terms[index])
+  }
+
+  fn term(&mut self, terms: &[Object]) -> Result<Object, CborError> {
+    let start = self.position;
+    let length = self.array()?;
+    if length < 2 {
+      return Err(self.error(start, "a term needs a kind and a value"));
+    }
+    let kind_start = self.position;
+    let term_type =
+      term_type(self.unsigned()?).ok_or_else(|| self.error(kind_start, "unknown term kind"))?;
+
+    let mut object = Object::new();
+    object.term_type = term_type;
+    object.value = self.text()?;
+    match (term_type, length) {
+      (TermType::Triple, 5) => {
+        let subject = to_subject(self.reference(terms)?);
+        let predicate = to_predicate(self.reference(terms)?);
+        let triple_object = self.reference(terms)?.clone();
+        object.triple = Some(Box::new(Triple {
+          subject,
+          predicate,
+          object: triple_object,
+        }));
+      }
+      (TermType::Triple, _) => return Err(self.error(start, "a triple term has 5 elements")),
+      (_, 2..=5) => {
+        let fields = length - 2;
+        // `None` while the datatype is the implied one
+        let mut datatype = None;
+        if fields > 0 {
+          if self.input.get(self.position) == Some(&FALSE) {
+            self.position += 1;
+            datatype = Some(None);
+          } else {
+            datatype = self
+              .optional(|decoder| decoder.reference(terms).map(|term| term.value.clone()))?
+              .map(Some);
+          }
+        }
+        if fields > 1 {
+          object.language = self.optional(Decoder::text)?;
+        }
+        if fields > 2 {
+          object.direction = self.optional(Decoder::text)?;
+        }
+        object.datatype = datatype.unwrap_or_else(|| implied_datatype(&object).map(String::from));
+      }
+      _ => return Err(self.error(start, "a term has at most 5 elements")),
+    }
+    Ok(object)
+  }
+}
+
+fn to_subject(term: &Object) -> Subject {
+  Subject {
+    term_type: term.term_type,
+    value: term.value.clone(),
+  }
+}
+
+fn to_predicate(term: &Object) -> Predicate {
+  Predicate {
+    term_type: term.term_type,
+    value: term.value.clone(),
+  }
+}
+
+fn to_graph(term: &Object) -> Graph {
+  Graph {
+    term_type: term.term_type,
+    value: term.value.clone(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads;
+
+  const DATASET: &str = concat!(
+    "<http://example.org/s> <http://example.org/p> \"v\" .\n",
+    "<http://example.org/s> <http://example.org/p> \"v\"@en .\n",
+    "<http://example.org/s> <http://example.org/p> \"v\"@ar--rtl <http://example.org/g> .\n",
+    "_:b0 <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> _:g .\n",
+    "_:b0 <http://example.org/p> <<( _:b1 <http://example.org/p> <http://example.org/s> )>> .\n",
+    "_:b1 <http://example.org/p> \"a \\\"quoted\\\"\\nline\"^^<http://example.org/dt> _:g .\n",
+  );
+
+  #[test]
+  fn round_trip() {
+    let dataset = nquads::parse_nquads(DATASET);
+    let encoded = serialize_cbor(&dataset);
+    assert_eq!(try_parse_cbor(&encoded).unwrap(), dataset);
+    // terms are stored once
+    assert_eq!(
+      encoded
+        .windows(20)
+        .filter(|w| w == b"http://example.org/s")
+        .count(),
+      1
+    );
+    assert!(encoded.len() < DATASET.len());
+
+    // a literal built without a datatype stays without one
+    let mut untyped = dataset.clone();
+    untyped.quads[0].object.datatype = None;
+    assert_eq!(try_parse_cbor(&serialize_cbor(&untyped)).unwrap(), untyped);
+  }
+
+  #[test]
+  fn encoding() {
+    let dataset = nquads::parse_nquads("<a:s> <a:p> _:o .\n");
+    assert_eq!(
+      serialize_cbor(&dataset),
+      [
+        &[0x83, 0x01, 0x84][..],
+        &[0x82, 0x00, 0x63][..],
+        b"a:s",
+        &[0x82, 0x00, 0x63][..],
+        b"a:p",
+        &[0x82, 0x01, 0x63][..],
+        b"_:o",
+        &[0x82, 0x04, 0x68][..],
+        b"@default",
+        &[0x84, 0x00, 0x01, 0x02, 0x03][..],
+      ]
+      .concat()
+    );
+  }
+
+  #[test]
+  fn canonical_hash_matches_nquads() {
+    let dataset = nquads::parse_nquads(DATASET);
+    let canonical = crate::canonize(&dataset, "URDNA2015").unwrap();
+    let encoded = crate::canonize_cbor(&dataset, "URDNA2015").unwrap();
+    assert_eq!(
+      try_parse_cbor(&encoded).unwrap(),
+      nquads::parse_nquads(&canonical)
+    );
+
+    let mut md: MessageDigest<Sha256> = MessageDigest::new();
+    md.update(&canonical);
+    assert_eq!(canonical_hash(&encoded).unwrap(), MessageDigest::digest(md));
+  }
+
+  #[test]
+  fn rejects_non_deterministic_input() {
+    let encoded = serialize_cbor(&nquads::parse_nquads("<a:s> <a:p> <a:o> .\n"));
+
+    // the version as a two-byte integer
+    let mut long_form = encoded.clone();
+    long_form.splice(1..2, [0x18, 0x01]);
+    assert_eq!(
+      try_parse_cbor(&long_form).unwrap_err().message,
+      "not the deterministic encoding of its dataset"
+    );
+
+    assert_eq!(
+      try_parse_cbor(&encoded[..encoded.len() - 1]).unwrap_err(),
+      CborError {
+        offset: encoded.len() - 1,
+        message: String::from("expected an unsigned integer, found end of input"),
+      }
+    );
+    let mut trailing = encoded;
+    trailing.push(0);
+    assert_eq!(
+      try_parse_cbor(&trailing).unwrap_err().message,
+      "trailing bytes"
+    );
+  }
+}
@@ -0,0 +1,347 @@
+// Synthetic datasets for stress tests and benchmarks, to measure how
+// canonicalization scales with the number of blank nodes, their degree and the
+// symmetry of the graph.
+//
+// Every shape is made of blank nodes, which are given random labels and
+// written in random order. The same shape, size and seed always give the same
+// dataset with the same version of `rand`.
+//
+// Cliques are the worst case: every node has the same first degree hash and
+// N-degree hashing tries every permutation of the other nodes, so their cost
+// grows with the factorial of `size`. The center of a star is told apart by
+// its first degree hash, which keeps stars cheap however many leaves they have.
+
+use crate::nquads::{self, Dataset, Graph, Object, Quad, TermType, RDF_TYPE, XSD_STRING};
+use crate::relabel::{relabel_blank_nodes, shuffle_quads};
+use crate::turtle::{
+  blank_node, default_graph, named_node, object_to_subject, rdf_predicate, typed_literal,
+};
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngExt, SeedableRng};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+const EX: &str = "http://example.org/vocab#";
+const CRED: &str = "https://www.w3.org/2018/credentials#";
+const SEC: &str = "https://w3id.org/security#";
+
+// Values of the claims of generated credentials. There are few of them, so
+// sibling claims are often identical.
+const CLAIMS: [&str; 3] = ["Alice", "Bob", "Carol"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+  // `size` blank nodes, each linked to the next
+  Chain,
+  // one blank node linked to `size` blank nodes
+  Star,
+  // `size` blank nodes, each linked to every other one
+  Clique,
+  // `size` blank nodes, each linked in both directions to the given number of
+  // others, like the two 3-regular graphs of test044
+  Regular(usize),
+  // `size` credentials, each a tree of blank nodes with a proof graph
+  Credentials,
+}
+
+impl FromStr for Shape {
+  type Err = GenerateError;
+
+  // Parses a shape name. "regular" has degree 3.
+  fn from_str(name: &str) -> Result<Shape, GenerateError> {
+    match name {
+      "chain" => Ok(Shape::Chain),
+      "star" => Ok(Shape::Star),
+      "clique" => Ok(Shape::Clique),
+      "regular" => Ok(Shape::Regular(3)),
+      "credentials" => Ok(Shape::Credentials),
+      _ => Err(GenerateError::UnknownShape(name.to_string())),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenerateError {
+  UnknownShape(String),
+  // There is no simple graph with `size` nodes of degree `degree`.
+  NoRegularGraph { size: usize, degree: usize },
+}
+
+impl fmt::Display for GenerateError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      GenerateError::UnknownShape(name) => write!(
+        f,
+        "unknown shape \"{}\", expected chain, star, clique, regular or credentials",
+        name
+      ),
+      GenerateError::NoRegularGraph { size, degree } => {
+        write!(
+          f,
+          "there is no {}-regular graph with {} nodes",
+          degree, size
+        )
+      }
+    }
+  }
+}
+
+impl Error for GenerateError {}
+
+pub fn generate<R>(shape: Shape, size: usize, rng: &mut R) -> Result<Dataset, GenerateError>
+where
+  R: Rng + ?Sized,
+{
+  let mut dataset = match shape {
+    Shape::Chain => edges((1..size).map(|i| (i - 1, i))),
+    Shape::Star => edges((1..=size).map(|i| (0, i))),
+    Shape::Clique => {
+      edges((0..size).flat_map(|i| (0..size).filter(move |j| *j != i).map(move |j| (i, j))))
+    }
+    Shape::Regular(degree) => {
+      let pairs = regular_graph(size, degree, rng)?;
+      edges(pairs.into_iter().flat_map(|(i, j)| vec![(i, j), (j, i)]))
+    }
+    Shape::Credentials => credentials(size, rng),
+  };
+  relabel_blank_nodes(&mut dataset, rng);
+  shuffle_quads(&mut dataset, rng);
+  Ok(dataset)
+}
+
+pub fn generate_seeded(shape: Shape, size: usize, seed: u64) -> Result<Dataset, GenerateError> {
+  generate(shape, size, &mut SmallRng::seed_from_u64(seed))
+}
+
+// The generated quads as N-Quads, in their generated order.
+pub fn generate_nquads(shape: Shape, size: usize, seed: u64) -> Result<String, GenerateError> {
+  let dataset = generate_seeded(shape, size, seed)?;
+  Ok(dataset.quads.iter().map(nquads::serialize_quad).collect())
+}
+
+fn node(index: usize) -> Object {
+  blank_node(format!("_:n{}", index))
+}
+
+fn quad(subject: Object, predicate: &str, object: Object, graph: Graph) -> Quad {
+  Quad {
+    subject: object_to_subject(subject),
+    predicate: rdf_predicate(predicate),
+    object,
+    graph,
+  }
+}
+
+// Links blank node `i` to blank node `j` for every `(i, j)`.
+fn edges<I>(pairs: I) -> Dataset
+where
+  I: Iterator<Item = (usize, usize)>,
+{
+  let predicate = format!("{}p", EX);
+  let quads = pairs
+    .map(|(i, j)| quad(node(i), &predicate, node(j), default_graph()))
+    .collect();
+  Dataset { quads }
+}
+
+// The undirected edges of a random `degree`-regular graph with `size` nodes.
+// Nodes are paired at random until no pairing has loops or repeated edges;
+// if that takes too long, a circulant graph is used instead.
+fn regular_graph<R>(
+  size: usize,
+  degree: usize,
+  rng: &mut R,
+) -> Result<Vec<(usize, usize)>, GenerateError>
+where
+  R: Rng + ?Sized,
+{
+  if degree >= size.max(1) || size * degree % 2 == 1 {
+    return Err(GenerateError::NoRegularGraph { size, degree });
+  }
+
+  let mut points: Vec<usize> = (0..size)
+    .flat_map(|i| std::iter::repeat_n(i, degree))
+    .collect();
+  'attempt: for _ in 0..100 {
+    points.shuffle(rng);
+    let mut pairs: Vec<(usize, usize)> = Vec::with_capacity(points.len() / 2);
+    for pair in points.chunks(2) {
+      let edge = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+      if edge.0 == edge.1 || pairs.contains(&edge) {
+        continue 'attempt;
+      }
+      pairs.push(edge);
+    }
+    return Ok(pairs);
+  }
+
+  // each node is linked to the `degree / 2` nodes on either side of it, and
+  // to the opposite node when `degree` is odd (then `size` is even)
+  let mut pairs = Vec::with_capacity(size * degree / 2);
+  for i in 0..size {
+    for k in 1..=degree / 2 {
+      pairs.push((i, (i + k) % size));
+    }
+    if degree % 2 == 1 && i < size / 2 {
+      pairs.push((i, i + size / 2));
+    }
+  }
+  Ok(pairs)
+}
+
+// `count` credentials. Each has an issuer, a subject with one to three claims
+// and a proof graph with a signature.
+fn credentials<R>(count: usize, rng: &mut R) -> Dataset
+where
+  R: Rng + ?Sized,
+{
+  let mut quads = Vec::new();
+  let mut next = 0;
+  let mut fresh = || {
+    next += 1;
+    node(next - 1)
+  };
+
+  for _ in 0..count {
+    let credential = fresh();
+    let subject = fresh();
+    let proof = fresh();
+    let signature = fresh();
+    let graph = Graph {
+      term_type: TermType::BlankNode,
+      value: proof.value.clone(),
+    };
+
+    quads.push(quad(
+      credential.clone(),
+      RDF_TYPE,
+      named_node(format!("{}VerifiableCredential", CRED)),
+      default_graph(),
+    ));
+    quads.push(quad(
+      credential.clone(),
+      &format!("{}issuer", CRED),
+      named_node(format!(
+        "https://example.org/issuers/{}",
+        rng.random_range(0..3)
+      )),
+      default_graph(),
+    ));
+    quads.push(quad(
+      credential.clone(),
+      &format!("{}credentialSubject", CRED),
+      subject.clone(),
+      default_graph(),
+    ));
+    for _ in 0..rng.random_range(1..=3) {
+      let claim = fresh();
+      let value = CLAIMS[rng.random_range(0..CLAIMS.len())];
+      quads.push(quad(
+        subject.clone(),
+        &format!("{}claim", EX),
+        claim.clone(),
+        default_graph(),
+      ));
+      quads.push(quad(
+        claim,
+        &format!("{}name", EX),
+        typed_literal(value.to_string(), XSD_STRING),
+        default_graph(),
+      ));
+    }
+    quads.push(quad(
+      credential,
+      &format!("{}proof", SEC),
+      proof,
+      default_graph(),
+    ));
+    quads.push(quad(
+      signature.clone(),
+      RDF_TYPE,
+      named_node(format!("{}Ed25519Signature2018", SEC)),
+      graph.clone(),
+    ));
+    quads.push(quad(
+      signature,
+      &format!("{}jws", SEC),
+      typed_literal(format!("{:016x}", rng.random::<u64>()), XSD_STRING),
+      graph,
+    ));
+  }
+
+  Dataset { quads }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  fn out_degrees(dataset: &Dataset) -> Vec<usize> {
+    let mut degrees: HashMap<&str, usize> = HashMap::new();
+    for quad in &dataset.quads {
+      *degrees.entry(&quad.subject.value).or_insert(0) += 1;
+    }
+    let mut degrees: Vec<usize> = degrees.into_values().collect();
+    degrees.sort_unstable();
+    degrees
+  }
+
+  #[test]
+  fn shapes_have_their_size() {
+    let chain = generate_seeded(Shape::Chain, 5, 1).unwrap();
+    assert_eq!(chain.quads.len(), 4);
+    assert_eq!(out_degrees(&chain), vec![1; 4]);
+
+    let star = generate_seeded(Shape::Star, 5, 1).unwrap();
+    assert_eq!(out_degrees(&star), vec![5]);
+
+    let clique = generate_seeded(Shape::Clique, 4, 1).unwrap();
+    assert_eq!(out_degrees(&clique), vec![3; 4]);
+
+    for (size, degree) in &[(6, 3), (12, 3), (7, 4), (5, 4)] {
+      let regular = generate_seeded(Shape::Regular(*degree), *size, 1).unwrap();
+      assert_eq!(out_degrees(&regular), vec![*degree; *size]);
+      let mut deduped = regular.clone();
+      deduped.dedup();
+      assert_eq!(deduped, regular);
+    }
+
+    let credentials = generate_seeded(Shape::Credentials, 3, 1).unwrap();
+    let graphs: Vec<&Graph> = credentials
+      .quads
+      .iter()
+      .map(|quad| &quad.graph)
+      .filter(|graph| graph.term_type == TermType::BlankNode)
+      .collect();
+    assert_eq!(graphs.len(), 6);
+  }
+
+  #[test]
+  fn seeds_are_reproducible() {
+    let nquads = generate_nquads(Shape::Credentials, 4, 7).unwrap();
+    assert_eq!(generate_nquads(Shape::Credentials, 4, 7).unwrap(), nquads);
+    assert_ne!(generate_nquads(Shape::Credentials, 4, 8).unwrap(), nquads);
+    assert_eq!(
+      nquads::parse_nquads(&nquads),
+      generate_seeded(Shape::Credentials, 4, 7).unwrap()
+    );
+  }
+
+  #[test]
+  fn invalid_shapes() {
+    assert_eq!("regular".parse::<Shape>().unwrap(), Shape::Regular(3));
+    assert_eq!(
+      "ring".parse::<Shape>().unwrap_err().to_string(),
+      "unknown shape \"ring\", expected chain, star, clique, regular or credentials"
+    );
+    assert_eq!(
+      generate_seeded(Shape::Regular(3), 5, 1).unwrap_err(),
+      GenerateError::NoRegularGraph { size: 5, degree: 3 }
+    );
+    assert!(generate_seeded(Shape::Regular(4), 4, 1).is_err());
+  }
+}
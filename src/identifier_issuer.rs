@@ -31,8 +31,7 @@ impl IdentifierIssuer {
     }
 
     // get next identifier
-    let identifier = self.prefix.to_string() + &self.counter.to_string();
-    self.counter += 1;
+    let identifier = self.get_new_id();
 
     // save mapping
     self.old_ids.push(old.to_string());
@@ -43,6 +42,13 @@ impl IdentifierIssuer {
     identifier
   }
 
+  // Issues the next identifier without associating it with an old one.
+  pub fn get_new_id(&mut self) -> String {
+    let identifier = self.prefix.to_string() + &self.counter.to_string();
+    self.counter += 1;
+    identifier
+  }
+
   pub fn get_existing_id(&self, old: &str) -> Option<String> {
     // return existing old identifier
     if let Some(existing) = self.existing.get(old) {
@@ -0,0 +1,424 @@
+// An in-memory dataset indexed for quad pattern matching.
+//
+// Every distinct term is stored once and quads are kept as keys of term ids in
+// six orderings (GSPO, GPOS, GOSP, SPOG, POSG and OSPG). Each of the sixteen
+// patterns of bound and wildcard positions is then a prefix of one of them, so
+// a lookup is a range scan over the matching quads only. Like `Dataset`, the
+// indexed dataset is a set: inserting a quad it already contains is a no-op.
+
+use crate::nquads::{Dataset, Graph, Object, Predicate, Quad, QuadRef, Subject};
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::slice;
+
+// positions in a quad key
+const S: usize = 0;
+const P: usize = 1;
+const O: usize = 2;
+const G: usize = 3;
+
+const ORDERS: [[usize; 4]; 6] = [
+  [G, S, P, O],
+  [G, P, O, S],
+  [G, O, S, P],
+  [S, P, O, G],
+  [P, O, S, G],
+  [O, S, P, G],
+];
+
+type Key = [usize; 4];
+
+#[derive(Clone, Debug)]
+struct Terms<T> {
+  ids: HashMap<T, usize>,
+  terms: Vec<T>,
+}
+
+impl<T: Clone + Eq + Hash> Terms<T> {
+  fn new() -> Terms<T> {
+    Terms {
+      ids: HashMap::new(),
+      terms: Vec::new(),
+    }
+  }
+
+  fn id(&self, term: &T) -> Option<usize> {
+    self.ids.get(term).copied()
+  }
+
+  fn intern(&mut self, term: T) -> usize {
+    if let Some(id) = self.id(&term) {
+      return id;
+    }
+    let id = self.terms.len();
+    self.ids.insert(term.clone(), id);
+    self.terms.push(term);
+    id
+  }
+}
+
+#[derive(Clone, Debug)]
+struct Index {
+  order: [usize; 4],
+  keys: BTreeSet<Key>,
+}
+
+impl Index {
+  // Reorders a quad key into the order of this index.
+  fn permute(&self, key: &Key) -> Key {
+    [
+      key[self.order[0]],
+      key[self.order[1]],
+      key[self.order[2]],
+      key[self.order[3]],
+    ]
+  }
+
+  // Restores a quad key from a key of this index.
+  fn restore(&self, permuted: &Key) -> Key {
+    let mut key = [0; 4];
+    for (i, position) in self.order.iter().enumerate() {
+      key[*position] = permuted[i];
+    }
+    key
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexedDataset {
+  subjects: Terms<Subject>,
+  predicates: Terms<Predicate>,
+  objects: Terms<Object>,
+  graphs: Terms<Graph>,
+  // quad keys in insertion order
+  quads: Vec<Key>,
+  indexes: Vec<Index>,
+}
+
+impl Default for IndexedDataset {
+  fn default() -> IndexedDataset {
+    IndexedDataset::new()
+  }
+}
+
+impl IndexedDataset {
+  pub fn new() -> IndexedDataset {
+    IndexedDataset {
+      subjects: Terms::new(),
+      predicates: Terms::new(),
+      objects: Terms::new(),
+      graphs: Terms::new(),
+      quads: Vec::new(),
+      indexes: ORDERS
+        .iter()
+        .map(|order| Index {
+          order: *order,
+          keys: BTreeSet::new(),
+        })
+        .collect(),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.quads.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.quads.is_empty()
+  }
+
+  // Adds the quad unless the dataset already contains it. Returns whether it
+  // was added.
+  pub fn insert(&mut self, quad: Quad) -> bool {
+    let key = [
+      self.subjects.intern(quad.subject),
+      self.predicates.intern(quad.predicate),
+      self.objects.intern(quad.object),
+      self.graphs.intern(quad.graph),
+    ];
+    if self.indexes[0]
+      .keys
+      .contains(&self.indexes[0].permute(&key))
+    {
+      return false;
+    }
+    for index in &mut self.indexes {
+      let permuted = index.permute(&key);
+      index.keys.insert(permuted);
+    }
+    self.quads.push(key);
+    true
+  }
+
+  pub fn contains(&self, quad: &Quad) -> bool {
+    self
+      .quads_matching(
+        Some(&quad.subject),
+        Some(&quad.predicate),
+        Some(&quad.object),
+        Some(&quad.graph),
+      )
+      .next()
+      .is_some()
+  }
+
+  // Returns the quads in insertion order.
+  pub fn quads(&self) -> Quads<'_> {
+    Quads {
+      dataset: self,
+      keys: self.quads.iter(),
+    }
+  }
+
+  // Returns the quads matching a pattern, where `None` matches any term. The
+  // order of the quads is unspecified.
+  pub fn quads_matching<'a>(
+    &'a self,
+    subject: Option<&Subject>,
+    predicate: Option<&Predicate>,
+    object: Option<&Object>,
+    graph: Option<&Graph>,
+  ) -> impl Iterator<Item = QuadRef<'a>> + 'a {
+    // look up the id of every bound term; a term that was never interned
+    // matches nothing
+    let ids = [
+      subject.map(|term| self.subjects.id(term)),
+      predicate.map(|term| self.predicates.id(term)),
+      object.map(|term| self.objects.id(term)),
+      graph.map(|term| self.graphs.id(term)),
+    ];
+    let unknown = ids.contains(&Some(None));
+    let bound: Vec<usize> = (0..4).filter(|position| ids[*position].is_some()).collect();
+
+    // the bound positions are a prefix of exactly one of the orders
+    let index = self
+      .indexes
+      .iter()
+      .find(|index| {
+        index.order[..bound.len()]
+          .iter()
+          .all(|position| bound.contains(position))
+      })
+      .unwrap();
+    let mut start = [0; 4];
+    let mut end = [usize::MAX; 4];
+    for (i, position) in index.order[..bound.len()].iter().enumerate() {
+      let id = ids[*position].unwrap().unwrap_or(0);
+      start[i] = id;
+      end[i] = id;
+    }
+
+    let keys = if unknown {
+      None
+    } else {
+      Some(index.keys.range(start..=end))
+    };
+    keys
+      .into_iter()
+      .flatten()
+      .map(move |permuted| self.quad_ref(&index.restore(permuted)))
+  }
+
+  // Returns the distinct graph names, including the default graph when it
+  // has quads, in the order they were first used.
+  pub fn graphs(&self) -> Vec<&Graph> {
+    // terms are only interned for inserted quads, so every graph has quads
+    self.graphs.terms.iter().collect()
+  }
+
+  // Returns the quads of a single graph as a `Dataset`, keeping their graph
+  // name. The order of the quads is unspecified.
+  pub fn graph(&self, graph: &Graph) -> Dataset {
+    Dataset {
+      quads: self
+        .quads_matching(None, None, None, Some(graph))
+        .map(|quad| to_quad(&quad))
+        .collect(),
+    }
+  }
+
+  pub fn to_dataset(&self) -> Dataset {
+    Dataset {
+      quads: self.quads().map(|quad| to_quad(&quad)).collect(),
+    }
+  }
+
+  fn quad_ref(&self, key: &Key) -> QuadRef<'_> {
+    QuadRef {
+      subject: &self.subjects.terms[key[S]],
+      predicate: &self.predicates.terms[key[P]],
+      object: &self.objects.terms[key[O]],
+      graph: &self.graphs.terms[key[G]],
+    }
+  }
+}
+
+fn to_quad(quad: &QuadRef) -> Quad {
+  Quad {
+    subject: quad.subject.clone(),
+    predicate: quad.predicate.clone(),
+    object: quad.object.clone(),
+    graph: quad.graph.clone(),
+  }
+}
+
+// Iterates over the quads of an `IndexedDataset` in insertion order.
+#[derive(Clone, Debug)]
+pub struct Quads<'a> {
+  dataset: &'a IndexedDataset,
+  keys: slice::Iter<'a, Key>,
+}
+
+impl<'a> Iterator for Quads<'a> {
+  type Item = QuadRef<'a>;
+
+  fn next(&mut self) -> Option<QuadRef<'a>> {
+    self.keys.next().map(|key| self.dataset.quad_ref(key))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.keys.size_hint()
+  }
+}
+
+impl From<Dataset> for IndexedDataset {
+  fn from(dataset: Dataset) -> IndexedDataset {
+    let mut indexed = IndexedDataset::new();
+    for quad in dataset.quads {
+      indexed.insert(quad);
+    }
+    indexed
+  }
+}
+
+impl From<&IndexedDataset> for Dataset {
+  fn from(indexed: &IndexedDataset) -> Dataset {
+    indexed.to_dataset()
+  }
+}
+
+impl<'a> IntoIterator for &'a IndexedDataset {
+  type Item = QuadRef<'a>;
+  type IntoIter = Quads<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.quads()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads::{parse_nquads, serialize_quad, QuadSerialize, Term, TermType};
+
+  const DATASET: &str = concat!(
+    "<http://example.org/a> <http://example.org/p> <http://example.org/b> .\n",
+    "<http://example.org/a> <http://example.org/p> \"a\" .\n",
+    "<http://example.org/a> <http://example.org/q> <http://example.org/b> <http://example.org/g> .\n",
+    "<http://example.org/b> <http://example.org/p> <http://example.org/a> <http://example.org/g> .\n",
+    "_:x <http://example.org/p> <<( <http://example.org/a> <http://example.org/p> \"a\" )>> _:g .\n",
+    "_:x <http://example.org/q> _:y _:g .\n",
+    "<http://example.org/a> <http://example.org/p> <http://example.org/b> .\n",
+  );
+
+  fn sorted<I, Q>(quads: I) -> Vec<String>
+  where
+    I: IntoIterator<Item = Q>,
+    Q: for<'q> QuadSerialize<'q>,
+  {
+    let mut lines: Vec<String> = quads
+      .into_iter()
+      .map(|quad| serialize_quad(&quad))
+      .collect();
+    lines.sort();
+    lines
+  }
+
+  #[test]
+  fn quad_patterns() {
+    let dataset = parse_nquads(DATASET);
+    let indexed = IndexedDataset::from(dataset.clone());
+
+    // every combination of bound and wildcard positions, bound to the terms
+    // of every quad, matches what a linear scan finds
+    for quad in &dataset.quads {
+      for mask in 0..16 {
+        let subject = Some(&quad.subject).filter(|_| mask & 1 != 0);
+        let predicate = Some(&quad.predicate).filter(|_| mask & 2 != 0);
+        let object = Some(&quad.object).filter(|_| mask & 4 != 0);
+        let graph = Some(&quad.graph).filter(|_| mask & 8 != 0);
+        let mut expected = dataset.clone();
+        expected.dedup();
+        expected.quads.retain(|q| {
+          subject.is_none_or(|s| *s == q.subject)
+            && predicate.is_none_or(|p| *p == q.predicate)
+            && object.is_none_or(|o| *o == q.object)
+            && graph.is_none_or(|g| *g == q.graph)
+        });
+        assert_eq!(
+          sorted(indexed.quads_matching(subject, predicate, object, graph)),
+          sorted(&expected.quads),
+          "mask {}",
+          mask
+        );
+      }
+    }
+
+    let mut unknown = Subject::new();
+    unknown.set_term_type(&TermType::NamedNode);
+    unknown.set_value("http://example.org/unknown");
+    assert_eq!(
+      indexed
+        .quads_matching(Some(&unknown), None, None, None)
+        .count(),
+      0
+    );
+    assert_eq!(indexed.quads_matching(None, None, None, None).count(), 6);
+  }
+
+  #[test]
+  fn set_semantics_and_conversion() {
+    let dataset = parse_nquads(DATASET);
+    let mut indexed = IndexedDataset::from(dataset.clone());
+    assert_eq!(indexed.len(), 6);
+    assert!(indexed.contains(&dataset.quads[0]));
+    assert!(!indexed.insert(dataset.quads[0].clone()));
+
+    // insertion order is kept, minus the duplicate
+    let mut expected = dataset.clone();
+    expected.dedup();
+    assert_eq!(Dataset::from(&indexed), expected);
+
+    // the indexed dataset is a quad source for the canonicalizer
+    assert_eq!(
+      crate::canonize_quads(&indexed, "URDNA2015"),
+      crate::canonize(&expected, "URDNA2015")
+    );
+
+    let mut quad = dataset.quads[1].clone();
+    quad.object.set_value("b");
+    assert!(!indexed.contains(&quad));
+    assert!(indexed.insert(quad.clone()));
+    assert!(indexed.contains(&quad));
+    assert_eq!(indexed.len(), 7);
+  }
+
+  #[test]
+  fn graphs() {
+    let indexed = IndexedDataset::from(parse_nquads(DATASET));
+    let graphs: Vec<String> = indexed.graphs().iter().map(|g| g.value.clone()).collect();
+    assert_eq!(graphs, vec!["@default", "http://example.org/g", "_:g"]);
+
+    let named = indexed.graphs()[1].clone();
+    let graph = indexed.graph(&named);
+    assert_eq!(
+      sorted(&graph.quads),
+      vec![
+        "<http://example.org/a> <http://example.org/q> <http://example.org/b> <http://example.org/g> .\n",
+        "<http://example.org/b> <http://example.org/p> <http://example.org/a> <http://example.org/g> .\n",
+      ]
+    );
+    assert!(graph.quads.iter().all(|quad| quad.graph == named));
+  }
+}
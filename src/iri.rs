@@ -0,0 +1,238 @@
+// IRI helpers shared by the parsers that accept relative IRIs.
+
+struct Parts<'a> {
+  scheme: Option<&'a str>,
+  authority: Option<&'a str>,
+  path: &'a str,
+  query: Option<&'a str>,
+  fragment: Option<&'a str>,
+}
+
+// https://tools.ietf.org/html/rfc3986#section-3.1
+pub fn has_scheme(iri: &str) -> bool {
+  scheme_length(iri).is_some()
+}
+
+// Resolves `reference` against the absolute IRI `base` following
+// https://tools.ietf.org/html/rfc3986#section-5.2.2
+pub fn resolve(base: &str, reference: &str) -> String {
+  let r = split(reference);
+  if r.scheme.is_some() {
+    return recompose(
+      r.scheme,
+      r.authority,
+      &remove_dot_segments(r.path),
+      r.query,
+      r.fragment,
+    );
+  }
+
+  let b = split(base);
+  if r.authority.is_some() {
+    return recompose(
+      b.scheme,
+      r.authority,
+      &remove_dot_segments(r.path),
+      r.query,
+      r.fragment,
+    );
+  }
+
+  if r.path.is_empty() {
+    let query = if r.query.is_some() { r.query } else { b.query };
+    return recompose(b.scheme, b.authority, b.path, query, r.fragment);
+  }
+
+  let path = if r.path.starts_with('/') {
+    remove_dot_segments(r.path)
+  } else {
+    remove_dot_segments(&merge(&b, r.path))
+  };
+  recompose(b.scheme, b.authority, &path, r.query, r.fragment)
+}
+
+fn scheme_length(iri: &str) -> Option<usize> {
+  let end = iri.find(':')?;
+  let scheme = &iri[..end];
+  let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+    && scheme
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+  if valid {
+    Some(end)
+  } else {
+    None
+  }
+}
+
+fn split(iri: &str) -> Parts<'_> {
+  let mut rest = iri;
+
+  let scheme = scheme_length(rest).map(|end| {
+    let scheme = &rest[..end];
+    rest = &rest[end + 1..];
+    scheme
+  });
+
+  let fragment = rest.find('#').map(|start| {
+    let fragment = &rest[start + 1..];
+    rest = &rest[..start];
+    fragment
+  });
+
+  let query = rest.find('?').map(|start| {
+    let query = &rest[start + 1..];
+    rest = &rest[..start];
+    query
+  });
+
+  let authority = if rest.starts_with("//") {
+    let end = rest[2..].find('/').map_or(rest.len(), |end| end + 2);
+    let authority = &rest[2..end];
+    rest = &rest[end..];
+    Some(authority)
+  } else {
+    None
+  };
+
+  Parts {
+    scheme,
+    authority,
+    path: rest,
+    query,
+    fragment,
+  }
+}
+
+// https://tools.ietf.org/html/rfc3986#section-5.2.3
+fn merge(base: &Parts, path: &str) -> String {
+  if base.authority.is_some() && base.path.is_empty() {
+    return format!("/{}", path);
+  }
+
+  match base.path.rfind('/') {
+    Some(end) => format!("{}{}", &base.path[..=end], path),
+    None => path.to_string(),
+  }
+}
+
+// https://tools.ietf.org/html/rfc3986#section-5.2.4
+fn remove_dot_segments(path: &str) -> String {
+  let mut input = path;
+  let mut output = String::with_capacity(path.len());
+
+  while !input.is_empty() {
+    if input.starts_with("../") {
+      input = &input[3..];
+    } else if input.starts_with("./") || input.starts_with("/./") {
+      input = &input[2..];
+    } else if input == "/." {
+      input = "/";
+    } else if input.starts_with("/../") {
+      input = &input[3..];
+      truncate_last_segment(&mut output);
+    } else if input == "/.." {
+      input = "/";
+      truncate_last_segment(&mut output);
+    } else if input == "." || input == ".." {
+      input = "";
+    } else {
+      // move the first path segment, and its leading "/", to the output
+      let start = if input.starts_with('/') { 1 } else { 0 };
+      let end = input[start..]
+        .find('/')
+        .map_or(input.len(), |end| end + start);
+      output.push_str(&input[..end]);
+      input = &input[end..];
+    }
+  }
+
+  output
+}
+
+fn truncate_last_segment(output: &mut String) {
+  let end = output.rfind('/').unwrap_or(0);
+  output.truncate(end);
+}
+
+fn recompose(
+  scheme: Option<&str>,
+  authority: Option<&str>,
+  path: &str,
+  query: Option<&str>,
+  fragment: Option<&str>,
+) -> String {
+  let mut iri = String::with_capacity(path.len() + 32);
+  if let Some(scheme) = scheme {
+    iri.push_str(scheme);
+    iri.push(':');
+  }
+  if let Some(authority) = authority {
+    iri.push_str("//");
+    iri.push_str(authority);
+  }
+  iri.push_str(path);
+  if let Some(query) = query {
+    iri.push('?');
+    iri.push_str(query);
+  }
+  if let Some(fragment) = fragment {
+    iri.push('#');
+    iri.push_str(fragment);
+  }
+  iri
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rfc3986_examples() {
+    // https://tools.ietf.org/html/rfc3986#section-5.4
+    let base = "http://a/b/c/d;p?q";
+    let examples = [
+      ("g:h", "g:h"),
+      ("g", "http://a/b/c/g"),
+      ("./g", "http://a/b/c/g"),
+      ("g/", "http://a/b/c/g/"),
+      ("/g", "http://a/g"),
+      ("//g", "http://g"),
+      ("?y", "http://a/b/c/d;p?y"),
+      ("g?y", "http://a/b/c/g?y"),
+      ("#s", "http://a/b/c/d;p?q#s"),
+      ("g#s", "http://a/b/c/g#s"),
+      ("g?y#s", "http://a/b/c/g?y#s"),
+      (";x", "http://a/b/c/;x"),
+      ("g;x", "http://a/b/c/g;x"),
+      ("", "http://a/b/c/d;p?q"),
+      (".", "http://a/b/c/"),
+      ("./", "http://a/b/c/"),
+      ("..", "http://a/b/"),
+      ("../", "http://a/b/"),
+      ("../g", "http://a/b/g"),
+      ("../..", "http://a/"),
+      ("../../g", "http://a/g"),
+      ("../../../g", "http://a/g"),
+      ("/./g", "http://a/g"),
+      ("/../g", "http://a/g"),
+      ("g.", "http://a/b/c/g."),
+      ("..g", "http://a/b/c/..g"),
+      ("./../g", "http://a/b/g"),
+      ("g/./h", "http://a/b/c/g/h"),
+      ("g/../h", "http://a/b/c/h"),
+      ("g;x=1/../y", "http://a/b/c/y"),
+    ];
+    for (reference, expected) in examples.iter() {
+      assert_eq!(resolve(base, reference), *expected, "{}", reference);
+    }
+  }
+
+  #[test]
+  fn schemes() {
+    assert!(has_scheme("http://example.org/"));
+    assert!(has_scheme("urn:uuid:1234"));
+    assert!(!has_scheme("example"));
+    assert!(!has_scheme("1http://example.org/"));
+  }
+}
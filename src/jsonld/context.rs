@@ -0,0 +1,912 @@
+use super::{DocumentLoader, JsonLdError};
+use crate::iri;
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// remote contexts may include each other; give up after this many
+const MAX_REMOTE_CONTEXTS: usize = 32;
+
+const KEYWORDS: [&str; 23] = [
+  "@base",
+  "@container",
+  "@context",
+  "@direction",
+  "@graph",
+  "@id",
+  "@import",
+  "@included",
+  "@index",
+  "@json",
+  "@language",
+  "@list",
+  "@nest",
+  "@none",
+  "@prefix",
+  "@propagate",
+  "@protected",
+  "@reverse",
+  "@set",
+  "@type",
+  "@value",
+  "@version",
+  "@vocab",
+];
+
+pub fn is_keyword(value: &str) -> bool {
+  KEYWORDS.contains(&value)
+}
+
+// Strings of the form `@` followed by letters are reserved for future
+// keywords and are ignored wherever a term or IRI is expected.
+pub fn looks_like_keyword(value: &str) -> bool {
+  value.len() > 1 && value.starts_with('@') && value[1..].bytes().all(|c| c.is_ascii_alphabetic())
+}
+
+// Returns the position of the first ':' after the first character, which
+// separates the prefix of a compact IRI from its suffix.
+fn prefix_colon(value: &str) -> Option<usize> {
+  value
+    .char_indices()
+    .skip(1)
+    .find(|(_, c)| *c == ':')
+    .map(|(i, _)| i)
+}
+
+pub fn is_absolute_iri(value: &str) -> bool {
+  iri::has_scheme(value)
+}
+
+// https://www.w3.org/TR/json-ld11-api/#dfn-active-context
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+  pub base: Option<String>,
+  pub original_base: Option<String>,
+  pub vocab: Option<String>,
+  pub default_language: Option<String>,
+  pub default_direction: Option<String>,
+  pub terms: HashMap<String, TermDefinition>,
+  // the context to revert to when a non-propagated context goes out of scope
+  pub previous: Option<Box<Context>>,
+}
+
+// https://www.w3.org/TR/json-ld11-api/#dfn-term-definition
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TermDefinition {
+  // `None` for a term explicitly mapped to null
+  pub iri: Option<String>,
+  pub prefix: bool,
+  pub protected: bool,
+  pub reverse: bool,
+  pub base_url: Option<String>,
+  pub context: Option<Value>,
+  pub containers: Vec<String>,
+  // `Some(None)` when the term resets the default direction or language
+  pub direction: Option<Option<String>>,
+  pub index: Option<String>,
+  pub language: Option<Option<String>>,
+  pub nest: Option<String>,
+  pub type_mapping: Option<String>,
+}
+
+impl TermDefinition {
+  pub fn has_container(&self, container: &str) -> bool {
+    self.containers.iter().any(|c| c == container)
+  }
+}
+
+// Flags of the context processing algorithm.
+#[derive(Clone, Copy)]
+pub struct Flags {
+  pub override_protected: bool,
+  pub propagate: bool,
+  pub validate_scoped: bool,
+}
+
+impl Default for Flags {
+  fn default() -> Flags {
+    Flags {
+      override_protected: false,
+      propagate: true,
+      validate_scoped: true,
+    }
+  }
+}
+
+impl Context {
+  pub fn new(base: Option<&str>) -> Context {
+    Context {
+      base: base.map(|base| base.to_string()),
+      original_base: base.map(|base| base.to_string()),
+      ..Context::default()
+    }
+  }
+
+  pub fn term(&self, term: &str) -> Option<&TermDefinition> {
+    self.terms.get(term)
+  }
+
+  // https://www.w3.org/TR/json-ld11-api/#iri-expansion
+  pub fn expand_iri(&self, value: &str, document_relative: bool, vocab: bool) -> Option<String> {
+    if is_keyword(value) {
+      return Some(value.to_string());
+    }
+    if looks_like_keyword(value) {
+      return None;
+    }
+
+    if let Some(definition) = self.terms.get(value) {
+      if let Some(iri) = &definition.iri {
+        if is_keyword(iri) {
+          return Some(iri.clone());
+        }
+      }
+      if vocab {
+        return definition.iri.clone();
+      }
+    }
+
+    if let Some(colon) = prefix_colon(value) {
+      let (prefix, suffix) = (&value[..colon], &value[colon + 1..]);
+      if prefix == "_" || suffix.starts_with("//") {
+        return Some(value.to_string());
+      }
+      if let Some(definition) = self.terms.get(prefix) {
+        if let (Some(iri), true) = (&definition.iri, definition.prefix) {
+          return Some(format!("{}{}", iri, suffix));
+        }
+      }
+      if is_absolute_iri(value) {
+        return Some(value.to_string());
+      }
+    }
+
+    if vocab {
+      if let Some(mapping) = &self.vocab {
+        return Some(format!("{}{}", mapping, value));
+      }
+    }
+    if document_relative {
+      if let Some(base) = &self.base {
+        return Some(iri::resolve(base, value));
+      }
+    }
+
+    Some(value.to_string())
+  }
+
+  fn has_protected_terms(&self) -> bool {
+    self.terms.values().any(|definition| definition.protected)
+  }
+}
+
+// https://www.w3.org/TR/json-ld11-api/#context-processing-algorithm
+pub fn process_context(
+  active: &Context,
+  local: &Value,
+  base_url: Option<&str>,
+  remote_contexts: &mut Vec<String>,
+  flags: Flags,
+  loader: &dyn DocumentLoader,
+) -> Result<Context, JsonLdError> {
+  let mut result = active.clone();
+  let mut propagate = flags.propagate;
+  if let Some(value) = local.get("@propagate") {
+    propagate = value.as_bool().ok_or_else(|| {
+      JsonLdError::new("invalid @propagate value", "@propagate must be a boolean")
+    })?;
+  }
+  if !propagate && result.previous.is_none() {
+    result.previous = Some(Box::new(active.clone()));
+  }
+
+  let contexts = match local {
+    Value::Array(contexts) => contexts.clone(),
+    context => vec![context.clone()],
+  };
+  for context in &contexts {
+    match context {
+      Value::Null => {
+        if !flags.override_protected && result.has_protected_terms() {
+          return Err(JsonLdError::new(
+            "invalid context nullification",
+            "a context with protected terms cannot be cleared",
+          ));
+        }
+        let previous = result;
+        result = Context::new(active.original_base.as_deref());
+        if !propagate {
+          result.previous = Some(Box::new(previous));
+        }
+      }
+      Value::String(reference) => {
+        let url = match base_url {
+          Some(base) => iri::resolve(base, reference),
+          None => reference.clone(),
+        };
+        if !flags.validate_scoped && remote_contexts.contains(&url) {
+          continue;
+        }
+        if remote_contexts.len() >= MAX_REMOTE_CONTEXTS {
+          return Err(JsonLdError::new(
+            "context overflow",
+            &format!("too many nested remote contexts loading {}", url),
+          ));
+        }
+        remote_contexts.push(url.clone());
+
+        let (document_url, context) = load_context(&url, loader)?;
+        result = process_context(
+          &result,
+          &context,
+          Some(&document_url),
+          &mut remote_contexts.clone(),
+          Flags {
+            validate_scoped: flags.validate_scoped,
+            ..Flags::default()
+          },
+          loader,
+        )?;
+      }
+      Value::Object(definitions) => {
+        result = process_definitions(
+          result,
+          definitions,
+          base_url,
+          remote_contexts,
+          flags,
+          loader,
+        )?;
+      }
+      _ => {
+        return Err(JsonLdError::new(
+          "invalid local context",
+          "a context must be null, a string or an object",
+        ))
+      }
+    }
+  }
+
+  Ok(result)
+}
+
+fn load_context(url: &str, loader: &dyn DocumentLoader) -> Result<(String, Value), JsonLdError> {
+  let document = loader.load_document(url).map_err(|e| {
+    JsonLdError::new(
+      "loading remote context failed",
+      &format!("{}: {}", url, e.message),
+    )
+  })?;
+  match document.document.get("@context") {
+    Some(context) => Ok((document.document_url, context.clone())),
+    None => Err(JsonLdError::new(
+      "invalid remote context",
+      &format!("{} does not contain an @context entry", url),
+    )),
+  }
+}
+
+// Steps 5.5 to 5.13 of the context processing algorithm, for a context
+// given as a map.
+fn process_definitions(
+  mut result: Context,
+  context: &Map<String, Value>,
+  base_url: Option<&str>,
+  remote_contexts: &mut Vec<String>,
+  flags: Flags,
+  loader: &dyn DocumentLoader,
+) -> Result<Context, JsonLdError> {
+  let mut context = context.clone();
+
+  if let Some(version) = context.get("@version") {
+    if version.as_f64() != Some(1.1) {
+      return Err(JsonLdError::new(
+        "invalid @version value",
+        "@version must be 1.1",
+      ));
+    }
+  }
+
+  if let Some(import) = context.get("@import").cloned() {
+    let reference = import
+      .as_str()
+      .ok_or_else(|| JsonLdError::new("invalid @import value", "@import must be a string"))?;
+    let url = match base_url {
+      Some(base) => iri::resolve(base, reference),
+      None => reference.to_string(),
+    };
+    let (_, imported) = load_context(&url, loader)?;
+    let imported = match imported {
+      Value::Object(imported) => imported,
+      _ => {
+        return Err(JsonLdError::new(
+          "invalid remote context",
+          &format!("{} must contain a single context object", url),
+        ))
+      }
+    };
+    if imported.contains_key("@import") {
+      return Err(JsonLdError::new(
+        "invalid context entry",
+        &format!("imported context {} cannot itself use @import", url),
+      ));
+    }
+    for (key, value) in imported {
+      context.entry(key).or_insert(value);
+    }
+  }
+
+  if let Some(base) = context.get("@base") {
+    if remote_contexts.is_empty() {
+      result.base = match base {
+        Value::Null => None,
+        Value::String(base) if is_absolute_iri(base) => Some(base.clone()),
+        Value::String(base) => match &result.base {
+          Some(current) => Some(iri::resolve(current, base)),
+          None => {
+            return Err(JsonLdError::new(
+              "invalid base IRI",
+              &format!("cannot resolve @base {} without a base IRI", base),
+            ))
+          }
+        },
+        _ => {
+          return Err(JsonLdError::new(
+            "invalid base IRI",
+            "@base must be a string",
+          ))
+        }
+      };
+    }
+  }
+
+  if let Some(vocab) = context.get("@vocab") {
+    result.vocab = match vocab {
+      Value::Null => None,
+      Value::String(vocab) => {
+        let expanded = result.expand_iri(vocab, true, true);
+        match expanded {
+          Some(expanded) if expanded.contains(':') => Some(expanded),
+          _ => {
+            return Err(JsonLdError::new(
+              "invalid vocab mapping",
+              &format!("{} is not an IRI", vocab),
+            ))
+          }
+        }
+      }
+      _ => {
+        return Err(JsonLdError::new(
+          "invalid vocab mapping",
+          "@vocab must be a string or null",
+        ))
+      }
+    };
+  }
+
+  if let Some(language) = context.get("@language") {
+    result.default_language = match language {
+      Value::Null => None,
+      Value::String(language) => Some(language.clone()),
+      _ => {
+        return Err(JsonLdError::new(
+          "invalid default language",
+          "@language must be a string or null",
+        ))
+      }
+    };
+  }
+
+  if let Some(direction) = context.get("@direction") {
+    result.default_direction = parse_direction(direction)?;
+  }
+
+  if let Some(propagate) = context.get("@propagate") {
+    if !propagate.is_boolean() {
+      return Err(JsonLdError::new(
+        "invalid @propagate value",
+        "@propagate must be a boolean",
+      ));
+    }
+  }
+
+  let protected = match context.get("@protected") {
+    None => false,
+    Some(Value::Bool(protected)) => *protected,
+    Some(_) => {
+      return Err(JsonLdError::new(
+        "invalid @protected value",
+        "@protected must be a boolean",
+      ))
+    }
+  };
+
+  let mut definer = Definer {
+    active: result,
+    local: &context,
+    defined: HashMap::new(),
+    base_url,
+    protected,
+    override_protected: flags.override_protected,
+    remote_contexts,
+    loader,
+  };
+  for term in context.keys() {
+    match term.as_str() {
+      "@base" | "@direction" | "@import" | "@language" | "@propagate" | "@protected"
+      | "@version" | "@vocab" => {}
+      _ => definer.define(term)?,
+    }
+  }
+
+  Ok(definer.active)
+}
+
+fn parse_direction(value: &Value) -> Result<Option<String>, JsonLdError> {
+  match value {
+    Value::Null => Ok(None),
+    Value::String(direction) if direction == "ltr" || direction == "rtl" => {
+      Ok(Some(direction.clone()))
+    }
+    _ => Err(JsonLdError::new(
+      "invalid base direction",
+      "@direction must be \"ltr\", \"rtl\" or null",
+    )),
+  }
+}
+
+// State for creating the term definitions of one local context.
+struct Definer<'a> {
+  active: Context,
+  local: &'a Map<String, Value>,
+  // true once a term is defined, false while its definition is in progress
+  defined: HashMap<String, bool>,
+  base_url: Option<&'a str>,
+  protected: bool,
+  override_protected: bool,
+  remote_contexts: &'a mut Vec<String>,
+  loader: &'a dyn DocumentLoader,
+}
+
+impl<'a> Definer<'a> {
+  // IRI expansion that first defines terms of the local context it depends on
+  fn expand_iri(
+    &mut self,
+    value: &str,
+    document_relative: bool,
+    vocab: bool,
+  ) -> Result<Option<String>, JsonLdError> {
+    if self.local.contains_key(value) && self.defined.get(value) != Some(&true) {
+      self.define(value)?;
+    }
+    if let Some(colon) = prefix_colon(value) {
+      let prefix = &value[..colon];
+      if self.local.contains_key(prefix) && self.defined.get(prefix) != Some(&true) {
+        self.define(prefix)?;
+      }
+    }
+
+    Ok(self.active.expand_iri(value, document_relative, vocab))
+  }
+
+  // https://www.w3.org/TR/json-ld11-api/#create-term-definition
+  fn define(&mut self, term: &str) -> Result<(), JsonLdError> {
+    match self.defined.get(term) {
+      Some(true) => return Ok(()),
+      Some(false) => {
+        return Err(JsonLdError::new(
+          "cyclic IRI mapping",
+          &format!("the definition of {} depends on itself", term),
+        ))
+      }
+      None => {}
+    }
+    if term.is_empty() {
+      return Err(JsonLdError::new(
+        "invalid term definition",
+        "terms cannot be empty strings",
+      ));
+    }
+    self.defined.insert(term.to_string(), false);
+
+    let value = self.local[term].clone();
+    if term == "@type" {
+      let valid = value.as_object().is_some_and(|map| {
+        !map.is_empty()
+          && map.iter().all(|(key, value)| match key.as_str() {
+            "@container" => value == "@set",
+            "@protected" => true,
+            _ => false,
+          })
+      });
+      if !valid {
+        return Err(JsonLdError::new(
+          "keyword redefinition",
+          "@type may only be given an @container of @set",
+        ));
+      }
+    } else if is_keyword(term) {
+      return Err(JsonLdError::new(
+        "keyword redefinition",
+        &format!("{} is a keyword", term),
+      ));
+    } else if looks_like_keyword(term) {
+      return Ok(());
+    }
+
+    let previous = self.active.terms.remove(term);
+
+    let (value, simple_term) = match value {
+      Value::Null => (map_with("@id", Value::Null), false),
+      Value::String(id) => (map_with("@id", Value::String(id)), true),
+      Value::Object(map) => (map, false),
+      _ => {
+        return Err(JsonLdError::new(
+          "invalid term definition",
+          &format!("the definition of {} must be a string or an object", term),
+        ))
+      }
+    };
+
+    let mut definition = TermDefinition {
+      protected: self.protected,
+      ..TermDefinition::default()
+    };
+
+    if let Some(protected) = value.get("@protected") {
+      definition.protected = protected.as_bool().ok_or_else(|| {
+        JsonLdError::new("invalid @protected value", "@protected must be a boolean")
+      })?;
+    }
+
+    if let Some(type_mapping) = value.get("@type") {
+      let type_mapping = type_mapping
+        .as_str()
+        .ok_or_else(|| JsonLdError::new("invalid type mapping", "@type must be a string"))?;
+      let expanded = self.expand_iri(type_mapping, false, true)?;
+      match expanded {
+        Some(expanded)
+          if matches!(expanded.as_str(), "@id" | "@json" | "@none" | "@vocab")
+            || is_absolute_iri(&expanded) =>
+        {
+          definition.type_mapping = Some(expanded)
+        }
+        _ => {
+          return Err(JsonLdError::new(
+            "invalid type mapping",
+            &format!("{} is not a valid type mapping", type_mapping),
+          ))
+        }
+      }
+    }
+
+    if let Some(reverse) = value.get("@reverse") {
+      if value.contains_key("@id") || value.contains_key("@nest") {
+        return Err(JsonLdError::new(
+          "invalid reverse property",
+          "@reverse cannot be combined with @id or @nest",
+        ));
+      }
+      let reverse = reverse
+        .as_str()
+        .ok_or_else(|| JsonLdError::new("invalid IRI mapping", "@reverse must be a string"))?;
+      if looks_like_keyword(reverse) {
+        return Ok(());
+      }
+      match self.expand_iri(reverse, false, true)? {
+        Some(iri) if iri.contains(':') => definition.iri = Some(iri),
+        _ => {
+          return Err(JsonLdError::new(
+            "invalid IRI mapping",
+            &format!("{} is not an IRI", reverse),
+          ))
+        }
+      }
+      if let Some(container) = value.get("@container") {
+        match container {
+          Value::Null => {}
+          Value::String(container) if container == "@set" || container == "@index" => {
+            definition.containers = vec![container.clone()];
+          }
+          _ => {
+            return Err(JsonLdError::new(
+              "invalid reverse property",
+              "reverse properties only allow @set or @index containers",
+            ))
+          }
+        }
+      }
+      definition.reverse = true;
+      return self.finish(term, definition, previous);
+    }
+
+    match value.get("@id") {
+      Some(id) if id.as_str() != Some(term) => match id {
+        Value::Null => {}
+        Value::String(id) => {
+          if !is_keyword(id) && looks_like_keyword(id) {
+            return Ok(());
+          }
+          let iri = match self.expand_iri(id, false, true)? {
+            Some(iri) if is_keyword(&iri) || iri.contains(':') => iri,
+            _ => {
+              return Err(JsonLdError::new(
+                "invalid IRI mapping",
+                &format!("{} is not an IRI, blank node or keyword", id),
+              ))
+            }
+          };
+          if iri == "@context" {
+            return Err(JsonLdError::new(
+              "invalid keyword alias",
+              "@context cannot be aliased",
+            ));
+          }
+
+          let inner_colon = term
+            .char_indices()
+            .any(|(i, c)| c == ':' && i > 0 && i + 1 < term.len());
+          if inner_colon || term.contains('/') {
+            self.defined.insert(term.to_string(), true);
+            if self.expand_iri(term, false, true)?.as_deref() != Some(iri.as_str()) {
+              return Err(JsonLdError::new(
+                "invalid IRI mapping",
+                &format!("{} does not expand to {}", term, iri),
+              ));
+            }
+          }
+          if !term.contains(':') && !term.contains('/') && simple_term {
+            definition.prefix = iri.starts_with("_:") || iri.ends_with(|c| ":/?#[]@".contains(c));
+          }
+          definition.iri = Some(iri);
+        }
+        _ => {
+          return Err(JsonLdError::new(
+            "invalid IRI mapping",
+            "@id must be a string or null",
+          ))
+        }
+      },
+      _ => {
+        definition.iri = Some(self.default_iri(term)?);
+      }
+    }
+
+    if let Some(container) = value.get("@container") {
+      definition.containers = parse_container(container)?;
+      if definition.has_container("@type") {
+        match definition.type_mapping.as_deref() {
+          None => definition.type_mapping = Some(String::from("@id")),
+          Some("@id") | Some("@vocab") => {}
+          Some(_) => {
+            return Err(JsonLdError::new(
+              "invalid type mapping",
+              "a @type container requires a type mapping of @id or @vocab",
+            ))
+          }
+        }
+      }
+    }
+
+    if let Some(index) = value.get("@index") {
+      let valid = definition.has_container("@index")
+        && index
+          .as_str()
+          .is_some_and(|index| !is_keyword(index) && !looks_like_keyword(index));
+      if !valid {
+        return Err(JsonLdError::new(
+          "invalid term definition",
+          "@index requires an @index container and a property name",
+        ));
+      }
+      definition.index = index.as_str().map(|index| index.to_string());
+    }
+
+    if let Some(scoped) = value.get("@context") {
+      let mut remote_contexts = self.remote_contexts.clone();
+      process_context(
+        &self.active,
+        scoped,
+        self.base_url,
+        &mut remote_contexts,
+        Flags {
+          override_protected: true,
+          propagate: true,
+          validate_scoped: false,
+        },
+        self.loader,
+      )
+      .map_err(|e| {
+        JsonLdError::new(
+          "invalid scoped context",
+          &format!("scoped context of {}: {}", term, e),
+        )
+      })?;
+      definition.context = Some(scoped.clone());
+      definition.base_url = self.base_url.map(|base| base.to_string());
+    }
+
+    if !value.contains_key("@type") {
+      if let Some(language) = value.get("@language") {
+        definition.language = Some(match language {
+          Value::Null => None,
+          Value::String(language) => Some(language.clone()),
+          _ => {
+            return Err(JsonLdError::new(
+              "invalid language mapping",
+              "@language must be a string or null",
+            ))
+          }
+        });
+      }
+      if let Some(direction) = value.get("@direction") {
+        definition.direction = Some(parse_direction(direction)?);
+      }
+    }
+
+    if let Some(nest) = value.get("@nest") {
+      match nest.as_str() {
+        Some(nest) if nest == "@nest" || !is_keyword(nest) => {
+          definition.nest = Some(nest.to_string())
+        }
+        _ => {
+          return Err(JsonLdError::new(
+            "invalid @nest value",
+            "@nest must be a term or @nest",
+          ))
+        }
+      }
+    }
+
+    if let Some(prefix) = value.get("@prefix") {
+      if term.contains(':') || term.contains('/') {
+        return Err(JsonLdError::new(
+          "invalid term definition",
+          "compact IRIs and IRIs cannot be used as prefixes",
+        ));
+      }
+      definition.prefix = prefix
+        .as_bool()
+        .ok_or_else(|| JsonLdError::new("invalid @prefix value", "@prefix must be a boolean"))?;
+      if definition.prefix && definition.iri.as_deref().is_some_and(is_keyword) {
+        return Err(JsonLdError::new(
+          "invalid term definition",
+          "keyword aliases cannot be prefixes",
+        ));
+      }
+    }
+
+    let allowed = [
+      "@id",
+      "@reverse",
+      "@container",
+      "@context",
+      "@direction",
+      "@index",
+      "@language",
+      "@nest",
+      "@prefix",
+      "@protected",
+      "@type",
+    ];
+    if let Some(key) = value.keys().find(|key| !allowed.contains(&key.as_str())) {
+      return Err(JsonLdError::new(
+        "invalid term definition",
+        &format!("unexpected {} in the definition of {}", key, term),
+      ));
+    }
+
+    self.finish(term, definition, previous)
+  }
+
+  // the IRI of a term definition without an @id (steps 15 to 18)
+  fn default_iri(&mut self, term: &str) -> Result<String, JsonLdError> {
+    if let Some(colon) = prefix_colon(term) {
+      let (prefix, suffix) = (&term[..colon], &term[colon + 1..]);
+      if self.local.contains_key(prefix) {
+        self.define(prefix)?;
+      }
+      if let Some(iri) = self.active.term(prefix).and_then(|d| d.iri.as_ref()) {
+        return Ok(format!("{}{}", iri, suffix));
+      }
+      return Ok(term.to_string());
+    }
+    if term.contains('/') {
+      return match self.expand_iri(term, false, true)? {
+        Some(iri) if is_absolute_iri(&iri) => Ok(iri),
+        _ => Err(JsonLdError::new(
+          "invalid IRI mapping",
+          &format!("{} does not expand to an IRI", term),
+        )),
+      };
+    }
+    if term == "@type" {
+      return Ok(term.to_string());
+    }
+    match &self.active.vocab {
+      Some(vocab) => Ok(format!("{}{}", vocab, term)),
+      None => Err(JsonLdError::new(
+        "invalid IRI mapping",
+        &format!("{} has no IRI and there is no @vocab", term),
+      )),
+    }
+  }
+
+  fn finish(
+    &mut self,
+    term: &str,
+    mut definition: TermDefinition,
+    previous: Option<TermDefinition>,
+  ) -> Result<(), JsonLdError> {
+    if let Some(previous) = previous {
+      if !self.override_protected && previous.protected {
+        definition.protected = true;
+        if definition != previous {
+          return Err(JsonLdError::new(
+            "protected term redefinition",
+            &format!("{} is protected", term),
+          ));
+        }
+        definition = previous;
+      }
+    }
+    self.active.terms.insert(term.to_string(), definition);
+    self.defined.insert(term.to_string(), true);
+    Ok(())
+  }
+}
+
+fn map_with(key: &str, value: Value) -> Map<String, Value> {
+  let mut map = Map::new();
+  map.insert(key.to_string(), value);
+  map
+}
+
+fn parse_container(container: &Value) -> Result<Vec<String>, JsonLdError> {
+  let invalid = || {
+    JsonLdError::new(
+      "invalid container mapping",
+      &format!("{} is not a valid container", container),
+    )
+  };
+  let containers: Vec<String> = match container {
+    Value::String(container) => vec![container.clone()],
+    Value::Array(containers) => containers
+      .iter()
+      .map(|c| c.as_str().map(|c| c.to_string()).ok_or_else(invalid))
+      .collect::<Result<_, _>>()?,
+    _ => return Err(invalid()),
+  };
+
+  let known = [
+    "@graph",
+    "@id",
+    "@index",
+    "@language",
+    "@list",
+    "@set",
+    "@type",
+  ];
+  if containers.iter().any(|c| !known.contains(&c.as_str())) {
+    return Err(invalid());
+  }
+  let has = |c: &str| containers.iter().any(|container| container == c);
+  let valid = match containers.len() {
+    0 => false,
+    1 => true,
+    _ if has("@list") => false,
+    _ if has("@graph") => {
+      containers
+        .iter()
+        .all(|c| matches!(c.as_str(), "@graph" | "@id" | "@index" | "@set"))
+        && !(has("@id") && has("@index"))
+    }
+    2 => has("@set"),
+    _ => false,
+  };
+  if !valid {
+    return Err(invalid());
+  }
+
+  Ok(containers)
+}
@@ -0,0 +1,718 @@
+use super::context::{is_keyword, process_context, Context, Flags};
+use super::{DocumentLoader, JsonLdError};
+
+use serde_json::{Map, Value};
+
+// https://www.w3.org/TR/json-ld11-api/#expansion-algorithm
+pub struct Expander<'a> {
+  pub loader: &'a dyn DocumentLoader,
+}
+
+impl<'a> Expander<'a> {
+  pub fn expand(
+    &self,
+    active: &Context,
+    active_property: Option<&str>,
+    element: &Value,
+    base_url: Option<&str>,
+    from_map: bool,
+  ) -> Result<Value, JsonLdError> {
+    let definition = active_property.and_then(|property| active.term(property));
+    let property_scoped = definition.and_then(|definition| definition.context.as_ref());
+
+    match element {
+      Value::Null => Ok(Value::Null),
+      Value::Array(items) => {
+        let mut result = Vec::new();
+        for item in items {
+          let mut expanded = self.expand(active, active_property, item, base_url, from_map)?;
+          if definition.is_some_and(|d| d.has_container("@list")) && expanded.is_array() {
+            expanded = object_with("@list", expanded);
+          }
+          match expanded {
+            Value::Array(expanded) => result.extend(expanded),
+            Value::Null => {}
+            expanded => result.push(expanded),
+          }
+        }
+        Ok(Value::Array(result))
+      }
+      Value::Object(element) => {
+        self.expand_object(active, active_property, element, base_url, from_map)
+      }
+      scalar => {
+        // free-floating scalars are dropped
+        if active_property.is_none() || active_property == Some("@graph") {
+          return Ok(Value::Null);
+        }
+        match property_scoped {
+          Some(scoped) => {
+            let base = definition.and_then(|d| d.base_url.as_deref());
+            let active = self.process(active, scoped, base, true, true)?;
+            Ok(expand_value(&active, active_property, scalar))
+          }
+          None => Ok(expand_value(active, active_property, scalar)),
+        }
+      }
+    }
+  }
+
+  fn process(
+    &self,
+    active: &Context,
+    local: &Value,
+    base_url: Option<&str>,
+    override_protected: bool,
+    propagate: bool,
+  ) -> Result<Context, JsonLdError> {
+    process_context(
+      active,
+      local,
+      base_url,
+      &mut Vec::new(),
+      Flags {
+        override_protected,
+        propagate,
+        validate_scoped: true,
+      },
+      self.loader,
+    )
+  }
+
+  fn expand_object(
+    &self,
+    active: &Context,
+    active_property: Option<&str>,
+    element: &Map<String, Value>,
+    base_url: Option<&str>,
+    from_map: bool,
+  ) -> Result<Value, JsonLdError> {
+    let definition = active_property.and_then(|property| active.term(property));
+    let mut active = active.clone();
+
+    // a non-propagated context does not apply to nested node objects
+    if let Some(previous) = &active.previous {
+      let is_value = element
+        .keys()
+        .any(|key| active.expand_iri(key, false, true).as_deref() == Some("@value"));
+      let is_reference = element.len() == 1
+        && element
+          .keys()
+          .all(|key| active.expand_iri(key, false, true).as_deref() == Some("@id"));
+      if !from_map && !is_value && !is_reference {
+        active = (**previous).clone();
+      }
+    }
+
+    if let Some(scoped) = definition.and_then(|definition| definition.context.as_ref()) {
+      let base = definition.and_then(|d| d.base_url.as_deref());
+      active = self.process(&active, scoped, base, true, true)?;
+    }
+
+    if let Some(local) = element.get("@context") {
+      active = self.process(&active, local, base_url, false, true)?;
+    }
+
+    // type-scoped contexts apply to this node, but not to nested nodes
+    let type_scoped = active.clone();
+    let mut input_type = None;
+    for (key, value) in element {
+      if active.expand_iri(key, false, true).as_deref() != Some("@type") {
+        continue;
+      }
+      let mut types: Vec<&str> = as_slice(value).iter().filter_map(|t| t.as_str()).collect();
+      types.sort_unstable();
+      for term in &types {
+        let scoped = type_scoped.term(term).and_then(|d| d.context.clone());
+        if let Some(scoped) = scoped {
+          let base = type_scoped.term(term).and_then(|d| d.base_url.clone());
+          active = self.process(&active, &scoped, base.as_deref(), false, false)?;
+        }
+      }
+      input_type = as_slice(value)
+        .last()
+        .and_then(|t| t.as_str())
+        .and_then(|t| active.expand_iri(t, false, true));
+    }
+
+    let mut result = Map::new();
+    self.expand_entries(
+      &active,
+      &type_scoped,
+      active_property,
+      element,
+      input_type.as_deref(),
+      base_url,
+      &mut result,
+    )?;
+
+    if let Some(value) = result.get("@value") {
+      let allowed = ["@direction", "@index", "@language", "@type", "@value"];
+      if result.keys().any(|key| !allowed.contains(&key.as_str())) {
+        return Err(JsonLdError::new(
+          "invalid value object",
+          "value objects can only contain @direction, @index, @language, @type and @value",
+        ));
+      }
+      let has_type = result.contains_key("@type");
+      if has_type && (result.contains_key("@language") || result.contains_key("@direction")) {
+        return Err(JsonLdError::new(
+          "invalid value object",
+          "a value object cannot have both @type and @language or @direction",
+        ));
+      }
+      if result.get("@type").and_then(|t| t.as_str()) == Some("@json") {
+        // JSON literals keep any value, including null
+      } else if value.is_null() {
+        return Ok(Value::Null);
+      } else if !value.is_string() && result.contains_key("@language") {
+        return Err(JsonLdError::new(
+          "invalid language-tagged value",
+          "only strings can have a language",
+        ));
+      } else if has_type
+        && !result["@type"]
+          .as_str()
+          .is_some_and(|t| t.contains(':') && !t.starts_with("_:"))
+      {
+        return Err(JsonLdError::new(
+          "invalid typed value",
+          &format!("{} is not a valid datatype", result["@type"]),
+        ));
+      }
+    } else if let Some(types) = result.get_mut("@type") {
+      if !types.is_array() {
+        *types = Value::Array(vec![types.take()]);
+      }
+    } else if result.contains_key("@set") || result.contains_key("@list") {
+      if result.len() > 2 || (result.len() == 2 && !result.contains_key("@index")) {
+        return Err(JsonLdError::new(
+          "invalid set or list object",
+          "@set and @list can only be combined with @index",
+        ));
+      }
+      if let Some(set) = result.remove("@set") {
+        return Ok(set);
+      }
+    }
+
+    if result.len() == 1 && result.contains_key("@language") {
+      return Ok(Value::Null);
+    }
+
+    if active_property.is_none() || active_property == Some("@graph") {
+      let drop = result.is_empty()
+        || result.contains_key("@value")
+        || result.contains_key("@list")
+        || (result.len() == 1 && result.contains_key("@id"));
+      if drop {
+        return Ok(Value::Null);
+      }
+    }
+
+    Ok(Value::Object(result))
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn expand_entries(
+    &self,
+    active: &Context,
+    type_scoped: &Context,
+    active_property: Option<&str>,
+    element: &Map<String, Value>,
+    input_type: Option<&str>,
+    base_url: Option<&str>,
+    result: &mut Map<String, Value>,
+  ) -> Result<(), JsonLdError> {
+    let mut nests = Vec::new();
+
+    for (key, value) in element {
+      if key == "@context" {
+        continue;
+      }
+      let expanded_property = match active.expand_iri(key, false, true) {
+        Some(property) if property.contains(':') || is_keyword(&property) => property,
+        _ => continue,
+      };
+
+      if is_keyword(&expanded_property) {
+        if active_property == Some("@reverse") {
+          return Err(JsonLdError::new(
+            "invalid reverse property map",
+            "a reverse map cannot contain keywords",
+          ));
+        }
+        if result.contains_key(&expanded_property)
+          && expanded_property != "@included"
+          && expanded_property != "@type"
+        {
+          return Err(JsonLdError::new(
+            "colliding keywords",
+            &format!("{} appears more than once", expanded_property),
+          ));
+        }
+
+        let expanded_value = match expanded_property.as_str() {
+          "@id" => match value {
+            Value::String(id) => Value::String(
+              active
+                .expand_iri(id, true, false)
+                .ok_or_else(|| JsonLdError::new("invalid @id value", "@id cannot be a keyword"))?,
+            ),
+            _ => {
+              return Err(JsonLdError::new(
+                "invalid @id value",
+                "@id must be a string",
+              ))
+            }
+          },
+          "@type" => {
+            let types: Vec<Value> = match value {
+              Value::String(_) => vec![value.clone()],
+              Value::Array(types) if types.iter().all(|t| t.is_string()) => types.clone(),
+              _ => {
+                return Err(JsonLdError::new(
+                  "invalid type value",
+                  "@type must be a string or an array of strings",
+                ))
+              }
+            };
+            let mut expanded: Vec<Value> = types
+              .iter()
+              .filter_map(|t| type_scoped.expand_iri(t.as_str().unwrap(), true, true))
+              .map(Value::String)
+              .collect();
+            if let Some(existing) = result.remove("@type") {
+              let mut merged = as_slice(&existing).to_vec();
+              merged.append(&mut expanded);
+              expanded = merged;
+            }
+            if value.is_string() && expanded.len() == 1 {
+              expanded.pop().unwrap()
+            } else {
+              Value::Array(expanded)
+            }
+          }
+          "@graph" => as_array(self.expand(active, Some("@graph"), value, base_url, false)?),
+          "@included" => {
+            let mut included = as_array(self.expand(active, None, value, base_url, false)?);
+            let valid = match &included {
+              Value::Array(items) => items.iter().all(|item| {
+                item.is_object() && item.get("@value").is_none() && item.get("@list").is_none()
+              }),
+              _ => false,
+            };
+            if !valid {
+              return Err(JsonLdError::new(
+                "invalid @included value",
+                "@included must only contain node objects",
+              ));
+            }
+            if let (Some(Value::Array(existing)), Value::Array(items)) =
+              (result.remove("@included"), &mut included)
+            {
+              let mut merged = existing;
+              merged.append(items);
+              included = Value::Array(merged);
+            }
+            included
+          }
+          "@value" => {
+            if input_type == Some("@json") {
+              value.clone()
+            } else if value.is_object() || value.is_array() {
+              return Err(JsonLdError::new(
+                "invalid value object value",
+                "@value must be a scalar or null",
+              ));
+            } else {
+              value.clone()
+            }
+          }
+          "@language" => match value {
+            Value::String(_) => value.clone(),
+            _ => {
+              return Err(JsonLdError::new(
+                "invalid language-tagged string",
+                "@language must be a string",
+              ))
+            }
+          },
+          "@direction" => match value.as_str() {
+            Some("ltr") | Some("rtl") => value.clone(),
+            _ => {
+              return Err(JsonLdError::new(
+                "invalid base direction",
+                "@direction must be \"ltr\" or \"rtl\"",
+              ))
+            }
+          },
+          "@index" => match value {
+            Value::String(_) => value.clone(),
+            _ => {
+              return Err(JsonLdError::new(
+                "invalid @index value",
+                "@index must be a string",
+              ))
+            }
+          },
+          "@list" => {
+            if active_property.is_none() || active_property == Some("@graph") {
+              continue;
+            }
+            as_array(self.expand(active, active_property, value, base_url, false)?)
+          }
+          "@set" => self.expand(active, active_property, value, base_url, false)?,
+          "@reverse" => {
+            if !value.is_object() {
+              return Err(JsonLdError::new(
+                "invalid @reverse value",
+                "@reverse must be an object",
+              ));
+            }
+            let expanded = self.expand(active, Some("@reverse"), value, base_url, false)?;
+            if let Value::Object(expanded) = expanded {
+              self.merge_reverse(expanded, result)?;
+            }
+            continue;
+          }
+          "@nest" => {
+            nests.push(key.clone());
+            continue;
+          }
+          _ => continue,
+        };
+
+        if expanded_value.is_null() && expanded_property == "@value" {
+          result.insert(expanded_property, Value::Null);
+        } else if !expanded_value.is_null() {
+          result.insert(expanded_property, expanded_value);
+        }
+        continue;
+      }
+
+      let definition = active.term(key);
+      let has_container = |c: &str| definition.is_some_and(|d| d.has_container(c));
+
+      let mut expanded = if definition.and_then(|d| d.type_mapping.as_deref()) == Some("@json") {
+        let mut json = object_with("@value", value.clone());
+        json["@type"] = Value::String(String::from("@json"));
+        json
+      } else if has_container("@language") && value.is_object() {
+        self.expand_language_map(active, definition, value)?
+      } else if (has_container("@index") || has_container("@type") || has_container("@id"))
+        && value.is_object()
+      {
+        self.expand_index_map(active, key, value, base_url)?
+      } else {
+        self.expand(active, Some(key), value, base_url, false)?
+      };
+
+      if expanded.is_null() {
+        continue;
+      }
+
+      if has_container("@list") && expanded.get("@list").is_none() {
+        expanded = object_with("@list", as_array(expanded));
+      }
+
+      if has_container("@graph") && !has_container("@id") && !has_container("@index") {
+        let graphs = as_slice(&expanded)
+          .iter()
+          .map(|value| object_with("@graph", as_array(value.clone())))
+          .collect();
+        expanded = Value::Array(graphs);
+      }
+
+      if definition.is_some_and(|d| d.reverse) {
+        let reverse = result
+          .entry("@reverse")
+          .or_insert_with(|| Value::Object(Map::new()));
+        for item in as_slice(&expanded) {
+          if item.get("@value").is_some() || item.get("@list").is_some() {
+            return Err(JsonLdError::new(
+              "invalid reverse property value",
+              "reverse properties cannot have values or lists",
+            ));
+          }
+          add_value(
+            reverse.as_object_mut().unwrap(),
+            &expanded_property,
+            item.clone(),
+          );
+        }
+      } else {
+        for item in as_slice(&expanded) {
+          add_value(result, &expanded_property, item.clone());
+        }
+        result
+          .entry(expanded_property)
+          .or_insert_with(|| Value::Array(Vec::new()));
+      }
+    }
+
+    nests.sort();
+    for nesting_key in nests {
+      for nested in as_slice(&element[&nesting_key]) {
+        let nested = match nested {
+          Value::Object(nested)
+            if !nested
+              .keys()
+              .any(|key| active.expand_iri(key, false, true).as_deref() == Some("@value")) =>
+          {
+            nested
+          }
+          _ => {
+            return Err(JsonLdError::new(
+              "invalid @nest value",
+              "@nest must contain node objects",
+            ))
+          }
+        };
+        self.expand_entries(
+          active,
+          type_scoped,
+          active_property,
+          nested,
+          input_type,
+          base_url,
+          result,
+        )?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn merge_reverse(
+    &self,
+    expanded: Map<String, Value>,
+    result: &mut Map<String, Value>,
+  ) -> Result<(), JsonLdError> {
+    for (property, values) in expanded {
+      if property == "@reverse" {
+        // a double reverse is a forward property
+        if let Value::Object(forward) = values {
+          for (property, values) in forward {
+            for item in as_slice(&values) {
+              add_value(result, &property, item.clone());
+            }
+          }
+        }
+        continue;
+      }
+      let reverse = result
+        .entry("@reverse")
+        .or_insert_with(|| Value::Object(Map::new()));
+      for item in as_slice(&values) {
+        if item.get("@value").is_some() || item.get("@list").is_some() {
+          return Err(JsonLdError::new(
+            "invalid reverse property value",
+            "reverse properties cannot have values or lists",
+          ));
+        }
+        add_value(reverse.as_object_mut().unwrap(), &property, item.clone());
+      }
+    }
+    Ok(())
+  }
+
+  // step 13.7 of the expansion algorithm
+  fn expand_language_map(
+    &self,
+    active: &Context,
+    definition: Option<&super::context::TermDefinition>,
+    value: &Value,
+  ) -> Result<Value, JsonLdError> {
+    let direction = match definition.and_then(|d| d.direction.clone()) {
+      Some(direction) => direction,
+      None => active.default_direction.clone(),
+    };
+
+    let mut expanded = Vec::new();
+    let map = value.as_object().unwrap();
+    let mut languages: Vec<&String> = map.keys().collect();
+    languages.sort();
+    for language in languages {
+      for item in as_slice(&map[language]) {
+        let string = match item {
+          Value::Null => continue,
+          Value::String(_) => item.clone(),
+          _ => {
+            return Err(JsonLdError::new(
+              "invalid language map value",
+              "language maps can only contain strings",
+            ))
+          }
+        };
+        let mut value = object_with("@value", string);
+        if active.expand_iri(language, false, true).as_deref() != Some("@none") {
+          value["@language"] = Value::String(language.clone());
+        }
+        if let Some(direction) = &direction {
+          value["@direction"] = Value::String(direction.clone());
+        }
+        expanded.push(value);
+      }
+    }
+
+    Ok(Value::Array(expanded))
+  }
+
+  // step 13.8 of the expansion algorithm, for @index, @id and @type maps
+  fn expand_index_map(
+    &self,
+    active: &Context,
+    key: &str,
+    value: &Value,
+    base_url: Option<&str>,
+  ) -> Result<Value, JsonLdError> {
+    let definition = active.term(key).unwrap();
+    let index_key = definition.index.as_deref().unwrap_or("@index");
+    let map = value.as_object().unwrap();
+    let mut indexes: Vec<&String> = map.keys().collect();
+    indexes.sort();
+
+    let mut expanded = Vec::new();
+    for index in indexes {
+      let mut map_context = if definition.has_container("@id") || definition.has_container("@type")
+      {
+        active.previous.as_deref().unwrap_or(active).clone()
+      } else {
+        active.clone()
+      };
+      if definition.has_container("@type") {
+        if let Some(scoped) = map_context.term(index).and_then(|d| d.context.clone()) {
+          let base = map_context.term(index).and_then(|d| d.base_url.clone());
+          map_context = self.process(&map_context, &scoped, base.as_deref(), false, true)?;
+        }
+      }
+
+      let expanded_index = active.expand_iri(index, false, true);
+      let is_none = expanded_index.as_deref() == Some("@none");
+      let items = as_array(map[index].clone());
+      let items = self.expand(&map_context, Some(key), &items, base_url, true)?;
+
+      for item in as_slice(&items) {
+        let mut item = item.clone();
+        if definition.has_container("@graph") && item.get("@graph").is_none() {
+          item = object_with("@graph", Value::Array(vec![item]));
+        }
+        if definition.has_container("@index") && index_key != "@index" && !is_none {
+          let re_expanded = expand_value(active, Some(index_key), &Value::String(index.clone()));
+          let property = active
+            .expand_iri(index_key, false, true)
+            .unwrap_or_default();
+          let object = item.as_object_mut().unwrap();
+          if object.contains_key("@value") {
+            return Err(JsonLdError::new(
+              "invalid value object",
+              "property-valued indexes cannot be used with value objects",
+            ));
+          }
+          let mut values = vec![re_expanded];
+          if let Some(existing) = object.remove(&property) {
+            values.extend(as_slice(&existing).iter().cloned());
+          }
+          object.insert(property, Value::Array(values));
+        } else if definition.has_container("@index") && item.get("@index").is_none() && !is_none {
+          item["@index"] = Value::String(index.clone());
+        } else if definition.has_container("@id") && item.get("@id").is_none() && !is_none {
+          let id = active.expand_iri(index, true, false).unwrap_or_default();
+          item["@id"] = Value::String(id);
+        } else if definition.has_container("@type") && !is_none {
+          let mut types = vec![Value::String(expanded_index.clone().unwrap_or_default())];
+          if let Some(existing) = item.get("@type") {
+            types.extend(as_slice(existing).iter().cloned());
+          }
+          item["@type"] = Value::Array(types);
+        }
+        expanded.push(item);
+      }
+    }
+
+    Ok(Value::Array(expanded))
+  }
+}
+
+// https://www.w3.org/TR/json-ld11-api/#value-expansion
+fn expand_value(active: &Context, active_property: Option<&str>, value: &Value) -> Value {
+  let definition = active_property.and_then(|property| active.term(property));
+  let type_mapping = definition.and_then(|d| d.type_mapping.as_deref());
+
+  if let Value::String(string) = value {
+    match type_mapping {
+      Some("@id") => {
+        let id = active.expand_iri(string, true, false);
+        return object_with("@id", id.map_or(Value::Null, Value::String));
+      }
+      Some("@vocab") => {
+        let id = active.expand_iri(string, true, true);
+        return object_with("@id", id.map_or(Value::Null, Value::String));
+      }
+      _ => {}
+    }
+  }
+
+  let mut result = object_with("@value", value.clone());
+  match type_mapping {
+    Some("@id") | Some("@vocab") | Some("@none") => {}
+    Some(datatype) => {
+      result["@type"] = Value::String(datatype.to_string());
+      return result;
+    }
+    None => {}
+  }
+
+  if value.is_string() {
+    let language = match definition.and_then(|d| d.language.clone()) {
+      Some(language) => language,
+      None => active.default_language.clone(),
+    };
+    let direction = match definition.and_then(|d| d.direction.clone()) {
+      Some(direction) => direction,
+      None => active.default_direction.clone(),
+    };
+    if let Some(language) = language {
+      result["@language"] = Value::String(language);
+    }
+    if let Some(direction) = direction {
+      result["@direction"] = Value::String(direction);
+    }
+  }
+
+  result
+}
+
+pub fn object_with(key: &str, value: Value) -> Value {
+  let mut map = Map::new();
+  map.insert(key.to_string(), value);
+  Value::Object(map)
+}
+
+// Returns the items of an array, or the value itself as a single item.
+pub fn as_slice(value: &Value) -> &[Value] {
+  match value {
+    Value::Array(items) => items,
+    value => std::slice::from_ref(value),
+  }
+}
+
+pub fn as_array(value: Value) -> Value {
+  match value {
+    Value::Array(_) => value,
+    Value::Null => Value::Array(Vec::new()),
+    value => Value::Array(vec![value]),
+  }
+}
+
+// Appends `value` to the array at `key`, creating the array if needed.
+pub fn add_value(map: &mut Map<String, Value>, key: &str, value: Value) {
+  let entry = map
+    .entry(key.to_string())
+    .or_insert_with(|| Value::Array(Vec::new()));
+  if !entry.is_array() {
+    *entry = Value::Array(vec![entry.take()]);
+  }
+  entry.as_array_mut().unwrap().push(value);
+}
@@ -0,0 +1,371 @@
+// JSON-LD 1.1 to RDF conversion, following the expansion and RDF
+// serialization algorithms of https://www.w3.org/TR/json-ld11-api/. Remote
+// contexts are only ever fetched through a `DocumentLoader`, so conversion can
+// run fully offline against a `ContextCache`.
+
+mod context;
+mod expansion;
+mod to_rdf;
+
+use crate::nquads::Dataset;
+
+use context::{process_context, Context, Flags};
+use expansion::{as_array, Expander};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// An error raised while processing JSON-LD. `code` is one of the error codes
+// defined by the JSON-LD API, e.g. "invalid IRI mapping".
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonLdError {
+  pub code: &'static str,
+  pub message: String,
+}
+
+impl JsonLdError {
+  pub fn new(code: &'static str, message: &str) -> JsonLdError {
+    JsonLdError {
+      code,
+      message: message.to_string(),
+    }
+  }
+}
+
+impl fmt::Display for JsonLdError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: {}", self.code, self.message)
+  }
+}
+
+impl Error for JsonLdError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemoteDocument {
+  // the final URL of the document, after any redirects
+  pub document_url: String,
+  pub document: Value,
+}
+
+// Loads the remote contexts referenced by a document.
+pub trait DocumentLoader {
+  fn load_document(&self, url: &str) -> Result<RemoteDocument, JsonLdError>;
+}
+
+// A document loader serving a fixed set of preloaded documents, for running
+// without network access. Loading any other URL fails. No contexts are bundled
+// with this crate, not even the credentials v1 and v2 contexts; callers
+// preload the ones their documents reference.
+#[derive(Clone, Debug, Default)]
+pub struct ContextCache {
+  documents: HashMap<String, Value>,
+}
+
+impl ContextCache {
+  pub fn new() -> ContextCache {
+    ContextCache::default()
+  }
+
+  // Creates a cache from a static table of URLs and documents, typically
+  // bundled with `include_str!`:
+  //
+  //   static CONTEXTS: &[(&str, &str)] = &[(
+  //     "https://www.w3.org/2018/credentials/v1",
+  //     include_str!("contexts/credentials-v1.jsonld"),
+  //   )];
+  //   let cache = ContextCache::preloaded(CONTEXTS)?;
+  pub fn preloaded(documents: &[(&str, &str)]) -> Result<ContextCache, JsonLdError> {
+    let mut cache = ContextCache::new();
+    for (url, document) in documents {
+      cache.insert_str(url, document)?;
+    }
+    Ok(cache)
+  }
+
+  pub fn insert(&mut self, url: &str, document: Value) {
+    self.documents.insert(url.to_string(), document);
+  }
+
+  // Parses and adds a document, typically one bundled with `include_str!`.
+  pub fn insert_str(&mut self, url: &str, document: &str) -> Result<(), JsonLdError> {
+    let document = serde_json::from_str(document).map_err(|e| {
+      JsonLdError::new(
+        "loading document failed",
+        &format!("{} is not valid JSON: {}", url, e),
+      )
+    })?;
+    self.insert(url, document);
+    Ok(())
+  }
+}
+
+impl DocumentLoader for ContextCache {
+  fn load_document(&self, url: &str) -> Result<RemoteDocument, JsonLdError> {
+    match self.documents.get(url) {
+      Some(document) => Ok(RemoteDocument {
+        document_url: url.to_string(),
+        document: document.clone(),
+      }),
+      None => Err(JsonLdError::new(
+        "loading document failed",
+        &format!("{} is not in the context cache", url),
+      )),
+    }
+  }
+}
+
+// https://www.w3.org/TR/json-ld11-api/#dom-jsonldoptions-rdfdirection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RdfDirection {
+  I18nDatatype,
+  CompoundLiteral,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsonLdOptions {
+  // the IRI relative IRIs in the document are resolved against
+  pub base: Option<String>,
+  // a context applied before the document's own contexts
+  pub expand_context: Option<Value>,
+  // keep triples whose predicate is a blank node
+  pub produce_generalized_rdf: bool,
+  // how to express the base direction of strings; dropped when unset
+  pub rdf_direction: Option<RdfDirection>,
+}
+
+// Expands a JSON-LD document, returning the expanded form as an array.
+pub fn expand(
+  input: &Value,
+  options: &JsonLdOptions,
+  loader: &dyn DocumentLoader,
+) -> Result<Value, JsonLdError> {
+  let base = options.base.as_deref();
+  let mut active = Context::new(base);
+  if let Some(expand_context) = &options.expand_context {
+    let local = expand_context.get("@context").unwrap_or(expand_context);
+    active = process_context(
+      &active,
+      local,
+      base,
+      &mut Vec::new(),
+      Flags::default(),
+      loader,
+    )?;
+  }
+
+  let expander = Expander { loader };
+  let expanded = expander.expand(&active, None, input, base, false)?;
+  let expanded = match expanded {
+    Value::Object(mut map) if map.len() == 1 && map.contains_key("@graph") => {
+      map.remove("@graph").unwrap()
+    }
+    expanded => expanded,
+  };
+
+  Ok(as_array(expanded))
+}
+
+// Converts a JSON-LD document to a dataset.
+pub fn to_rdf(
+  input: &Value,
+  options: &JsonLdOptions,
+  loader: &dyn DocumentLoader,
+) -> Result<Dataset, JsonLdError> {
+  let expanded = expand(input, options, loader)?;
+  to_rdf::Converter::new(options).convert(&expanded)
+}
+
+pub fn to_rdf_str(
+  input: &str,
+  options: &JsonLdOptions,
+  loader: &dyn DocumentLoader,
+) -> Result<Dataset, JsonLdError> {
+  let input = serde_json::from_str(input).map_err(|e| {
+    JsonLdError::new(
+      "loading document failed",
+      &format!("the document is not valid JSON: {}", e),
+    )
+  })?;
+  to_rdf(&input, options, loader)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads;
+  use serde_json::json;
+
+  fn assert_to_rdf(input: Value, expected: &str) {
+    let dataset = to_rdf(&input, &JsonLdOptions::default(), &ContextCache::new()).unwrap();
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      crate::canonize(&nquads::parse_nquads(expected), "URDNA2015").unwrap()
+    );
+  }
+
+  #[test]
+  fn preloaded_context_cache() {
+    let cache = ContextCache::preloaded(&[
+      (
+        "https://example.org/a",
+        r#"{"@context": {"a": "https://example.org/a#"}}"#,
+      ),
+      (
+        "https://example.org/b",
+        r#"{"@context": {"b": "https://example.org/b#"}}"#,
+      ),
+    ])
+    .unwrap();
+    let input = json!({
+      "@context": ["https://example.org/a", "https://example.org/b"],
+      "a": "x",
+      "b": "y"
+    });
+    let dataset = to_rdf(&input, &JsonLdOptions::default(), &cache).unwrap();
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      "_:c14n0 <https://example.org/a#> \"x\" .\n_:c14n0 <https://example.org/b#> \"y\" .\n"
+    );
+    assert!(cache.load_document("https://example.org/c").is_err());
+
+    let error = ContextCache::preloaded(&[("https://example.org/a", "{")]).unwrap_err();
+    assert_eq!(error.code, "loading document failed");
+  }
+
+  #[test]
+  fn credential_with_cached_context() {
+    let mut cache = ContextCache::new();
+    cache
+      .insert_str(
+        "https://example.org/credentials/v1",
+        r#"{
+          "@context": {
+            "@version": 1.1,
+            "@protected": true,
+            "id": "@id",
+            "type": "@type",
+            "VerifiableCredential": {
+              "@id": "https://example.org/cred#VerifiableCredential",
+              "@context": {
+                "@protected": true,
+                "cred": "https://example.org/cred#",
+                "issuer": {"@id": "cred:issuer", "@type": "@id"},
+                "credentialSubject": {"@id": "cred:credentialSubject", "@type": "@id"},
+                "proof": {"@id": "cred:proof", "@type": "@id", "@container": "@graph"}
+              }
+            }
+          }
+        }"#,
+      )
+      .unwrap();
+    let input = json!({
+      "@context": [
+        "https://example.org/credentials/v1",
+        {"name": "https://schema.org/name"}
+      ],
+      "id": "urn:uuid:1",
+      "type": "VerifiableCredential",
+      "issuer": "https://issuer.example/",
+      "credentialSubject": {"id": "did:example:alice", "name": "Alice"},
+      "proof": {"type": "https://example.org/Proof", "https://example.org/created": "2020"}
+    });
+    let dataset = to_rdf(&input, &JsonLdOptions::default(), &cache).unwrap();
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      crate::canonize(
+        &nquads::parse_nquads(
+          "<urn:uuid:1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/cred#VerifiableCredential> .\n\
+<urn:uuid:1> <https://example.org/cred#issuer> <https://issuer.example/> .\n\
+<urn:uuid:1> <https://example.org/cred#credentialSubject> <did:example:alice> .\n\
+<did:example:alice> <https://schema.org/name> \"Alice\" .\n\
+<urn:uuid:1> <https://example.org/cred#proof> _:g .\n\
+_:p <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://example.org/Proof> _:g .\n\
+_:p <https://example.org/created> \"2020\" _:g .\n"
+        ),
+        "URDNA2015"
+      )
+      .unwrap()
+    );
+
+    // protected terms cannot be redefined
+    let redefined = json!({
+      "@context": ["https://example.org/credentials/v1", {"VerifiableCredential": "https://evil.example/"}],
+      "type": "VerifiableCredential"
+    });
+    let error = to_rdf(&redefined, &JsonLdOptions::default(), &cache).unwrap_err();
+    assert_eq!(error.code, "protected term redefinition");
+  }
+
+  #[test]
+  fn literals_and_lists() {
+    assert_to_rdf(
+      json!({
+        "@context": {
+          "@vocab": "http://example.org/",
+          "@language": "en",
+          "plain": {"@language": null}
+        },
+        "@id": "http://example.org/s",
+        "int": 5,
+        "double": 1.5,
+        "bool": true,
+        "text": "hello",
+        "plain": "x",
+        "list": {"@list": [1, "a"]}
+      }),
+      "<http://example.org/s> <http://example.org/int> \"5\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/double> \"1.5E0\"^^<http://www.w3.org/2001/XMLSchema#double> .\n\
+<http://example.org/s> <http://example.org/bool> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .\n\
+<http://example.org/s> <http://example.org/text> \"hello\"@en .\n\
+<http://example.org/s> <http://example.org/plain> \"x\" .\n\
+<http://example.org/s> <http://example.org/list> _:l0 .\n\
+_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:l1 .\n\
+_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"a\"@en .\n\
+_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n",
+    );
+  }
+
+  #[test]
+  fn negative_zero() {
+    assert_to_rdf(
+      json!({
+        "@context": {"@vocab": "http://example.org/"},
+        "@id": "http://example.org/s",
+        "int": -0.0,
+        "double": {"@value": -0.0, "@type": "http://www.w3.org/2001/XMLSchema#double"}
+      }),
+      "<http://example.org/s> <http://example.org/int> \"0\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/double> \"0.0E0\"^^<http://www.w3.org/2001/XMLSchema#double> .\n",
+    );
+  }
+
+  #[test]
+  fn keyword_like_terms() {
+    // only "@" followed by letters has the form of a keyword; "@context2" and
+    // "@foo.bar" are ordinary terms, as in expansion test 0119 of the suite
+    assert_to_rdf(
+      json!({
+        "@context": {
+          "@vocab": "http://example.org/",
+          "@ignoreMe": "http://example.org/ignored",
+          "@foo.bar": "http://example.org/foo.bar"
+        },
+        "@id": "http://example.org/s",
+        "@ignoreMe": "x",
+        "@context2": "y",
+        "@foo.bar": "z"
+      }),
+      "<http://example.org/s> <http://example.org/@context2> \"y\" .\n\
+<http://example.org/s> <http://example.org/foo.bar> \"z\" .\n",
+    );
+  }
+
+  #[test]
+  fn unknown_remote_context() {
+    let input = json!({"@context": "https://example.org/missing", "@id": "urn:x"});
+    let error = to_rdf(&input, &JsonLdOptions::default(), &ContextCache::new()).unwrap_err();
+    assert_eq!(error.code, "loading remote context failed");
+  }
+}
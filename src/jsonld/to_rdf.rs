@@ -0,0 +1,530 @@
+use super::context::{is_absolute_iri, is_keyword};
+use super::expansion::{add_value, as_slice, object_with};
+use super::{JsonLdError, JsonLdOptions, RdfDirection};
+use crate::identifier_issuer::IdentifierIssuer;
+use crate::language_tag;
+use crate::literal::{canonical_lexical_form, XSD_BOOLEAN, XSD_DOUBLE, XSD_INTEGER};
+use crate::nquads::{
+  Dataset, Graph, Object, Predicate, Quad, Subject, Term, TermType, RDF_FIRST, RDF_LANGSTRING,
+  RDF_NIL, RDF_REST, RDF_TYPE, XSD_STRING,
+};
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+const RDF_VALUE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#value";
+const RDF_LANGUAGE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#language";
+const RDF_DIRECTION: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#direction";
+const I18N: &str = "https://www.w3.org/ns/i18n#";
+
+// graph name -> node identifier -> node object
+type NodeMap = BTreeMap<String, BTreeMap<String, Map<String, Value>>>;
+
+enum ActiveSubject {
+  None,
+  Id(String),
+  // the node referencing the current element through a reverse property
+  Reverse(Value),
+}
+
+// Converts expanded JSON-LD to a dataset by way of a node map. Blank nodes
+// are relabeled `_:b0`, `_:b1`, ... in document order.
+pub struct Converter<'a> {
+  options: &'a JsonLdOptions,
+  issuer: IdentifierIssuer,
+  node_map: NodeMap,
+}
+
+impl<'a> Converter<'a> {
+  pub fn new(options: &'a JsonLdOptions) -> Converter<'a> {
+    Converter {
+      options,
+      issuer: IdentifierIssuer::new("_:b"),
+      node_map: NodeMap::new(),
+    }
+  }
+
+  pub fn convert(mut self, expanded: &Value) -> Result<Dataset, JsonLdError> {
+    self
+      .node_map
+      .insert(String::from("@default"), BTreeMap::new());
+    self.generate(expanded, "@default", &ActiveSubject::None, None, None)?;
+
+    let node_map = std::mem::take(&mut self.node_map);
+    let mut quads = Vec::new();
+    for (graph_name, graph) in &node_map {
+      let graph_term = match graph_name.as_str() {
+        "@default" => Graph {
+          term_type: TermType::DefaultGraph,
+          value: String::from("@default"),
+        },
+        name => match node_type(name) {
+          Some(term_type) => Graph {
+            term_type,
+            value: name.to_string(),
+          },
+          None => continue,
+        },
+      };
+
+      for (id, node) in graph {
+        let subject = match node_type(id) {
+          Some(term_type) => Subject {
+            term_type,
+            value: id.clone(),
+          },
+          None => continue,
+        };
+
+        for (property, values) in node {
+          let mut triples = Vec::new();
+          if property == "@type" {
+            for t in as_slice(values).iter().filter_map(|t| t.as_str()) {
+              if let Some(term_type) = node_type(t) {
+                triples.push((
+                  subject.clone(),
+                  named_predicate(RDF_TYPE),
+                  node_term(term_type, t),
+                ));
+              }
+            }
+          } else if is_keyword(property) {
+            continue;
+          } else {
+            let predicate = match node_type(property) {
+              Some(TermType::BlankNode) if !self.options.produce_generalized_rdf => continue,
+              Some(term_type) => Predicate {
+                term_type,
+                value: property.clone(),
+              },
+              None => continue,
+            };
+            for item in as_slice(values) {
+              let mut list_triples = Vec::new();
+              if let Some(object) = self.object_to_rdf(item, &mut list_triples) {
+                triples.push((subject.clone(), predicate.clone(), object));
+              }
+              triples.append(&mut list_triples);
+            }
+          }
+
+          for (subject, predicate, object) in triples {
+            quads.push(Quad {
+              subject,
+              predicate,
+              object,
+              graph: graph_term.clone(),
+            });
+          }
+        }
+      }
+    }
+
+    Ok(Dataset { quads })
+  }
+
+  // https://www.w3.org/TR/json-ld11-api/#node-map-generation
+  fn generate(
+    &mut self,
+    element: &Value,
+    graph: &str,
+    subject: &ActiveSubject,
+    property: Option<&str>,
+    mut list: Option<&mut Vec<Value>>,
+  ) -> Result<(), JsonLdError> {
+    let element = match element {
+      Value::Array(items) => {
+        for item in items {
+          self.generate(item, graph, subject, property, list.as_deref_mut())?;
+        }
+        return Ok(());
+      }
+      Value::Object(element) => element,
+      _ => return Ok(()),
+    };
+
+    let mut element = element.clone();
+    if let Some(types) = element.get_mut("@type") {
+      for t in types.as_array_mut().into_iter().flatten() {
+        if let Some(id) = t.as_str().filter(|id| id.starts_with("_:")) {
+          *t = Value::String(self.issuer.get_id(id));
+        }
+      }
+    }
+
+    if element.contains_key("@value") {
+      match list {
+        Some(list) => list.push(Value::Object(element)),
+        None => self.add_to_subject(graph, subject, property, Value::Object(element), true),
+      }
+      return Ok(());
+    }
+
+    if let Some(items) = element.get("@list") {
+      let mut result = Vec::new();
+      self.generate(items, graph, subject, property, Some(&mut result))?;
+      let result = object_with("@list", Value::Array(result));
+      match list {
+        Some(list) => list.push(result),
+        None => self.add_to_subject(graph, subject, property, result, false),
+      }
+      return Ok(());
+    }
+
+    let id = match element.remove("@id").as_ref().and_then(|id| id.as_str()) {
+      Some(id) if id.starts_with("_:") => self.issuer.get_id(id),
+      Some(id) => id.to_string(),
+      None => self.issuer.get_new_id(),
+    };
+    self.node(graph, &id);
+
+    match subject {
+      ActiveSubject::Reverse(referencing) => {
+        let node = self.node(graph, &id);
+        add_unique(node, property.unwrap(), referencing.clone());
+      }
+      _ => {
+        if property.is_some() {
+          let reference = object_with("@id", Value::String(id.clone()));
+          match list {
+            Some(list) => list.push(reference),
+            None => self.add_to_subject(graph, subject, property, reference, true),
+          }
+        }
+      }
+    }
+
+    if let Some(types) = element.remove("@type") {
+      let node = self.node(graph, &id);
+      for t in as_slice(&types) {
+        add_unique(node, "@type", t.clone());
+      }
+    }
+
+    if let Some(index) = element.remove("@index") {
+      let node = self.node(graph, &id);
+      if node
+        .get("@index")
+        .is_some_and(|existing| *existing != index)
+      {
+        return Err(JsonLdError::new(
+          "conflicting indexes",
+          &format!("{} has more than one @index", id),
+        ));
+      }
+      node.insert(String::from("@index"), index);
+    }
+
+    if let Some(Value::Object(reverse)) = element.remove("@reverse") {
+      let referencing = ActiveSubject::Reverse(object_with("@id", Value::String(id.clone())));
+      for (property, values) in &reverse {
+        for value in as_slice(values) {
+          self.generate(value, graph, &referencing, Some(property), None)?;
+        }
+      }
+    }
+
+    if let Some(graph_value) = element.remove("@graph") {
+      self.node_map.entry(id.clone()).or_default();
+      self.generate(&graph_value, &id, &ActiveSubject::None, None, None)?;
+    }
+
+    if let Some(included) = element.remove("@included") {
+      self.generate(&included, graph, &ActiveSubject::None, None, None)?;
+    }
+
+    for (property, value) in &element {
+      let property = if property.starts_with("_:") {
+        self.issuer.get_id(property)
+      } else {
+        property.clone()
+      };
+      self
+        .node(graph, &id)
+        .entry(property.clone())
+        .or_insert_with(|| Value::Array(Vec::new()));
+      self.generate(
+        value,
+        graph,
+        &ActiveSubject::Id(id.clone()),
+        Some(&property),
+        None,
+      )?;
+    }
+
+    Ok(())
+  }
+
+  fn node(&mut self, graph: &str, id: &str) -> &mut Map<String, Value> {
+    self
+      .node_map
+      .entry(graph.to_string())
+      .or_default()
+      .entry(id.to_string())
+      .or_insert_with(|| {
+        let mut node = Map::new();
+        node.insert(String::from("@id"), Value::String(id.to_string()));
+        node
+      })
+  }
+
+  fn add_to_subject(
+    &mut self,
+    graph: &str,
+    subject: &ActiveSubject,
+    property: Option<&str>,
+    value: Value,
+    unique: bool,
+  ) {
+    if let (ActiveSubject::Id(id), Some(property)) = (subject, property) {
+      let node = self.node(graph, id);
+      if unique {
+        add_unique(node, property, value);
+      } else {
+        add_value(node, property, value);
+      }
+    }
+  }
+
+  // https://www.w3.org/TR/json-ld11-api/#object-to-rdf-conversion
+  fn object_to_rdf(
+    &mut self,
+    item: &Value,
+    triples: &mut Vec<(Subject, Predicate, Object)>,
+  ) -> Option<Object> {
+    if let Some(id) = item.get("@id").and_then(|id| id.as_str()) {
+      return node_type(id).map(|term_type| node_term(term_type, id));
+    }
+    if let Some(list) = item.get("@list") {
+      return Some(self.list_to_rdf(as_slice(list), triples));
+    }
+
+    let value = item.get("@value")?;
+    let mut datatype = item.get("@type").and_then(|t| t.as_str()).map(String::from);
+    if datatype
+      .as_deref()
+      .is_some_and(|t| t != "@json" && !is_absolute_iri(t))
+    {
+      return None;
+    }
+    let language = item.get("@language").and_then(|l| l.as_str());
+    if language.is_some_and(|l| !language_tag::is_well_formed(l)) {
+      return None;
+    }
+
+    let lexical = if datatype.as_deref() == Some("@json") {
+      datatype = Some(String::from(RDF_JSON));
+      canonical_json(value)
+    } else {
+      match value {
+        Value::Bool(b) => {
+          datatype.get_or_insert_with(|| String::from(XSD_BOOLEAN));
+          b.to_string()
+        }
+        Value::Number(number) => {
+          // -0 is written as 0, as ECMAScript number formatting does
+          let f = match number.as_f64() {
+            Some(f) if f != 0.0 => f,
+            _ => 0.0,
+          };
+          let is_double = (number.is_f64() && f.fract() != 0.0)
+            || datatype.as_deref() == Some(XSD_DOUBLE)
+            || f.abs() >= 1e21;
+          if is_double {
+            datatype.get_or_insert_with(|| String::from(XSD_DOUBLE));
+            canonical_lexical_form(&format!("{:e}", f), XSD_DOUBLE).unwrap()
+          } else {
+            datatype.get_or_insert_with(|| String::from(XSD_INTEGER));
+            if number.is_f64() {
+              format!("{:.0}", f)
+            } else {
+              number.to_string()
+            }
+          }
+        }
+        Value::String(s) => s.clone(),
+        _ => return None,
+      }
+    };
+
+    let direction = item.get("@direction").and_then(|d| d.as_str());
+    if let (Some(direction), Some(mode)) = (direction, self.options.rdf_direction) {
+      return Some(match mode {
+        RdfDirection::I18nDatatype => {
+          let datatype = format!(
+            "{}{}_{}",
+            I18N,
+            language.unwrap_or("").to_ascii_lowercase(),
+            direction
+          );
+          literal(lexical, &datatype, None)
+        }
+        RdfDirection::CompoundLiteral => {
+          let id = self.issuer.get_new_id();
+          let subject = Subject {
+            term_type: TermType::BlankNode,
+            value: id.clone(),
+          };
+          triples.push((
+            subject.clone(),
+            named_predicate(RDF_VALUE),
+            literal(lexical, XSD_STRING, None),
+          ));
+          if let Some(language) = language {
+            triples.push((
+              subject.clone(),
+              named_predicate(RDF_LANGUAGE),
+              literal(language.to_ascii_lowercase(), XSD_STRING, None),
+            ));
+          }
+          triples.push((
+            subject,
+            named_predicate(RDF_DIRECTION),
+            literal(direction.to_string(), XSD_STRING, None),
+          ));
+          node_term(TermType::BlankNode, &id)
+        }
+      });
+    }
+
+    match language {
+      Some(language) if datatype.is_none() => {
+        Some(literal(lexical, RDF_LANGSTRING, Some(language)))
+      }
+      _ => Some(literal(
+        lexical,
+        datatype.as_deref().unwrap_or(XSD_STRING),
+        None,
+      )),
+    }
+  }
+
+  // https://www.w3.org/TR/json-ld11-api/#list-to-rdf-conversion
+  fn list_to_rdf(
+    &mut self,
+    list: &[Value],
+    triples: &mut Vec<(Subject, Predicate, Object)>,
+  ) -> Object {
+    if list.is_empty() {
+      return node_term(TermType::NamedNode, RDF_NIL);
+    }
+
+    let ids: Vec<String> = list.iter().map(|_| self.issuer.get_new_id()).collect();
+    for (i, item) in list.iter().enumerate() {
+      let subject = Subject {
+        term_type: TermType::BlankNode,
+        value: ids[i].clone(),
+      };
+      let mut embedded = Vec::new();
+      if let Some(object) = self.object_to_rdf(item, &mut embedded) {
+        triples.push((subject.clone(), named_predicate(RDF_FIRST), object));
+      }
+      let rest = match ids.get(i + 1) {
+        Some(next) => node_term(TermType::BlankNode, next),
+        None => node_term(TermType::NamedNode, RDF_NIL),
+      };
+      triples.push((subject, named_predicate(RDF_REST), rest));
+      triples.append(&mut embedded);
+    }
+
+    node_term(TermType::BlankNode, &ids[0])
+  }
+}
+
+// Returns the term type of a node identifier, or `None` for relative IRIs,
+// which cannot be expressed in RDF.
+fn node_type(id: &str) -> Option<TermType> {
+  if id.starts_with("_:") {
+    Some(TermType::BlankNode)
+  } else if is_absolute_iri(id) {
+    Some(TermType::NamedNode)
+  } else {
+    None
+  }
+}
+
+fn node_term(term_type: TermType, value: &str) -> Object {
+  let mut object = Object::new();
+  object.term_type = term_type;
+  object.value = value.to_string();
+  object
+}
+
+fn literal(value: String, datatype: &str, language: Option<&str>) -> Object {
+  let mut object = Object::new();
+  object.term_type = TermType::Literal;
+  object.value = value;
+  object.datatype = Some(datatype.to_string());
+  object.language = language.map(|language| language.to_string());
+  object
+}
+
+fn named_predicate(iri: &str) -> Predicate {
+  Predicate {
+    term_type: TermType::NamedNode,
+    value: iri.to_string(),
+  }
+}
+
+fn add_unique(node: &mut Map<String, Value>, property: &str, value: Value) {
+  let exists = node
+    .get(property)
+    .is_some_and(|values| as_slice(values).contains(&value));
+  if !exists {
+    add_value(node, property, value);
+  }
+}
+
+// The JSON Canonicalization Scheme (RFC 8785) form of a JSON literal.
+fn canonical_json(value: &Value) -> String {
+  match value {
+    Value::Array(items) => {
+      let items: Vec<String> = items.iter().map(canonical_json).collect();
+      format!("[{}]", items.join(","))
+    }
+    Value::Object(map) => {
+      let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+      // members are sorted by the UTF-16 code units of their names
+      entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+      let entries: Vec<String> = entries
+        .into_iter()
+        .map(|(key, value)| format!("{}:{}", Value::String(key.clone()), canonical_json(value)))
+        .collect();
+      format!("{{{}}}", entries.join(","))
+    }
+    Value::Number(number) if number.is_f64() => {
+      let f = number.as_f64().unwrap();
+      // ECMAScript number serialization
+      if f == 0.0 {
+        String::from("0")
+      } else if f.fract() == 0.0 && f.abs() < 1e21 {
+        format!("{:.0}", f)
+      } else if f.abs() >= 1e21 || f.abs() < 1e-6 {
+        let exponential = format!("{:e}", f);
+        match exponential.find("e-") {
+          Some(_) => exponential,
+          None => exponential.replace('e', "e+"),
+        }
+      } else {
+        f.to_string()
+      }
+    }
+    value => value.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn canonical_json_literals() {
+    let value: Value =
+      serde_json::from_str(r#"{"b": [1.0, 1.5, 1e21, 1e-7, "é"], "a": null, "c": true}"#).unwrap();
+    assert_eq!(
+      canonical_json(&value),
+      r#"{"a":null,"b":[1,1.5,1e+21,1e-7,"é"],"c":true}"#
+    );
+  }
+}
@@ -0,0 +1,259 @@
+use crate::nquads::{Dataset, Object};
+
+use std::error::Error;
+use std::fmt;
+
+// https://tools.ietf.org/html/rfc5646#section-2.2.8
+const IRREGULAR: [&str; 17] = [
+  "en-gb-oed",
+  "i-ami",
+  "i-bnn",
+  "i-default",
+  "i-enochian",
+  "i-hak",
+  "i-klingon",
+  "i-lux",
+  "i-mingo",
+  "i-navajo",
+  "i-pwn",
+  "i-tao",
+  "i-tay",
+  "i-tsu",
+  "sgn-be-fr",
+  "sgn-be-nl",
+  "sgn-ch-de",
+];
+const REGULAR: [&str; 9] = [
+  "art-lojban",
+  "cel-gaulish",
+  "no-bok",
+  "no-nyn",
+  "zh-guoyu",
+  "zh-hakka",
+  "zh-min",
+  "zh-min-nan",
+  "zh-xiang",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageTagError {
+  pub tag: String,
+}
+
+impl fmt::Display for LanguageTagError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a well-formed BCP47 language tag",
+      self.tag
+    )
+  }
+}
+
+impl Error for LanguageTagError {}
+
+// Checks `tag` against the `Language-Tag` production of RFC 5646. Only the
+// syntax is checked; subtags are not looked up in the IANA registry.
+pub fn is_well_formed(tag: &str) -> bool {
+  let lower = tag.to_ascii_lowercase();
+  if IRREGULAR.contains(&lower.as_str()) || REGULAR.contains(&lower.as_str()) {
+    return true;
+  }
+
+  let subtags: Vec<&str> = tag.split('-').collect();
+  if subtags
+    .iter()
+    .any(|s| s.is_empty() || s.len() > 8 || !s.bytes().all(|c| c.is_ascii_alphanumeric()))
+  {
+    return false;
+  }
+
+  let mut rest = &subtags[..];
+
+  // privateuse = "x" 1*("-" (1*8alphanum))
+  if is_private_use_singleton(rest[0]) {
+    return rest.len() > 1;
+  }
+
+  // language = 2*3ALPHA ["-" extlang] / 4ALPHA / 5*8ALPHA
+  let language = rest[0];
+  if language.len() < 2 || !is_alpha(language) {
+    return false;
+  }
+  rest = &rest[1..];
+
+  // extlang = 3ALPHA *2("-" 3ALPHA)
+  if language.len() <= 3 {
+    let mut extlangs = 0;
+    while extlangs < 3 && !rest.is_empty() && rest[0].len() == 3 && is_alpha(rest[0]) {
+      extlangs += 1;
+      rest = &rest[1..];
+    }
+  }
+
+  // script = 4ALPHA
+  if !rest.is_empty() && rest[0].len() == 4 && is_alpha(rest[0]) {
+    rest = &rest[1..];
+  }
+
+  // region = 2ALPHA / 3DIGIT
+  if !rest.is_empty()
+    && ((rest[0].len() == 2 && is_alpha(rest[0])) || (rest[0].len() == 3 && is_digit(rest[0])))
+  {
+    rest = &rest[1..];
+  }
+
+  // variant = 5*8alphanum / (DIGIT 3alphanum)
+  while !rest.is_empty()
+    && (rest[0].len() >= 5 || (rest[0].len() == 4 && rest[0].as_bytes()[0].is_ascii_digit()))
+  {
+    rest = &rest[1..];
+  }
+
+  // extension = singleton 1*("-" (2*8alphanum))
+  while !rest.is_empty() && rest[0].len() == 1 && !is_private_use_singleton(rest[0]) {
+    rest = &rest[1..];
+    let mut extension_subtags = 0;
+    while !rest.is_empty() && rest[0].len() >= 2 {
+      extension_subtags += 1;
+      rest = &rest[1..];
+    }
+    if extension_subtags == 0 {
+      return false;
+    }
+  }
+
+  // ["-" privateuse]
+  if !rest.is_empty() && is_private_use_singleton(rest[0]) {
+    return rest.len() > 1;
+  }
+
+  rest.is_empty()
+}
+
+// Validates `tag` and returns its lowercased form. Language tags compare
+// case-insensitively, so lowercasing gives every spelling of the same tag the
+// same canonical serialization.
+pub fn normalize(tag: &str) -> Result<String, LanguageTagError> {
+  if !is_well_formed(tag) {
+    return Err(LanguageTagError {
+      tag: tag.to_string(),
+    });
+  }
+
+  Ok(tag.to_ascii_lowercase())
+}
+
+// Normalizes the language tag of every language-tagged literal in `dataset`,
+// including those nested inside triple terms.
+pub fn normalize_dataset(dataset: &mut Dataset) -> Result<(), LanguageTagError> {
+  for quad in &mut dataset.quads {
+    normalize_object(&mut quad.object)?;
+  }
+
+  Ok(())
+}
+
+fn normalize_object(object: &mut Object) -> Result<(), LanguageTagError> {
+  if let Some(language) = &object.language {
+    let normalized = normalize(language)?;
+    object.set_language(&normalized);
+  }
+  // only the object of a triple term can be a literal
+  if let Some(triple) = &mut object.triple {
+    normalize_object(&mut triple.object)?;
+  }
+
+  Ok(())
+}
+
+fn is_private_use_singleton(subtag: &str) -> bool {
+  subtag.eq_ignore_ascii_case("x")
+}
+
+fn is_alpha(subtag: &str) -> bool {
+  subtag.bytes().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_digit(subtag: &str) -> bool {
+  subtag.bytes().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads::parse_nquads;
+
+  #[test]
+  fn well_formed_tags() {
+    let tags = [
+      "en",
+      "en-US",
+      "zh-Hant-TW",
+      "zh-yue-HK",
+      "sl-rozaj-biske",
+      "de-CH-1901",
+      "es-419",
+      "en-US-u-islamcal",
+      "de-CH-x-phonebk",
+      "x-whatever",
+      "i-klingon",
+      "zh-min-nan",
+    ];
+    for tag in tags.iter() {
+      assert!(is_well_formed(tag), "{}", tag);
+    }
+  }
+
+  #[test]
+  fn ill_formed_tags() {
+    let tags = [
+      "",
+      "e",
+      "en-",
+      "en--US",
+      "1en",
+      "en-US-u",
+      "en-x",
+      "abcdefghi",
+      "en-US-a-b",
+      "en_US",
+    ];
+    for tag in tags.iter() {
+      assert!(!is_well_formed(tag), "{}", tag);
+    }
+  }
+
+  #[test]
+  fn normalize_lowercases() {
+    assert_eq!(normalize("en-US").unwrap(), "en-us");
+    assert_eq!(normalize("ZH-Hant-TW").unwrap(), "zh-hant-tw");
+    assert_eq!(
+      normalize("en-US-u").unwrap_err(),
+      LanguageTagError {
+        tag: String::from("en-US-u")
+      }
+    );
+  }
+
+  #[test]
+  fn normalize_dataset_in_triple_terms() {
+    let mut dataset = parse_nquads(
+      "_:a <http://example.org/p> \"a\"@EN-US .\n\
+_:a <http://example.org/p> <<( _:b <http://example.org/p> \"b\"@DE-CH )>> .\n",
+    );
+    normalize_dataset(&mut dataset).unwrap();
+    assert_eq!(
+      dataset,
+      parse_nquads(
+        "_:a <http://example.org/p> \"a\"@en-us .\n\
+_:a <http://example.org/p> <<( _:b <http://example.org/p> \"b\"@de-ch )>> .\n"
+      )
+    );
+
+    let mut dataset = parse_nquads(
+      "_:a <http://example.org/p> <<( _:b <http://example.org/p> \"b\"@en-US-u )>> .\n",
+    );
+    assert!(normalize_dataset(&mut dataset).is_err());
+  }
+}
@@ -0,0 +1,331 @@
+use crate::iri::has_scheme;
+use crate::nquads::ParseError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+  // IRI value, without the enclosing angle brackets
+  Iri(String),
+  // blank node identifier, including the `_:` prefix
+  BlankNode(String),
+  // unescaped value of a quoted string
+  String(String),
+  // language tag, without the leading `@`, and optional base direction
+  LangTag(String, Option<String>),
+  // `^^`
+  DatatypeMarker,
+  // `<<(`
+  TripleTermStart,
+  // `)>>`
+  TripleTermEnd,
+  // `.`
+  Dot,
+}
+
+// A tokenizer for a single line of N-Triples or N-Quads.
+pub struct Lexer<'a> {
+  input: &'a str,
+  position: usize,
+  line: usize,
+}
+
+impl<'a> Lexer<'a> {
+  pub fn new(input: &'a str, line: usize) -> Lexer<'a> {
+    Lexer {
+      input,
+      position: 0,
+      line,
+    }
+  }
+
+  // Returns the next token and the column it starts at, or `None` once the
+  // end of the line (or a comment) is reached.
+  pub fn next_token(&mut self) -> Result<Option<(usize, Token)>, ParseError> {
+    self.skip_whitespace();
+
+    let start = self.position;
+    let c = match self.peek() {
+      Some(c) => c,
+      None => return Ok(None),
+    };
+
+    let token = match c {
+      '#' => {
+        self.position = self.input.len();
+        return Ok(None);
+      }
+      '<' if self.rest().starts_with("<<(") => {
+        self.position += 3;
+        Token::TripleTermStart
+      }
+      ')' if self.rest().starts_with(")>>") => {
+        self.position += 3;
+        Token::TripleTermEnd
+      }
+      '<' => Token::Iri(self.iri()?),
+      '_' => Token::BlankNode(self.blank_node()?),
+      '"' => Token::String(self.string()?),
+      '@' => {
+        let language = self.lang_tag()?;
+        let direction = self.direction()?;
+        Token::LangTag(language, direction)
+      }
+      '^' if self.rest().starts_with("^^") => {
+        self.position += 2;
+        Token::DatatypeMarker
+      }
+      '.' => {
+        self.position += 1;
+        Token::Dot
+      }
+      _ => return Err(self.error(start, &format!("unexpected character '{}'", c))),
+    };
+
+    Ok(Some((self.column(start), token)))
+  }
+
+  pub fn error(&self, position: usize, message: &str) -> ParseError {
+    ParseError {
+      line: self.line,
+      column: self.column(position),
+      message: message.to_string(),
+    }
+  }
+
+  fn column(&self, position: usize) -> usize {
+    self.input[..position].chars().count() + 1
+  }
+
+  fn rest(&self) -> &'a str {
+    &self.input[self.position..]
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.position += c.len_utf8();
+    Some(c)
+  }
+
+  fn skip_whitespace(&mut self) {
+    while let Some(c) = self.peek() {
+      if c != ' ' && c != '\t' && c != '\r' && c != '\n' {
+        break;
+      }
+      self.position += 1;
+    }
+  }
+
+  // https://www.w3.org/TR/n-quads/#grammar-production-IRIREF
+  fn iri(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    self.bump();
+    let mut iri = String::with_capacity(crate::nquads::DEFAULT_TERM_CAPACITY);
+    loop {
+      match self.bump() {
+        Some('>') => break,
+        Some('\\') => iri.push(self.uchar()?),
+        Some(c) if is_iri_char(c) => iri.push(c),
+        Some(c) => {
+          return Err(self.error(
+            self.position - c.len_utf8(),
+            &format!("invalid character '{}' in IRI", c.escape_default()),
+          ))
+        }
+        None => return Err(self.error(start, "unterminated IRI")),
+      }
+    }
+
+    if !has_scheme(&iri) {
+      return Err(self.error(start, &format!("IRI <{}> is not absolute", iri)));
+    }
+
+    Ok(iri)
+  }
+
+  // https://www.w3.org/TR/n-quads/#grammar-production-BLANK_NODE_LABEL
+  fn blank_node(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    if !self.rest().starts_with("_:") {
+      return Err(self.error(start, "expected blank node label"));
+    }
+    self.position += 2;
+
+    match self.peek() {
+      Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => self.position += c.len_utf8(),
+      _ => return Err(self.error(start, "invalid blank node label")),
+    }
+    while let Some(c) = self.peek() {
+      if !is_pn_chars(c) && c != '.' {
+        break;
+      }
+      self.position += c.len_utf8();
+    }
+    // a label cannot end with `.`; leave it to terminate the statement
+    while self.input[..self.position].ends_with('.') {
+      self.position -= 1;
+    }
+
+    Ok(self.input[start..self.position].to_string())
+  }
+
+  // https://www.w3.org/TR/n-quads/#grammar-production-STRING_LITERAL_QUOTE
+  fn string(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    self.bump();
+    let mut value = String::with_capacity(crate::nquads::DEFAULT_TERM_CAPACITY);
+    loop {
+      match self.bump() {
+        Some('"') => break,
+        Some('\\') => value.push(self.echar_or_uchar()?),
+        Some(c) => value.push(c),
+        None => return Err(self.error(start, "unterminated string")),
+      }
+    }
+
+    Ok(value)
+  }
+
+  // https://www.w3.org/TR/n-quads/#grammar-production-LANGTAG
+  fn lang_tag(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    self.bump();
+    let tag_start = self.position;
+    let mut subtags = 0;
+    loop {
+      let subtag_start = self.position;
+      while let Some(c) = self.peek() {
+        let valid = if subtags == 0 {
+          c.is_ascii_alphabetic()
+        } else {
+          c.is_ascii_alphanumeric()
+        };
+        if !valid {
+          break;
+        }
+        self.position += 1;
+      }
+      if self.position == subtag_start {
+        return Err(self.error(start, "invalid language tag"));
+      }
+      subtags += 1;
+      if self.peek() != Some('-') || self.rest().starts_with("--") {
+        break;
+      }
+      self.position += 1;
+    }
+
+    Ok(self.input[tag_start..self.position].to_string())
+  }
+
+  // https://www.w3.org/TR/rdf12-n-quads/#grammar-production-LANG_DIR
+  fn direction(&mut self) -> Result<Option<String>, ParseError> {
+    if !self.rest().starts_with("--") {
+      return Ok(None);
+    }
+    let start = self.position;
+    self.position += 2;
+    let direction_start = self.position;
+    while let Some(c) = self.peek() {
+      if !c.is_ascii_alphabetic() {
+        break;
+      }
+      self.position += 1;
+    }
+
+    match &self.input[direction_start..self.position] {
+      direction @ "ltr" | direction @ "rtl" => Ok(Some(direction.to_string())),
+      _ => Err(self.error(start, "base direction must be 'ltr' or 'rtl'")),
+    }
+  }
+
+  fn echar_or_uchar(&mut self) -> Result<char, ParseError> {
+    let escaped = match self.peek() {
+      Some('t') => '\t',
+      Some('b') => '\x08',
+      Some('n') => '\n',
+      Some('r') => '\r',
+      Some('f') => '\x0C',
+      Some('"') => '"',
+      Some('\'') => '\'',
+      Some('\\') => '\\',
+      _ => return self.uchar(),
+    };
+    self.position += 1;
+
+    Ok(escaped)
+  }
+
+  // decodes `\uXXXX` or `\UXXXXXXXX`; the backslash is already consumed
+  fn uchar(&mut self) -> Result<char, ParseError> {
+    let start = self.position - 1;
+    let length = match self.bump() {
+      Some('u') => 4,
+      Some('U') => 8,
+      _ => return Err(self.error(start, "invalid escape sequence")),
+    };
+    let hex = self.rest().get(..length).unwrap_or("");
+    if hex.len() != length || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+      return Err(self.error(start, "invalid unicode escape sequence"));
+    }
+    self.position += length;
+
+    u32::from_str_radix(hex, 16)
+      .ok()
+      .and_then(std::char::from_u32)
+      .ok_or_else(|| self.error(start, "invalid unicode code point"))
+  }
+}
+
+fn is_iri_char(c: char) -> bool {
+  !matches!(
+    c,
+    '\x00'..=' ' | '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\'
+  )
+}
+
+// https://www.w3.org/TR/turtle/#grammar-production-PN_CHARS_BASE
+pub fn is_pn_chars_base(c: char) -> bool {
+  matches!(c,
+    'A'..='Z'
+    | 'a'..='z'
+    | '\u{00C0}'..='\u{00D6}'
+    | '\u{00D8}'..='\u{00F6}'
+    | '\u{00F8}'..='\u{02FF}'
+    | '\u{0370}'..='\u{037D}'
+    | '\u{037F}'..='\u{1FFF}'
+    | '\u{200C}'..='\u{200D}'
+    | '\u{2070}'..='\u{218F}'
+    | '\u{2C00}'..='\u{2FEF}'
+    | '\u{3001}'..='\u{D7FF}'
+    | '\u{F900}'..='\u{FDCF}'
+    | '\u{FDF0}'..='\u{FFFD}'
+    | '\u{10000}'..='\u{EFFFF}')
+}
+
+pub fn is_pn_chars_u(c: char) -> bool {
+  is_pn_chars_base(c) || c == '_'
+}
+
+pub fn is_pn_chars(c: char) -> bool {
+  is_pn_chars_u(c)
+    || matches!(c,
+      '0'..='9'
+      | '-'
+      | '\u{00B7}'
+      | '\u{0300}'..='\u{036F}'
+      | '\u{203F}'..='\u{2040}')
+}
+
+// Whether `label` is a valid blank node label without its `_:` prefix.
+// https://www.w3.org/TR/n-quads/#grammar-production-BLANK_NODE_LABEL
+pub fn is_blank_node_label(label: &str) -> bool {
+  let mut chars = label.chars();
+  match chars.next() {
+    Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => (),
+    _ => return false,
+  }
+  !label.ends_with('.') && chars.all(|c| is_pn_chars(c) || c == '.')
+}
@@ -2,15 +2,590 @@
 extern crate lazy_static;
 
 mod identifier_issuer;
+mod iri;
+mod lexer;
 mod message_digest;
 mod permuter;
+#[cfg(feature = "serde")]
+mod rdfjs;
 mod urdna2015;
 
+pub mod cbor;
+#[cfg(feature = "rand")]
+pub mod generate;
+pub mod indexed_dataset;
+#[cfg(feature = "jsonld")]
+pub mod jsonld;
+pub mod language_tag;
+pub mod literal;
 pub mod nquads;
+pub mod ntriples;
+#[cfg(feature = "rdfxml")]
+pub mod rdfxml;
+#[cfg(feature = "rand")]
+pub mod relabel;
+pub mod sink;
+pub mod skolem;
+pub mod trig;
+pub mod turtle;
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CanonizeOptions {
+  // When set, every language tag is validated as a well-formed BCP47 tag and
+  // lowercased before canonicalization. By default tags are emitted exactly
+  // as they were parsed, so `en-US` and `en-us` produce different output.
+  pub normalize_language_tags: bool,
+  // When set, literals of the well-known XSD datatypes (integer, decimal,
+  // boolean, double and dateTime) are rewritten to their canonical lexical
+  // forms before canonicalization, so `"01"^^xsd:integer` and
+  // `"1"^^xsd:integer` hash the same. Off by default.
+  pub canonicalize_literals: bool,
+  // When set, duplicate quads are removed before canonicalization, treating
+  // the input as the set of quads it describes. By default every input quad,
+  // duplicates included, becomes a line of the output.
+  pub deduplicate_quads: bool,
+  // When set, only the quads of these graphs are canonicalized and the rest
+  // of the dataset is ignored, e.g. to sign one graph of a larger dataset. A
+  // graph with the term type `DefaultGraph` selects the default graph; blank
+  // node graph names are matched by their input labels.
+  pub graphs: Option<Vec<nquads::Graph>>,
+  // When set, the (selected) quads are moved to the default graph before
+  // canonicalization, so a named graph is canonicalized as if its triples
+  // were a dataset of their own.
+  pub move_to_default_graph: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CanonizeError {
+  UnknownAlgorithm(String),
+  LanguageTag(language_tag::LanguageTagError),
+  Literal(literal::LiteralError),
+  NamedGraph(ntriples::NamedGraphError),
+}
+
+impl fmt::Display for CanonizeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CanonizeError::UnknownAlgorithm(algorithm) => {
+        write!(f, "unknown canonicalization algorithm \"{}\"", algorithm)
+      }
+      CanonizeError::LanguageTag(e) => e.fmt(f),
+      CanonizeError::Literal(e) => e.fmt(f),
+      CanonizeError::NamedGraph(e) => e.fmt(f),
+    }
+  }
+}
+
+impl Error for CanonizeError {}
+
+impl From<language_tag::LanguageTagError> for CanonizeError {
+  fn from(e: language_tag::LanguageTagError) -> CanonizeError {
+    CanonizeError::LanguageTag(e)
+  }
+}
+
+impl From<literal::LiteralError> for CanonizeError {
+  fn from(e: literal::LiteralError) -> CanonizeError {
+    CanonizeError::Literal(e)
+  }
+}
+
+impl From<ntriples::NamedGraphError> for CanonizeError {
+  fn from(e: ntriples::NamedGraphError) -> CanonizeError {
+    CanonizeError::NamedGraph(e)
+  }
+}
 
 pub fn canonize(dataset: &nquads::Dataset, algorithm: &str) -> Option<String> {
   match algorithm {
-    "URDNA2015" => Some(urdna2015::URDNA2015::new().main(&dataset)),
+    "URDNA2015" => Some(urdna2015::URDNA2015::new().main(&dataset.quads)),
+    _ => None,
+  }
+}
+
+// Canonizes the quads of any `QuadSource`, e.g. the quads of a store borrowed
+// as `QuadRef`s, without first copying them into a `Dataset`.
+pub fn canonize_quads<S>(source: S, algorithm: &str) -> Option<String>
+where
+  S: nquads::QuadSource,
+{
+  let mut canonical = String::new();
+  canonize_into(source, algorithm, &mut canonical).map(|result| {
+    // writing to a `String` cannot fail
+    result.unwrap();
+    canonical
+  })
+}
+
+// Canonizes the quads of `source` into `sink`, which receives the canonical
+// N-Quads one line at a time in canonical order. Returns `None` for an unknown
+// algorithm and the first error of the sink otherwise.
+pub fn canonize_into<S, K>(source: S, algorithm: &str, sink: &mut K) -> Option<io::Result<()>>
+where
+  S: nquads::QuadSource,
+  K: sink::QuadSink,
+{
+  match algorithm {
+    "URDNA2015" => {
+      let quads: Vec<S::Quad> = source.quads().collect();
+      Some(urdna2015::URDNA2015::new().main_into(&quads, sink))
+    }
     _ => None,
   }
 }
+
+// The canonical form of a dataset both as N-Quads and as a `Dataset`. The
+// quads of `dataset` are in canonical order and their blank nodes carry the
+// canonical labels, so serializing them one by one yields `nquads`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CanonicalDataset {
+  pub dataset: nquads::Dataset,
+  pub nquads: String,
+}
+
+pub fn canonize_dataset<S>(source: S, algorithm: &str) -> Option<CanonicalDataset>
+where
+  S: nquads::QuadSource,
+{
+  match algorithm {
+    "URDNA2015" => {
+      let quads: Vec<S::Quad> = source.quads().collect();
+      let (quads, nquads) = urdna2015::URDNA2015::new().main_quads(&quads);
+      Some(CanonicalDataset {
+        dataset: nquads::Dataset { quads },
+        nquads,
+      })
+    }
+    _ => None,
+  }
+}
+
+// Canonizes a dataset to the binary form of the `cbor` module, with the quads
+// in canonical N-Quads order.
+pub fn canonize_cbor(dataset: &nquads::Dataset, algorithm: &str) -> Option<Vec<u8>> {
+  canonize_dataset(dataset, algorithm).map(|canonical| cbor::serialize_cbor(&canonical.dataset))
+}
+
+pub fn canonize_with_options(
+  dataset: &nquads::Dataset,
+  algorithm: &str,
+  options: &CanonizeOptions,
+) -> Result<String, CanonizeError> {
+  if algorithm != "URDNA2015" {
+    return Err(CanonizeError::UnknownAlgorithm(algorithm.to_string()));
+  }
+
+  let dataset = preprocess(dataset, options)?;
+  Ok(urdna2015::URDNA2015::new().main(&dataset.quads))
+}
+
+// Applies the preprocessing passes selected by `options`, in the order graph
+// selection, moving to the default graph, deduplication, language tags and
+// literals.
+fn preprocess<'a>(
+  dataset: &'a nquads::Dataset,
+  options: &CanonizeOptions,
+) -> Result<Cow<'a, nquads::Dataset>, CanonizeError> {
+  // only copy the dataset when a preprocessing pass has to rewrite it
+  let mut dataset = Cow::Borrowed(dataset);
+  if let Some(graphs) = &options.graphs {
+    dataset.to_mut().retain_graphs(graphs);
+  }
+  if options.move_to_default_graph {
+    dataset.to_mut().move_to_default_graph();
+  }
+  if options.deduplicate_quads {
+    dataset.to_mut().dedup();
+  }
+  if options.normalize_language_tags {
+    language_tag::normalize_dataset(dataset.to_mut())?;
+  }
+  if options.canonicalize_literals {
+    literal::canonicalize_dataset(dataset.to_mut())?;
+  }
+  Ok(dataset)
+}
+
+// Canonizes a dataset that only has a default graph. The canonical N-Quads of
+// such a dataset is also its canonical N-Triples; datasets with named graphs
+// are rejected rather than silently merged. The check applies to the dataset
+// after graph selection, so `move_to_default_graph` makes any dataset valid.
+pub fn canonize_ntriples(
+  dataset: &nquads::Dataset,
+  algorithm: &str,
+  options: &CanonizeOptions,
+) -> Result<String, CanonizeError> {
+  if algorithm != "URDNA2015" {
+    return Err(CanonizeError::UnknownAlgorithm(algorithm.to_string()));
+  }
+
+  let dataset = preprocess(dataset, options)?;
+  ntriples::check_default_graph(&dataset)?;
+  Ok(urdna2015::URDNA2015::new().main(&dataset.quads))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MIXED_CASE: &str = "_:b0 <http://example.org/vocab#name> \"colour\"@en-GB .\n\
+_:b1 <http://example.org/vocab#name> \"colour\"@en-gb .\n";
+
+  #[test]
+  fn language_tags_are_preserved_by_default() {
+    let dataset = nquads::parse_nquads(MIXED_CASE);
+    let canonized = canonize(&dataset, "URDNA2015").unwrap();
+    assert!(canonized.contains("@en-GB"));
+    assert!(canonized.contains("@en-gb"));
+    assert_eq!(
+      canonize_with_options(&dataset, "URDNA2015", &CanonizeOptions::default()).unwrap(),
+      canonized
+    );
+  }
+
+  #[test]
+  fn normalize_language_tags() {
+    let dataset = nquads::parse_nquads(MIXED_CASE);
+    let options = CanonizeOptions {
+      normalize_language_tags: true,
+      ..Default::default()
+    };
+    let canonized = canonize_with_options(&dataset, "URDNA2015", &options).unwrap();
+    assert_eq!(
+      canonized,
+      "_:c14n0 <http://example.org/vocab#name> \"colour\"@en-gb .\n\
+_:c14n1 <http://example.org/vocab#name> \"colour\"@en-gb .\n"
+    );
+  }
+
+  #[test]
+  fn normalize_language_tags_rejects_ill_formed_tags() {
+    let dataset =
+      nquads::parse_nquads("<http://example.org/s> <http://example.org/p> \"x\"@en-US-u .");
+    let options = CanonizeOptions {
+      normalize_language_tags: true,
+      ..Default::default()
+    };
+    let error = canonize_with_options(&dataset, "URDNA2015", &options).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "\"en-US-u\" is not a well-formed BCP47 language tag"
+    );
+  }
+
+  #[test]
+  fn literals_are_preserved_by_default() {
+    let dataset = nquads::parse_nquads(
+      "<http://example.org/s> <http://example.org/p> \"01\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+    );
+    let canonized = canonize(&dataset, "URDNA2015").unwrap();
+    assert!(canonized.contains("\"01\"^^"));
+  }
+
+  #[test]
+  fn canonicalize_literals() {
+    let a = nquads::parse_nquads(
+      "_:b0 <http://example.org/p> \"01\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:b0 <http://example.org/q> \"2018-12-21T23:40:20+01:00\"^^<http://www.w3.org/2001/XMLSchema#dateTime> .",
+    );
+    let b = nquads::parse_nquads(
+      "_:x <http://example.org/p> \"+1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:x <http://example.org/q> \"2018-12-21T22:40:20Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime> .",
+    );
+    let options = CanonizeOptions {
+      canonicalize_literals: true,
+      ..Default::default()
+    };
+    assert_ne!(canonize(&a, "URDNA2015"), canonize(&b, "URDNA2015"));
+    assert_eq!(
+      canonize_with_options(&a, "URDNA2015", &options),
+      canonize_with_options(&b, "URDNA2015", &options)
+    );
+  }
+
+  #[test]
+  fn canonicalize_literals_rejects_invalid_lexical_forms() {
+    let dataset = nquads::parse_nquads(
+      "<http://example.org/s> <http://example.org/p> \"yes\"^^<http://www.w3.org/2001/XMLSchema#boolean> .",
+    );
+    let options = CanonizeOptions {
+      canonicalize_literals: true,
+      ..Default::default()
+    };
+    assert_eq!(
+      canonize_with_options(&dataset, "URDNA2015", &options),
+      Err(CanonizeError::Literal(literal::LiteralError {
+        value: String::from("yes"),
+        datatype: String::from(literal::XSD_BOOLEAN),
+      }))
+    );
+  }
+
+  #[test]
+  fn deduplicate_quads() {
+    let dataset = nquads::parse_nquads(
+      "_:a <http://example.org/p> _:b .\n\
+_:b <http://example.org/p> \"x\" .\n\
+_:a <http://example.org/p> _:b .\n",
+    );
+    let canonized = canonize(&dataset, "URDNA2015").unwrap();
+    assert_eq!(canonized.lines().count(), 3);
+
+    let options = CanonizeOptions {
+      deduplicate_quads: true,
+      ..Default::default()
+    };
+    let deduplicated = canonize_with_options(&dataset, "URDNA2015", &options).unwrap();
+    assert_eq!(deduplicated.lines().count(), 2);
+    let mut unique = dataset.clone();
+    unique.quads.pop();
+    assert_eq!(deduplicated, canonize(&unique, "URDNA2015").unwrap());
+  }
+
+  #[test]
+  fn canonize_selected_graphs() {
+    let dataset = nquads::parse_nquads(
+      "_:vc <http://example.org/issuer> <http://example.org/i> .\n\
+_:vc <http://example.org/subject> _:s <http://example.org/credential> .\n\
+_:s <http://example.org/name> \"s\" <http://example.org/credential> .\n\
+_:p <http://example.org/signature> \"xyz\" _:proof .\n",
+    );
+    let credential = nquads::Graph {
+      term_type: nquads::TermType::NamedNode,
+      value: String::from("http://example.org/credential"),
+    };
+    let default_graph = nquads::Graph {
+      term_type: nquads::TermType::DefaultGraph,
+      value: String::new(),
+    };
+
+    let mut options = CanonizeOptions {
+      graphs: Some(vec![credential.clone()]),
+      ..Default::default()
+    };
+    assert_eq!(
+      canonize_with_options(&dataset, "URDNA2015", &options).unwrap(),
+      "_:c14n0 <http://example.org/name> \"s\" <http://example.org/credential> .\n\
+_:c14n1 <http://example.org/subject> _:c14n0 <http://example.org/credential> .\n"
+    );
+
+    // the selected graph canonicalizes like a dataset of its own triples
+    options.move_to_default_graph = true;
+    let triples = ntriples::parse_ntriples(
+      "_:x <http://example.org/subject> _:y .\n\
+_:y <http://example.org/name> \"s\" .\n",
+    );
+    let canonized = canonize_with_options(&dataset, "URDNA2015", &options).unwrap();
+    assert_eq!(canonized, canonize(&triples, "URDNA2015").unwrap());
+    assert_eq!(
+      canonize_ntriples(&dataset, "URDNA2015", &options).unwrap(),
+      canonized
+    );
+
+    // the default graph is selected by its term type
+    options.graphs = Some(vec![default_graph, credential]);
+    options.move_to_default_graph = false;
+    let canonized = canonize_with_options(&dataset, "URDNA2015", &options).unwrap();
+    assert_eq!(canonized.lines().count(), 3);
+    assert!(!canonized.contains("signature"));
+    assert!(canonize_ntriples(&dataset, "URDNA2015", &options).is_err());
+  }
+
+  #[test]
+  fn canonize_sorts_hash_paths_by_code_point() {
+    // natural sort orders the hash paths of this graph differently
+    let dataset = nquads::parse_nquads(
+      "_:n0 <http://example.org/p> _:n1 .\n\
+_:n1 <http://example.org/p> _:n2 .\n\
+_:n2 <http://example.org/p> _:n3 .\n\
+_:n3 <http://example.org/p> _:n4 .\n\
+_:n4 <http://example.org/p> _:n0 .\n\
+_:n4 <http://example.org/q> _:n1 .\n",
+    );
+    assert_eq!(
+      canonize(&dataset, "URDNA2015").unwrap(),
+      "_:c14n0 <http://example.org/p> _:c14n2 .\n\
+_:c14n0 <http://example.org/q> _:c14n1 .\n\
+_:c14n1 <http://example.org/p> _:c14n3 .\n\
+_:c14n2 <http://example.org/p> _:c14n1 .\n\
+_:c14n3 <http://example.org/p> _:c14n4 .\n\
+_:c14n4 <http://example.org/p> _:c14n0 .\n"
+    );
+  }
+
+  #[test]
+  fn canonize_references_quads_once_per_blank_node() {
+    // `_:a p _:a` counts once in the first degree hash of _:a
+    let dataset = nquads::parse_nquads(
+      "_:a <http://example.org/p> _:a .\n\
+_:a <http://example.org/p> _:b .\n\
+_:b <http://example.org/p> _:a .\n\
+_:b <http://example.org/q> _:b .\n\
+_:c <http://example.org/p> _:a .\n",
+    );
+    assert_eq!(
+      canonize(&dataset, "URDNA2015").unwrap(),
+      "_:c14n0 <http://example.org/p> _:c14n0 .\n\
+_:c14n0 <http://example.org/p> _:c14n1 .\n\
+_:c14n1 <http://example.org/p> _:c14n0 .\n\
+_:c14n1 <http://example.org/q> _:c14n1 .\n\
+_:c14n2 <http://example.org/p> _:c14n0 .\n"
+    );
+  }
+
+  #[test]
+  fn canonize_input_with_canonical_labels() {
+    // an input label that looks canonical is relabeled like any other
+    let a = nquads::parse_nquads(
+      "_:c14n0 <http://example.org/p> _:x .\n\
+_:x <http://example.org/q> \"x\" .\n",
+    );
+    let b = nquads::parse_nquads(
+      "_:y <http://example.org/p> _:x .\n\
+_:x <http://example.org/q> \"x\" .\n",
+    );
+    let canonized = canonize(&a, "URDNA2015").unwrap();
+    assert_eq!(canonized, canonize(&b, "URDNA2015").unwrap());
+    assert_eq!(
+      canonized,
+      "_:c14n0 <http://example.org/q> \"x\" .\n\
+_:c14n1 <http://example.org/p> _:c14n0 .\n"
+    );
+  }
+
+  #[test]
+  fn canonize_triple_terms() {
+    let a = nquads::parse_nquads(
+      "_:x <http://example.org/says> <<( _:y <http://example.org/knows> _:x )>> .\n\
+_:y <http://example.org/name> \"y\" .\n",
+    );
+    let b = nquads::parse_nquads(
+      "_:b1 <http://example.org/name> \"y\" .\n\
+_:b0 <http://example.org/says> <<( _:b1 <http://example.org/knows> _:b0 )>> .\n",
+    );
+    let canonized = canonize(&a, "URDNA2015").unwrap();
+    assert_eq!(canonized, canonize(&b, "URDNA2015").unwrap());
+    assert_eq!(
+      canonized,
+      "_:c14n0 <http://example.org/name> \"y\" .\n\
+_:c14n1 <http://example.org/says> <<( _:c14n0 <http://example.org/knows> _:c14n1 )>> .\n"
+    );
+  }
+
+  #[test]
+  fn canonize_directional_language_tags() {
+    let a = nquads::parse_nquads(
+      "_:x <http://example.org/label> \"abc\"@ar--rtl .\n\
+_:y <http://example.org/label> \"abc\"@ar--ltr .\n",
+    );
+    let b = nquads::parse_nquads(
+      "_:x <http://example.org/label> \"abc\"@ar--ltr .\n\
+_:y <http://example.org/label> \"abc\"@ar--rtl .\n",
+    );
+    let canonized = canonize(&a, "URDNA2015").unwrap();
+    // the direction is part of the first degree hash, so the blank nodes are
+    // told apart by it rather than by their input labels
+    assert_eq!(canonized, canonize(&b, "URDNA2015").unwrap());
+    assert_eq!(canonized.matches("\"abc\"@ar--rtl").count(), 1);
+    assert_eq!(canonized.matches("\"abc\"@ar--ltr").count(), 1);
+  }
+
+  #[test]
+  fn canonize_ntriples_requires_a_single_graph() {
+    let dataset = ntriples::parse_ntriples("_:x <http://example.org/p> _:y .\n");
+    let canonized = canonize_ntriples(&dataset, "URDNA2015", &CanonizeOptions::default()).unwrap();
+    assert_eq!(canonized, "_:c14n1 <http://example.org/p> _:c14n0 .\n");
+    assert_eq!(ntriples::parse_ntriples(&canonized).quads.len(), 1);
+
+    let dataset = nquads::parse_nquads("_:x <http://example.org/p> _:y <http://example.org/g> .");
+    assert_eq!(
+      canonize_ntriples(&dataset, "URDNA2015", &CanonizeOptions::default()),
+      Err(CanonizeError::NamedGraph(ntriples::NamedGraphError {
+        graph: String::from("<http://example.org/g>")
+      }))
+    );
+  }
+
+  #[test]
+  fn canonize_quad_sources() {
+    let input = "_:x <http://example.org/p> _:y .\n\
+_:y <http://example.org/p> \"y\" <http://example.org/g> .\n";
+    let dataset = nquads::parse_nquads(input);
+    let expected = canonize(&dataset, "URDNA2015").unwrap();
+
+    assert_eq!(canonize_quads(&dataset, "URDNA2015").unwrap(), expected);
+    assert_eq!(
+      canonize_quads(&dataset.quads[..], "URDNA2015").unwrap(),
+      expected
+    );
+
+    // quads borrowing their terms from somewhere else
+    let refs: Vec<nquads::QuadRef> = dataset
+      .quads
+      .iter()
+      .map(|quad| nquads::QuadRef {
+        subject: &quad.subject,
+        predicate: &quad.predicate,
+        object: &quad.object,
+        graph: &quad.graph,
+      })
+      .collect();
+    assert_eq!(canonize_quads(refs, "URDNA2015").unwrap(), expected);
+
+    // quads produced one at a time
+    let streamed = input.lines().map(nquads::parse_nquad);
+    assert_eq!(canonize_quads(streamed, "URDNA2015").unwrap(), expected);
+
+    assert_eq!(canonize_quads(&dataset, "URGNA2012"), None);
+  }
+
+  #[test]
+  fn canonize_to_dataset() {
+    let dataset = nquads::parse_nquads(
+      "_:x <http://example.org/says> <<( _:y <http://example.org/knows> _:x )>> _:g .\n\
+_:y <http://example.org/name> \"y\"@en .\n\
+<http://example.org/s> <http://example.org/p> _:y _:g .\n",
+    );
+    let canonical = canonize_dataset(&dataset, "URDNA2015").unwrap();
+    assert_eq!(canonical.nquads, canonize(&dataset, "URDNA2015").unwrap());
+    assert_eq!(canonical.dataset, nquads::parse_nquads(&canonical.nquads));
+
+    let lines: Vec<String> = canonical
+      .dataset
+      .quads
+      .iter()
+      .map(nquads::serialize_quad)
+      .collect();
+    assert_eq!(lines.concat(), canonical.nquads);
+    for quad in &canonical.dataset.quads {
+      let mut labels = quad.object.nested_blank_nodes();
+      for (term_type, value) in &[
+        (quad.subject.term_type, &quad.subject.value),
+        (quad.object.term_type, &quad.object.value),
+        (quad.graph.term_type, &quad.graph.value),
+      ] {
+        if *term_type == nquads::TermType::BlankNode {
+          labels.push(value);
+        }
+      }
+      assert!(labels.iter().all(|label| label.starts_with("_:c14n")));
+    }
+
+    assert_eq!(canonize_dataset(&dataset, "URGNA2012"), None);
+  }
+
+  #[test]
+  fn unknown_algorithm() {
+    let dataset = nquads::Dataset::default();
+    assert_eq!(canonize(&dataset, "URGNA2012"), None);
+    assert_eq!(
+      canonize_with_options(&dataset, "URGNA2012", &CanonizeOptions::default()),
+      Err(CanonizeError::UnknownAlgorithm(String::from("URGNA2012")))
+    );
+  }
+}
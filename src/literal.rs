@@ -0,0 +1,400 @@
+use crate::nquads::{Dataset, Object, TermType};
+
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+
+// XSD constants
+pub const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+pub const XSD_DATETIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+pub const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+pub const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+pub const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+
+lazy_static! {
+  // lexical spaces from https://www.w3.org/TR/xmlschema11-2/
+  static ref INTEGER: Regex = Regex::new(r"^([+-])?([0-9]+)$").unwrap();
+  static ref DECIMAL: Regex =
+    Regex::new(r"^([+-])?(?:([0-9]+)(?:\.([0-9]*))?|\.([0-9]+))$").unwrap();
+  static ref DOUBLE: Regex =
+    Regex::new(r"^(?:[+-]?(?:[0-9]+(?:\.[0-9]*)?|\.[0-9]+)(?:[eE][+-]?[0-9]+)?|[+-]?INF|NaN)$")
+      .unwrap();
+  static ref DATETIME: Regex = Regex::new(concat!(
+    r"^(-?[0-9]{4,})-([0-9]{2})-([0-9]{2})",
+    r"T([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.([0-9]+))?",
+    r"(Z|[+-][0-9]{2}:[0-9]{2})?$"
+  ))
+  .unwrap();
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiteralError {
+  pub value: String,
+  pub datatype: String,
+}
+
+impl fmt::Display for LiteralError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "\"{}\" is not a valid lexical form of <{}>",
+      self.value, self.datatype
+    )
+  }
+}
+
+impl Error for LiteralError {}
+
+// Returns the canonical lexical form of `value` for `datatype`, following the
+// canonical mappings of XML Schema 1.1. Values of datatypes other than
+// xsd:integer, xsd:decimal, xsd:boolean, xsd:double and xsd:dateTime are
+// returned unchanged.
+pub fn canonical_lexical_form(value: &str, datatype: &str) -> Result<String, LiteralError> {
+  // all of the supported datatypes collapse whitespace
+  let trimmed = value.trim_matches(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r');
+  let canonical = match datatype {
+    XSD_INTEGER => canonical_integer(trimmed),
+    XSD_DECIMAL => canonical_decimal(trimmed),
+    XSD_BOOLEAN => canonical_boolean(trimmed),
+    XSD_DOUBLE => canonical_double(trimmed),
+    XSD_DATETIME => canonical_datetime(trimmed),
+    _ => return Ok(value.to_string()),
+  };
+
+  canonical.ok_or_else(|| LiteralError {
+    value: value.to_string(),
+    datatype: datatype.to_string(),
+  })
+}
+
+// Rewrites every typed literal in `dataset` to its canonical lexical form,
+// including those nested inside triple terms.
+pub fn canonicalize_dataset(dataset: &mut Dataset) -> Result<(), LiteralError> {
+  for quad in &mut dataset.quads {
+    canonicalize_object(&mut quad.object)?;
+  }
+
+  Ok(())
+}
+
+fn canonicalize_object(object: &mut Object) -> Result<(), LiteralError> {
+  if object.term_type == TermType::Literal {
+    if let Some(datatype) = &object.datatype {
+      object.value = canonical_lexical_form(&object.value, datatype)?;
+    }
+  }
+  // only the object of a triple term can be a literal
+  if let Some(triple) = &mut object.triple {
+    canonicalize_object(&mut triple.object)?;
+  }
+
+  Ok(())
+}
+
+fn canonical_integer(value: &str) -> Option<String> {
+  let group = INTEGER.captures(value)?;
+  let negative = group.get(1).map(|sign| sign.as_str()) == Some("-");
+  let digits = group.get(2).unwrap().as_str().trim_start_matches('0');
+
+  if digits.is_empty() {
+    return Some(String::from("0"));
+  }
+
+  let mut canonical = String::with_capacity(digits.len() + 1);
+  if negative {
+    canonical.push('-');
+  }
+  canonical.push_str(digits);
+
+  Some(canonical)
+}
+
+fn canonical_decimal(value: &str) -> Option<String> {
+  let group = DECIMAL.captures(value)?;
+  let negative = group.get(1).map(|sign| sign.as_str()) == Some("-");
+  let integer = group
+    .get(2)
+    .map_or("", |m| m.as_str())
+    .trim_start_matches('0');
+  let fraction = group
+    .get(3)
+    .or_else(|| group.get(4))
+    .map_or("", |m| m.as_str())
+    .trim_end_matches('0');
+
+  // integral values have no decimal point in XSD 1.1
+  if integer.is_empty() && fraction.is_empty() {
+    return Some(String::from("0"));
+  }
+
+  let mut canonical = String::with_capacity(integer.len() + fraction.len() + 3);
+  if negative {
+    canonical.push('-');
+  }
+  if integer.is_empty() {
+    canonical.push('0');
+  } else {
+    canonical.push_str(integer);
+  }
+  if !fraction.is_empty() {
+    canonical.push('.');
+    canonical.push_str(fraction);
+  }
+
+  Some(canonical)
+}
+
+fn canonical_boolean(value: &str) -> Option<String> {
+  match value {
+    "true" | "1" => Some(String::from("true")),
+    "false" | "0" => Some(String::from("false")),
+    _ => None,
+  }
+}
+
+fn canonical_double(value: &str) -> Option<String> {
+  if !DOUBLE.is_match(value) {
+    return None;
+  }
+
+  let double: f64 = match value {
+    "INF" | "+INF" => f64::INFINITY,
+    "-INF" => f64::NEG_INFINITY,
+    "NaN" => f64::NAN,
+    _ => value.parse().ok()?,
+  };
+
+  if double.is_nan() {
+    return Some(String::from("NaN"));
+  } else if double.is_infinite() {
+    return Some(String::from(if double > 0.0 { "INF" } else { "-INF" }));
+  }
+
+  // `{:e}` gives the shortest mantissa that round-trips, e.g. "1.5e3" or "1e0";
+  // the canonical form always has a fractional part and an uppercase `E`.
+  let formatted = format!("{:e}", double);
+  let (mantissa, exponent) = formatted.split_at(formatted.find('e').unwrap());
+  let mut canonical = String::with_capacity(formatted.len() + 2);
+  canonical.push_str(mantissa);
+  if !mantissa.contains('.') {
+    canonical.push_str(".0");
+  }
+  canonical.push('E');
+  canonical.push_str(&exponent[1..]);
+
+  Some(canonical)
+}
+
+fn canonical_datetime(value: &str) -> Option<String> {
+  let group = DATETIME.captures(value)?;
+  let year_str = group.get(1).unwrap().as_str();
+  // years may not have leading zeros beyond four digits
+  let unsigned_year = year_str.trim_start_matches('-');
+  if unsigned_year.len() > 4 && unsigned_year.starts_with('0') {
+    return None;
+  }
+  let mut year: i64 = year_str.parse().ok()?;
+  let mut month: i64 = group.get(2).unwrap().as_str().parse().ok()?;
+  let mut day: i64 = group.get(3).unwrap().as_str().parse().ok()?;
+  let mut hour: i64 = group.get(4).unwrap().as_str().parse().ok()?;
+  let mut minute: i64 = group.get(5).unwrap().as_str().parse().ok()?;
+  let second: i64 = group.get(6).unwrap().as_str().parse().ok()?;
+  let fraction = group
+    .get(7)
+    .map_or("", |m| m.as_str())
+    .trim_end_matches('0');
+  let timezone = group.get(8).map(|m| m.as_str());
+
+  if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+    return None;
+  }
+  if hour == 24 {
+    // 24:00:00 is only allowed as an alias for midnight of the next day
+    if minute != 0 || second != 0 || !fraction.is_empty() {
+      return None;
+    }
+  } else if hour > 23 || minute > 59 || second > 59 {
+    return None;
+  }
+
+  // normalize timezoned values to UTC
+  if let Some(timezone) = timezone {
+    if timezone != "Z" {
+      let offset_hours: i64 = timezone[1..3].parse().ok()?;
+      let offset_minutes: i64 = timezone[4..6].parse().ok()?;
+      if offset_minutes > 59 || offset_hours * 60 + offset_minutes > 14 * 60 {
+        return None;
+      }
+      let offset = offset_hours * 60 + offset_minutes;
+      if timezone.starts_with('+') {
+        minute -= offset;
+      } else {
+        minute += offset;
+      }
+    }
+  }
+
+  // carry minutes into hours and hours into days
+  hour += minute.div_euclid(60);
+  minute = minute.rem_euclid(60);
+  day += hour.div_euclid(24);
+  hour = hour.rem_euclid(24);
+  while day > days_in_month(year, month) {
+    day -= days_in_month(year, month);
+    month += 1;
+    if month > 12 {
+      month = 1;
+      year += 1;
+    }
+  }
+  while day < 1 {
+    month -= 1;
+    if month < 1 {
+      month = 12;
+      year -= 1;
+    }
+    day += days_in_month(year, month);
+  }
+
+  let mut canonical = String::with_capacity(value.len());
+  if year < 0 {
+    canonical.push('-');
+  }
+  canonical.push_str(&format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+    year.abs(),
+    month,
+    day,
+    hour,
+    minute,
+    second
+  ));
+  if !fraction.is_empty() {
+    canonical.push('.');
+    canonical.push_str(fraction);
+  }
+  if timezone.is_some() {
+    canonical.push('Z');
+  }
+
+  Some(canonical)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+  match month {
+    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads::parse_nquads;
+
+  fn canonical(value: &str, datatype: &str) -> String {
+    canonical_lexical_form(value, datatype).unwrap()
+  }
+
+  #[test]
+  fn integers() {
+    assert_eq!(canonical("01", XSD_INTEGER), "1");
+    assert_eq!(canonical("+42", XSD_INTEGER), "42");
+    assert_eq!(canonical("-000", XSD_INTEGER), "0");
+    assert_eq!(canonical("-0012", XSD_INTEGER), "-12");
+    assert_eq!(
+      canonical("123456789012345678901234567890", XSD_INTEGER),
+      "123456789012345678901234567890"
+    );
+    assert!(canonical_lexical_form("1.0", XSD_INTEGER).is_err());
+    assert!(canonical_lexical_form("", XSD_INTEGER).is_err());
+  }
+
+  #[test]
+  fn decimals() {
+    assert_eq!(canonical("01.50", XSD_DECIMAL), "1.5");
+    assert_eq!(canonical("+.5", XSD_DECIMAL), "0.5");
+    assert_eq!(canonical("2.", XSD_DECIMAL), "2");
+    assert_eq!(canonical("-0.0", XSD_DECIMAL), "0");
+    assert_eq!(canonical("-10.010", XSD_DECIMAL), "-10.01");
+    assert!(canonical_lexical_form(".", XSD_DECIMAL).is_err());
+    assert!(canonical_lexical_form("1e3", XSD_DECIMAL).is_err());
+  }
+
+  #[test]
+  fn booleans() {
+    assert_eq!(canonical("1", XSD_BOOLEAN), "true");
+    assert_eq!(canonical("false", XSD_BOOLEAN), "false");
+    assert!(canonical_lexical_form("TRUE", XSD_BOOLEAN).is_err());
+  }
+
+  #[test]
+  fn doubles() {
+    assert_eq!(canonical("1", XSD_DOUBLE), "1.0E0");
+    assert_eq!(canonical("1500.0", XSD_DOUBLE), "1.5E3");
+    assert_eq!(canonical("0.00015", XSD_DOUBLE), "1.5E-4");
+    assert_eq!(canonical("-0", XSD_DOUBLE), "-0.0E0");
+    assert_eq!(canonical("+INF", XSD_DOUBLE), "INF");
+    assert_eq!(canonical("NaN", XSD_DOUBLE), "NaN");
+    assert!(canonical_lexical_form("inf", XSD_DOUBLE).is_err());
+    assert!(canonical_lexical_form("1.0E", XSD_DOUBLE).is_err());
+  }
+
+  #[test]
+  fn datetimes() {
+    assert_eq!(
+      canonical("2018-12-21T23:40:20Z", XSD_DATETIME),
+      "2018-12-21T23:40:20Z"
+    );
+    assert_eq!(
+      canonical("2018-12-21T23:40:20.500+00:00", XSD_DATETIME),
+      "2018-12-21T23:40:20.5Z"
+    );
+    assert_eq!(
+      canonical("2018-12-31T23:40:20-01:30", XSD_DATETIME),
+      "2019-01-01T01:10:20Z"
+    );
+    assert_eq!(
+      canonical("2020-03-01T00:30:00+01:00", XSD_DATETIME),
+      "2020-02-29T23:30:00Z"
+    );
+    assert_eq!(
+      canonical("2018-12-21T24:00:00", XSD_DATETIME),
+      "2018-12-22T00:00:00"
+    );
+    assert!(canonical_lexical_form("2019-02-29T00:00:00Z", XSD_DATETIME).is_err());
+    assert!(canonical_lexical_form("2018-12-21T24:00:01", XSD_DATETIME).is_err());
+    assert!(canonical_lexical_form("2018-12-21", XSD_DATETIME).is_err());
+  }
+
+  #[test]
+  fn canonicalize_dataset_in_triple_terms() {
+    let mut dataset = parse_nquads(
+      "_:a <http://example.org/p> \"01\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:a <http://example.org/p> <<( _:b <http://example.org/p> \"+2\"^^<http://www.w3.org/2001/XMLSchema#integer> )>> .\n",
+    );
+    canonicalize_dataset(&mut dataset).unwrap();
+    assert_eq!(
+      dataset,
+      parse_nquads(
+        "_:a <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:a <http://example.org/p> <<( _:b <http://example.org/p> \"2\"^^<http://www.w3.org/2001/XMLSchema#integer> )>> .\n"
+      )
+    );
+
+    let mut dataset = parse_nquads(
+      "_:a <http://example.org/p> <<( _:b <http://example.org/p> \"x\"^^<http://www.w3.org/2001/XMLSchema#integer> )>> .\n",
+    );
+    assert!(canonicalize_dataset(&mut dataset).is_err());
+  }
+
+  #[test]
+  fn other_datatypes_are_unchanged() {
+    assert_eq!(
+      canonical(" 01 ", "http://www.w3.org/2001/XMLSchema#string"),
+      " 01 "
+    );
+  }
+}
@@ -2,10 +2,16 @@ extern crate rdf_canonize;
 
 use std::env;
 use std::fs;
+use std::process;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
+    if args.len() > 1 && args[1] == "generate" {
+        generate(&args[2..]);
+        return;
+    }
+
     let filename = &args[1];
 
     println!("Filename {}", filename);
@@ -21,3 +27,59 @@ fn main() {
     println!("OUTPUT:");
     println!("{}", serialized_nquads);
 }
+
+const GENERATE_USAGE: &str =
+    "usage: rdf-canonize generate <chain|star|clique|regular|credentials> <size> [--seed <n>] [--degree <n>]";
+
+// rdf-canonize generate <shape> <size> [--seed <n>] [--degree <n>]
+//
+// Prints a synthetic dataset as N-Quads. `--degree` sets the degree of a
+// regular graph (3 by default) and `--seed` the random seed (0 by default).
+#[cfg(feature = "rand")]
+fn generate(args: &[String]) {
+    use rdf_canonize::generate::{generate_nquads, Shape};
+
+    let fail = |message: &str| -> ! {
+        eprintln!("{}", message);
+        process::exit(2)
+    };
+    let number = |value: Option<&String>| -> u64 {
+        match value.map(|value| value.parse()) {
+            Some(Ok(number)) => number,
+            _ => fail(GENERATE_USAGE),
+        }
+    };
+
+    if args.len() < 2 {
+        fail(GENERATE_USAGE);
+    }
+    let mut shape: Shape = args[0].parse().unwrap_or_else(|e| fail(&format!("{}", e)));
+    let size = number(args.get(1)) as usize;
+    let mut seed = 0;
+    let mut degree = None;
+    let mut rest = args[2..].iter();
+    while let Some(flag) = rest.next() {
+        match flag.as_str() {
+            "--seed" => seed = number(rest.next()),
+            "--degree" => degree = Some(number(rest.next()) as usize),
+            _ => fail(GENERATE_USAGE),
+        }
+    }
+    match (shape, degree) {
+        (Shape::Regular(_), Some(degree)) => shape = Shape::Regular(degree),
+        (_, Some(_)) => fail("--degree only applies to regular graphs"),
+        _ => (),
+    }
+
+    match generate_nquads(shape, size, seed) {
+        Ok(nquads) => print!("{}", nquads),
+        Err(e) => fail(&format!("{}", e)),
+    }
+}
+
+#[cfg(not(feature = "rand"))]
+fn generate(_: &[String]) {
+    eprintln!("{}", GENERATE_USAGE);
+    eprintln!("the generate subcommand requires the `rand` feature");
+    process::exit(2);
+}
@@ -1,30 +1,35 @@
 extern crate regex;
 
+use crate::lexer::{Lexer, Token};
+
 use regex::Regex;
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 
 // define default capacities
 pub const DEFAULT_NQUAD_CAPACITY: usize = 256;
 pub const DEFAULT_TERM_CAPACITY: usize = 64;
 
-// define partial regexes
-const IRI: &str = "(?:<([^:]+:[^>]*)>)";
-const PLAIN: &str = "\"([^\"\\\\]*(?:\\\\.[^\"\\\\]*)*)\"";
-const LANGUAGE: &str = "(?:@([a-zA-Z]+(?:-[a-zA-Z0-9]+)*))";
-const WS: &str = "[ \\t]+";
-const WSO: &str = "[ \\t]*";
-
 // XSD constants
-const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+pub const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
 
 // RDF constants
-const RDF_LANGSTRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
-
-#[derive(Copy, Clone, Debug, PartialEq)]
+pub const RDF_LANGSTRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+pub const RDF_DIRLANGSTRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#dirLangString";
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+pub const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+pub const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum TermType {
   BlankNode,
   NamedNode,
   Literal,
+  // an RDF 1.2 triple term, only valid in object position
+  Triple,
   DefaultGraph,
   None,
 }
@@ -37,7 +42,7 @@ pub trait Term {
   fn set_value(&mut self, value: &str);
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Subject {
   pub term_type: TermType,
   pub value: String,
@@ -68,7 +73,7 @@ impl Term for Subject {
   }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Predicate {
   pub term_type: TermType,
   pub value: String,
@@ -99,12 +104,17 @@ impl Term for Predicate {
   }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Object {
   pub term_type: TermType,
   pub value: String,
   pub datatype: Option<String>,
   pub language: Option<String>,
+  // the base direction (`ltr` or `rtl`) of an RDF 1.2 directional
+  // language-tagged string
+  pub direction: Option<String>,
+  // the quoted triple when `term_type` is `TermType::Triple`
+  pub triple: Option<Box<Triple>>,
 }
 
 impl Term for Object {
@@ -114,6 +124,8 @@ impl Term for Object {
       value: String::from(""),
       datatype: None,
       language: None,
+      direction: None,
+      triple: None,
     }
   }
 
@@ -143,6 +155,14 @@ impl Object {
     self.language = Some(language.to_string());
   }
 
+  pub fn get_direction(&self) -> Option<String> {
+    self.direction.clone()
+  }
+
+  pub fn set_direction(&mut self, direction: &str) {
+    self.direction = Some(direction.to_string());
+  }
+
   pub fn get_datatype(&self) -> Option<String> {
     self.datatype.clone()
   }
@@ -150,9 +170,82 @@ impl Object {
   pub fn set_datatype(&mut self, datatype: &str) {
     self.datatype = Some(datatype.to_string());
   }
+
+  // Returns the identifiers of the blank nodes nested inside a triple term.
+  pub fn nested_blank_nodes(&self) -> Vec<&str> {
+    let mut ids = Vec::new();
+    if let Some(triple) = &self.triple {
+      triple.collect_blank_nodes(&mut ids);
+    }
+    ids
+  }
+
+  // Returns a copy of the object with every blank node identifier, including
+  // those nested inside a triple term, replaced by `f(identifier)`.
+  pub fn map_blank_nodes<F>(&self, f: &mut F) -> Object
+  where
+    F: FnMut(&str) -> String,
+  {
+    Object {
+      term_type: self.term_type,
+      value: if self.term_type == TermType::BlankNode {
+        f(&self.value)
+      } else {
+        self.value.clone()
+      },
+      datatype: self.get_datatype(),
+      language: self.get_language(),
+      direction: self.get_direction(),
+      triple: self
+        .triple
+        .as_ref()
+        .map(|triple| Box::new(triple.map_blank_nodes(f))),
+    }
+  }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Triple {
+  pub subject: Subject,
+  pub predicate: Predicate,
+  pub object: Object,
+}
+
+impl Triple {
+  pub fn map_blank_nodes<F>(&self, f: &mut F) -> Triple
+  where
+    F: FnMut(&str) -> String,
+  {
+    let subject = Subject {
+      term_type: self.subject.term_type,
+      value: if self.subject.term_type == TermType::BlankNode {
+        f(&self.subject.value)
+      } else {
+        self.subject.value.clone()
+      },
+    };
+
+    Triple {
+      subject,
+      predicate: self.predicate.clone(),
+      object: self.object.map_blank_nodes(f),
+    }
+  }
+
+  fn collect_blank_nodes<'a>(&'a self, ids: &mut Vec<&'a str>) {
+    if self.subject.term_type == TermType::BlankNode {
+      ids.push(&self.subject.value);
+    }
+    if self.object.term_type == TermType::BlankNode {
+      ids.push(&self.object.value);
+    }
+    if let Some(triple) = &self.object.triple {
+      triple.collect_blank_nodes(ids);
+    }
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Graph {
   pub term_type: TermType,
   pub value: String,
@@ -190,6 +283,52 @@ pub trait QuadSerialize<'a> {
   fn get_graph(&'a self) -> &'a Graph;
 }
 
+impl<'a, T> QuadSerialize<'a> for &T
+where
+  T: QuadSerialize<'a>,
+{
+  fn get_subject(&'a self) -> &'a Subject {
+    (**self).get_subject()
+  }
+
+  fn get_predicate(&'a self) -> &'a Predicate {
+    (**self).get_predicate()
+  }
+
+  fn get_object(&'a self) -> &'a Object {
+    (**self).get_object()
+  }
+
+  fn get_graph(&'a self) -> &'a Graph {
+    (**self).get_graph()
+  }
+}
+
+// A source of quads for the canonicalizer. It is implemented for everything
+// that can be iterated into quad-like values, e.g. `&Dataset`, `&[Quad]`, a
+// `Vec<QuadRef>` borrowing the terms of a store, or an iterator over quads
+// read from a stream. The canonicalizer keeps the yielded values (references
+// for the borrowing sources) rather than copying them into a `Dataset`.
+pub trait QuadSource {
+  type Quad: for<'a> QuadSerialize<'a>;
+  type Quads: Iterator<Item = Self::Quad>;
+
+  fn quads(self) -> Self::Quads;
+}
+
+impl<I> QuadSource for I
+where
+  I: IntoIterator,
+  I::Item: for<'a> QuadSerialize<'a>,
+{
+  type Quad = I::Item;
+  type Quads = I::IntoIter;
+
+  fn quads(self) -> Self::Quads {
+    self.into_iter()
+  }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct QuadRef<'a> {
   pub subject: &'a Subject,
@@ -216,7 +355,7 @@ impl QuadSerialize<'_> for QuadRef<'_> {
   }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Quad {
   pub subject: Subject,
   pub predicate: Predicate,
@@ -266,6 +405,100 @@ pub struct Dataset {
   pub quads: QuadSet,
 }
 
+// A dataset is a set of quads. `quads` may still hold duplicates, since the
+// parsers keep every statement they read; the methods below treat the dataset
+// as a set and `dedup` removes the duplicates.
+//
+// `quads` is a plain vector, so `contains`, `insert` and `remove` scan every
+// quad, and building a dataset of n quads with `insert` takes O(n^2) time. To
+// build a large dataset, push the quads and call `dedup` once, which hashes
+// them; for repeated lookups, load the quads into an
+// `indexed_dataset::IndexedDataset`.
+impl Dataset {
+  pub fn contains(&self, quad: &Quad) -> bool {
+    self.quads.contains(quad)
+  }
+
+  // Adds the quad unless the dataset already contains it. Returns whether it
+  // was added.
+  pub fn insert(&mut self, quad: Quad) -> bool {
+    if self.contains(&quad) {
+      return false;
+    }
+    self.quads.push(quad);
+    true
+  }
+
+  // Removes every copy of the quad. Returns whether the dataset contained it.
+  pub fn remove(&mut self, quad: &Quad) -> bool {
+    let len = self.quads.len();
+    self.quads.retain(|q| q != quad);
+    self.quads.len() != len
+  }
+
+  // Keeps only the quads of the given graphs. Any graph with the term type
+  // `DefaultGraph` selects the default graph.
+  pub fn retain_graphs(&mut self, graphs: &[Graph]) {
+    let default_graph = graphs
+      .iter()
+      .any(|graph| graph.term_type == TermType::DefaultGraph);
+    self.quads.retain(|quad| {
+      if quad.graph.term_type == TermType::DefaultGraph {
+        default_graph
+      } else {
+        graphs.contains(&quad.graph)
+      }
+    });
+  }
+
+  // Moves every quad to the default graph. Quads that were in different
+  // graphs may become duplicates.
+  pub fn move_to_default_graph(&mut self) {
+    for quad in &mut self.quads {
+      quad.graph = Graph {
+        term_type: TermType::DefaultGraph,
+        value: String::from("@default"),
+      };
+    }
+  }
+
+  // Removes duplicate quads, keeping the first copy of each in place.
+  pub fn dedup(&mut self) {
+    let mut seen = HashSet::with_capacity(self.quads.len());
+    let first: Vec<bool> = self.quads.iter().map(|quad| seen.insert(quad)).collect();
+    let mut first = first.into_iter();
+    self.quads.retain(|_| first.next().unwrap());
+  }
+}
+
+impl<'a> IntoIterator for &'a Dataset {
+  type Item = &'a Quad;
+  type IntoIter = std::slice::Iter<'a, Quad>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.quads.iter()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} at line {}, column {}",
+      self.message, self.line, self.column
+    )
+  }
+}
+
+impl Error for ParseError {}
+
 pub fn serialize_quad<'a, T>(quad: &'a T) -> String
 where
   T: QuadSerialize<'a>,
@@ -277,6 +510,31 @@ where
 
   let mut nquad = String::with_capacity(DEFAULT_NQUAD_CAPACITY);
 
+  // append "subject predicate object"
+  push_triple(&mut nquad, s, p, o);
+
+  // graph can only be NamedNode or BlankNode (or DefaultGraph, but that
+  // does not add to `nquad`)
+  if g.term_type == TermType::NamedNode {
+    // append " <graph.value>"
+    nquad.push(' ');
+    nquad.push('<');
+    nquad.push_str(&g.value);
+    nquad.push('>');
+  } else if g.term_type == TermType::BlankNode {
+    // append " graph.value"
+    nquad.push(' ');
+    nquad.push_str(&g.value);
+  }
+
+  // append " .\n"
+  nquad.push(' ');
+  nquad.push('.');
+  nquad.push('\n');
+  nquad
+}
+
+pub(crate) fn push_triple(nquad: &mut String, s: &Subject, p: &Predicate, o: &Object) {
   // subject can only be NamedNode or BlankNode
   if s.term_type == TermType::NamedNode {
     // append "<subject.value>"
@@ -296,7 +554,11 @@ where
   nquad.push('>');
   nquad.push(' ');
 
-  // object is NamedNode, BlankNode, or Literal
+  // object is NamedNode, BlankNode, Literal or Triple
+  push_object(nquad, o);
+}
+
+pub(crate) fn push_object(nquad: &mut String, o: &Object) {
   if o.term_type == TermType::NamedNode {
     // append "<object.value>"
     nquad.push('<');
@@ -305,235 +567,268 @@ where
   } else if o.term_type == TermType::BlankNode {
     // append "object.value"
     nquad.push_str(&o.value)
+  } else if o.term_type == TermType::Triple {
+    // append "<<( subject predicate object )>>"
+    let triple = o.triple.as_ref().expect("triple term without a triple");
+    nquad.push_str("<<( ");
+    push_triple(nquad, &triple.subject, &triple.predicate, &triple.object);
+    nquad.push_str(" )>>");
   } else {
     // append "\"escape(object.value)\""
     nquad.push('\"');
     nquad.push_str(&escape_string(&o.value));
     nquad.push('\"');
     if let Some(datatype) = &o.datatype {
-      if datatype == RDF_LANGSTRING {
+      if datatype == RDF_LANGSTRING || datatype == RDF_DIRLANGSTRING {
         if let Some(language) = &o.language {
           // append "@language"
           nquad.push('@');
-          nquad.push_str(&language);
+          nquad.push_str(language);
+        }
+        if let Some(direction) = &o.direction {
+          // append "--direction"
+          nquad.push('-');
+          nquad.push('-');
+          nquad.push_str(direction);
         }
       } else if datatype != XSD_STRING {
         // append "^^<datatype>"
         nquad.push('^');
         nquad.push('^');
         nquad.push('<');
-        nquad.push_str(&datatype);
+        nquad.push_str(datatype);
         nquad.push('>');
       }
     }
   }
+}
 
-  // graph can only be NamedNode or BlankNode (or DefaultGraph, but that
-  // does not add to `nquad`)
-  if g.term_type == TermType::NamedNode {
-    // append " <graph.value>"
-    nquad.push(' ');
-    nquad.push('<');
-    nquad.push_str(&g.value);
-    nquad.push('>');
-  } else if g.term_type == TermType::BlankNode {
-    // append " graph.value"
-    nquad.push(' ');
-    nquad.push_str(&g.value);
-  }
+pub fn parse_nquads(dataset: &str) -> Dataset {
+  try_parse_nquads(dataset).unwrap_or_else(|e| panic!("{}", e))
+}
 
-  // append " .\n"
-  nquad.push(' ');
-  nquad.push('.');
-  nquad.push('\n');
-  nquad
+pub fn try_parse_nquads(dataset: &str) -> Result<Dataset, ParseError> {
+  parse_statements(dataset, true)
 }
 
-pub fn parse_nquads(dataset: &str) -> Dataset {
-  let quads = dataset.lines().map(parse_nquad).collect();
-
-  Dataset {quads}
-}
-
-lazy_static! {
-  // https://www.w3.org/TR/turtle/#grammar-production-BLANK_NODE_LABEL
-  static ref PN_CHARS_BASE: String = format!(
-    "{}{}{}{}{}{}{}{}{}{}{}{}{}",
-    "A-Z",
-    "a-z",
-    "\u{00C0}-\u{00D6}",
-    "\u{00D8}-\u{00F6}",
-    "\u{00F8}-\u{02FF}",
-    "\u{0370}-\u{037D}",
-    "\u{037F}-\u{1FFF}",
-    "\u{200C}-\u{200D}",
-    "\u{2070}-\u{218F}",
-    "\u{2C00}-\u{2FEF}",
-    "\u{3001}-\u{D7FF}",
-    "\u{F900}-\u{FDCF}",
-    "\u{FDF0}-\u{FFFD}"
-    // TODO:
-    // "\u{1000}0-\u{EFFF}F"
-  );
-  static ref PN_CHARS_U: String = format!(
-    "{}{}",
-    PN_CHARS_BASE.as_str(),
-    "_"
-  );
-  static ref PN_CHARS: String = format!(
-    "{}{}{}{}{}{}",
-    PN_CHARS_U.as_str(),
-    "0-9",
-    "-",
-    "\u{00B7}",
-    "\u{0300}-\u{036F}",
-    "\u{203F}-\u{2040}"
-  );
-  // define partial regexes
-  static ref BLANK_NODE_LABEL: String = format!(
-    "{}{}{}{}{}{}{}{}{}{}",
-    "(_:",
-      "(?:[", PN_CHARS_U.as_str(), "0-9])",
-      "(?:(?:[" , PN_CHARS.as_str() , ".])*(?:[" , PN_CHARS.as_str() , "]))?",
-    ")"
-  );
-  static ref BNODE: String = BLANK_NODE_LABEL.clone();
-  static ref DATATYPE: String = format!("{}{}{}", "(?:\\^\\^", IRI, ")");
-  static ref LITERAL: String = format!("(?:{}(?:{}|{})?)", PLAIN, DATATYPE.as_str(), LANGUAGE);
-
-  // define quad part regexes
-  static ref SUBJECT: String = format!("(?:{}|{}){}", IRI, BNODE.as_str(), WS);
-  static ref PROPERTY: String = format!("{}{}", IRI, WS);
-  static ref OBJECT: String = format!("(?:{}|{}|{}){}", IRI, BNODE.as_str(), LITERAL.as_str(), WSO);
-  static ref GRAPH: String = format!("(?:\\.|(?:(?:{}|{}){}\\.))", IRI, BNODE.as_str(), WSO);
-
-  // full quad regex
-  static ref QUAD: String = format!(
-      "^{}{}{}{}{}{}$",
-      WSO,
-      SUBJECT.as_str(),
-      PROPERTY.as_str(),
-      OBJECT.as_str(),
-      GRAPH.as_str(),
-      WSO
-  );
-
-
-  static ref QUAD_REGEX: Regex = Regex::new(&QUAD).unwrap();
+// Parses one statement per line; N-Triples is parsed by disallowing graph
+// names.
+pub(crate) fn parse_statements(dataset: &str, allow_graph: bool) -> Result<Dataset, ParseError> {
+  let mut quads = Vec::new();
+  for (i, line) in dataset.lines().enumerate() {
+    let mut parser = Parser::new(line, i + 1, allow_graph)?;
+    // skip empty and comment-only lines
+    if parser.is_at_end() {
+      continue;
+    }
+    quads.push(parser.statement()?);
+  }
+
+  Ok(Dataset { quads })
 }
 
 pub fn parse_nquad(serialized_triple: &str) -> Quad {
-  let group = QUAD_REGEX.captures(serialized_triple).unwrap();
+  try_parse_nquad(serialized_triple).unwrap_or_else(|e| panic!("{}", e))
+}
 
-  let subject = parse_subject(&group);
-  let predicate = parse_predicate(&group);
-  let object = parse_object(&group);
-  let graph = parse_graph(&group);
+pub fn try_parse_nquad(serialized_triple: &str) -> Result<Quad, ParseError> {
+  Parser::new(serialized_triple, 1, true)?.statement()
+}
 
-  Quad {
-    subject,
-    predicate,
-    object,
-    graph,
-  }
+// A recursive descent parser over the tokens of a single statement.
+struct Parser<'a> {
+  lexer: Lexer<'a>,
+  tokens: Vec<(usize, Token)>,
+  index: usize,
+  allow_graph: bool,
 }
 
-fn parse_subject(group: &regex::Captures) -> Subject {
-  let subject = match group.get(1) {
-    Some(value) => Subject {
-      term_type: TermType::NamedNode,
-      value: String::from(value.as_str()),
-    },
-    None => Subject {
-      term_type: TermType::BlankNode,
-      value: String::from(group.get(2).unwrap().as_str()),
-    },
-  };
+impl<'a> Parser<'a> {
+  fn new(line: &'a str, line_number: usize, allow_graph: bool) -> Result<Parser<'a>, ParseError> {
+    let mut lexer = Lexer::new(line, line_number);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+      tokens.push(token);
+    }
 
-  subject
-}
+    Ok(Parser {
+      lexer,
+      tokens,
+      index: 0,
+      allow_graph,
+    })
+  }
 
-fn parse_predicate(group: &regex::Captures) -> Predicate {
-  let value = group.get(3).unwrap();
+  fn is_at_end(&self) -> bool {
+    self.index >= self.tokens.len()
+  }
 
-  Predicate {
-    term_type: TermType::NamedNode,
-    value: String::from(value.as_str()),
+  fn next(&mut self, expected: &str) -> Result<(usize, Token), ParseError> {
+    match self.tokens.get(self.index) {
+      Some(token) => {
+        self.index += 1;
+        Ok(token.clone())
+      }
+      None => Err(self.error(&format!("expected {}, found end of line", expected))),
+    }
   }
-}
 
-fn parse_object(group: &regex::Captures) -> Object {
-  if let Some(value) = group.get(4) {
-    let object = Object {
-      term_type: TermType::NamedNode,
-      value: String::from(value.as_str()),
-      datatype: None,
-      language: None,
+  fn error(&self, message: &str) -> ParseError {
+    let mut error = self.lexer.error(0, message);
+    error.column = match self.tokens.get(self.index) {
+      Some((column, _)) => *column,
+      None => self.tokens.last().map_or(1, |(column, _)| *column),
     };
-    return object;
-  } else if let Some(value) = group.get(5) {
-    let object = Object {
-      term_type: TermType::BlankNode,
-      value: String::from(value.as_str()),
-      datatype: None,
-      language: None,
-    };
-    return object;
+    error
   }
 
-  let escaped = String::from(group.get(6).unwrap().as_str());
-  let unescaped = unescape_string(&escaped);
+  fn unexpected(&mut self, expected: &str) -> ParseError {
+    self.index -= 1;
+    let error = self.error(&format!("expected {}", expected));
+    self.index += 1;
+    error
+  }
 
-  if let Some(datatype) = group.get(7) {
-    let object = Object {
-      term_type: TermType::Literal,
-      value: unescaped,
-      datatype: Some(String::from(datatype.as_str())),
-      language: None,
+  // subject predicate object graphLabel? '.'
+  fn statement(&mut self) -> Result<Quad, ParseError> {
+    let (subject, predicate, object) = self.triple()?;
+    let expected = if self.allow_graph {
+      "graph name or '.'"
+    } else {
+      "'.'"
     };
-    return object;
-  } else if let Some(language) = group.get(8) {
-    let object = Object {
-      term_type: TermType::Literal,
-      value: unescaped,
-      datatype: Some(String::from(RDF_LANGSTRING)),
-      language: Some(String::from(language.as_str())),
+    let graph = match self.next(expected)? {
+      (_, Token::Dot) => {
+        self.index -= 1;
+        Graph {
+          term_type: TermType::DefaultGraph,
+          value: String::from("@default"),
+        }
+      }
+      (_, Token::Iri(_)) | (_, Token::BlankNode(_)) if !self.allow_graph => {
+        self.index -= 1;
+        return Err(self.error("N-Triples statements cannot have a graph name"));
+      }
+      (_, Token::Iri(value)) => Graph {
+        term_type: TermType::NamedNode,
+        value,
+      },
+      (_, Token::BlankNode(value)) => Graph {
+        term_type: TermType::BlankNode,
+        value,
+      },
+      _ => return Err(self.unexpected(expected)),
     };
-    return object;
+    self.dot()?;
+
+    Ok(Quad {
+      subject,
+      predicate,
+      object,
+      graph,
+    })
   }
 
-  Object {
-    term_type: TermType::Literal,
-    value: unescaped,
-    datatype: Some(String::from(XSD_STRING)),
-    language: None,
+  fn dot(&mut self) -> Result<(), ParseError> {
+    match self.next("'.'")? {
+      (_, Token::Dot) => (),
+      _ => return Err(self.unexpected("'.'")),
+    }
+    if !self.is_at_end() {
+      return Err(self.error("expected end of line"));
+    }
+
+    Ok(())
   }
-}
 
-fn parse_graph(group: &regex::Captures) -> Graph {
-  if let Some(value) = group.get(9) {
-    let graph_name = String::from(value.as_str());
-    let graph = Graph {
-      term_type: TermType::NamedNode,
-      value: graph_name,
+  fn triple(&mut self) -> Result<(Subject, Predicate, Object), ParseError> {
+    let subject = match self.next("subject")? {
+      (_, Token::Iri(value)) => Subject {
+        term_type: TermType::NamedNode,
+        value,
+      },
+      (_, Token::BlankNode(value)) => Subject {
+        term_type: TermType::BlankNode,
+        value,
+      },
+      _ => return Err(self.unexpected("IRI or blank node subject")),
     };
-    return graph;
-  } else if let Some(value) = group.get(10) {
-    let graph_name = String::from(value.as_str());
-    let graph = Graph {
-      term_type: TermType::BlankNode,
-      value: graph_name,
+    let predicate = match self.next("predicate")? {
+      (_, Token::Iri(value)) => Predicate {
+        term_type: TermType::NamedNode,
+        value,
+      },
+      _ => return Err(self.unexpected("IRI predicate")),
     };
-    return graph;
+    let object = self.object()?;
+
+    Ok((subject, predicate, object))
   }
 
-  Graph {
-    term_type: TermType::DefaultGraph,
-    value: String::from("@default"),
+  fn object(&mut self) -> Result<Object, ParseError> {
+    let mut object = Object::new();
+    match self.next("object")? {
+      (_, Token::Iri(value)) => {
+        object.term_type = TermType::NamedNode;
+        object.value = value;
+      }
+      (_, Token::BlankNode(value)) => {
+        object.term_type = TermType::BlankNode;
+        object.value = value;
+      }
+      (_, Token::TripleTermStart) => {
+        let (subject, predicate, triple_object) = self.triple()?;
+        match self.next("')>>'")? {
+          (_, Token::TripleTermEnd) => (),
+          _ => return Err(self.unexpected("')>>'")),
+        }
+        object.term_type = TermType::Triple;
+        object.triple = Some(Box::new(Triple {
+          subject,
+          predicate,
+          object: triple_object,
+        }));
+      }
+      (_, Token::String(value)) => {
+        object.term_type = TermType::Literal;
+        object.value = value;
+        object.datatype = Some(String::from(XSD_STRING));
+        match self.tokens.get(self.index) {
+          Some((_, Token::DatatypeMarker)) => {
+            self.index += 1;
+            match self.next("datatype IRI")? {
+              // the language tag of these literals can only be written with '@'
+              (_, Token::Iri(datatype))
+                if datatype == RDF_LANGSTRING || datatype == RDF_DIRLANGSTRING =>
+              {
+                self.index -= 1;
+                return Err(self.error(&format!("<{}> literal without a language tag", datatype)));
+              }
+              (_, Token::Iri(datatype)) => object.datatype = Some(datatype),
+              _ => return Err(self.unexpected("datatype IRI")),
+            }
+          }
+          Some((_, Token::LangTag(language, direction))) => {
+            object.datatype = Some(String::from(if direction.is_some() {
+              RDF_DIRLANGSTRING
+            } else {
+              RDF_LANGSTRING
+            }));
+            object.language = Some(language.clone());
+            object.direction = direction.clone();
+            self.index += 1;
+          }
+          _ => (),
+        }
+      }
+      _ => return Err(self.unexpected("IRI, blank node, literal or triple term object")),
+    }
+
+    Ok(object)
   }
 }
 
-fn escape_string<'a, S: Into<Cow<'a, str>>>(input: S) -> Cow<'a, str> {
+pub(crate) fn escape_string<'a, S: Into<Cow<'a, str>>>(input: S) -> Cow<'a, str> {
   lazy_static! {
     static ref REGEX: Regex = Regex::new("[\\\\\n\r\"]").unwrap();
   }
@@ -566,29 +861,16 @@ fn escape_string<'a, S: Into<Cow<'a, str>>>(input: S) -> Cow<'a, str> {
   input
 }
 
-fn unescape_string(escaped: &str) -> String {
-  let mut unescaped = escaped.to_string();
-
-  unescaped = unescaped.replace("\\t", "\t");
-  // Must use hex for escape sequence
-  // see: https://github.com/rust-lang/rfcs/issues/751
-  unescaped = unescaped.replace("\\b", "\x08");
-  unescaped = unescaped.replace("\\n", "\n");
-  unescaped = unescaped.replace("\\r", "\r");
-  // Must use hex for escape sequence
-  // see: https://github.com/rust-lang/rfcs/issues/751
-  unescaped = unescaped.replace("\\f", "\x0C");
-  unescaped = unescaped.replace("\\\"", "\"");
-  unescaped = unescaped.replace("\'", "'");
-  unescaped = unescaped.replace("\\\\", "\\");
-
-  unescaped
-}
-
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn unescape_single_quote() {
+    let dataset = parse_nquads("<http://example.com/s> <http://example.com/p> \"it\\'s\" .\n");
+    assert_eq!(dataset.quads[0].object.value, "it's");
+  }
+
   #[test]
   fn subject_equals() {
     let subject_a = Subject {
@@ -648,12 +930,16 @@ mod tests {
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: None,
+      direction: None,
+      triple: None,
     };
     let object_b = Object {
       term_type: TermType::NamedNode,
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: None,
+      direction: None,
+      triple: None,
     };
     assert_eq!(object_a, object_b);
   }
@@ -665,12 +951,16 @@ mod tests {
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: None,
+      direction: None,
+      triple: None,
     };
     let object_b = Object {
       term_type: TermType::NamedNode,
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: Some(String::from("fr")),
+      direction: None,
+      triple: None,
     };
     assert_ne!(object_a, object_b);
   }
@@ -716,6 +1006,8 @@ mod tests {
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: None,
+      direction: None,
+      triple: None,
     };
     let graph = Graph {
       term_type: TermType::NamedNode,
@@ -752,6 +1044,8 @@ mod tests {
       value: String::from("ganesh"),
       datatype: Some(String::from("http://example.com/t2")),
       language: None,
+      direction: None,
+      triple: None,
     };
     let graph_a = Graph {
       term_type: TermType::NamedNode,
@@ -776,4 +1070,125 @@ mod tests {
     };
     assert_ne!(quad_a, quad_b);
   }
+
+  #[test]
+  fn parse_literals() {
+    let dataset = parse_nquads(
+      "<http://example.org/s> <http://example.org/p> \"a\\\"b\\u00E9\\\\\" .\n\
+\n\
+# a comment\n\
+<http://example.org/s> <http://example.org/p> \"chat\"@fr <http://example.org/g> . # trailing\n",
+    );
+    assert_eq!(dataset.quads.len(), 2);
+    assert_eq!(dataset.quads[0].object.value, "a\"b\u{e9}\\");
+    assert_eq!(dataset.quads[0].object.get_datatype().unwrap(), XSD_STRING);
+    assert_eq!(dataset.quads[1].object.get_language().unwrap(), "fr");
+    assert_eq!(dataset.quads[1].graph.term_type, TermType::NamedNode);
+  }
+
+  #[test]
+  fn parse_triple_term() {
+    let nquad = "_:b0 <http://example.org/says> <<( _:b1 <http://example.org/p> \
+<<( <http://example.org/s> <http://example.org/q> \"o\" )>> )>> _:g .\n";
+    let quad = parse_nquad(nquad);
+    assert_eq!(quad.object.term_type, TermType::Triple);
+    let triple = quad.object.triple.as_ref().unwrap();
+    assert_eq!(triple.subject.value, "_:b1");
+    assert_eq!(triple.object.term_type, TermType::Triple);
+    assert_eq!(quad.object.nested_blank_nodes(), vec!["_:b1"]);
+    assert_eq!(serialize_quad(&quad), nquad);
+  }
+
+  #[test]
+  fn parse_errors() {
+    let error = try_parse_nquads(
+      "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n\
+<http://example.org/s> <http://example.org/p> \"o\"",
+    )
+    .unwrap_err();
+    assert_eq!(error.line, 2);
+    assert_eq!(
+      error.to_string(),
+      "expected graph name or '.', found end of line at line 2, column 47"
+    );
+
+    assert!(try_parse_nquad("<s> <http://example.org/p> <http://example.org/o> .").is_err());
+    assert!(try_parse_nquad("\"s\" <http://example.org/p> <http://example.org/o> .").is_err());
+    assert!(
+      try_parse_nquad("_:s <http://example.org/p> <<( _:a <http://example.org/p> ) .").is_err()
+    );
+    assert!(try_parse_nquad("_:s <http://example.org/p> _:o . _:g").is_err());
+  }
+
+  #[test]
+  fn parse_directional_language_tags() {
+    let nquad = "<http://example.org/s> <http://example.org/p> \"abc\"@ar-EG--rtl .\n";
+    let quad = parse_nquad(nquad);
+    assert_eq!(quad.object.get_language().unwrap(), "ar-EG");
+    assert_eq!(quad.object.get_direction().unwrap(), "rtl");
+    assert_eq!(quad.object.get_datatype().unwrap(), RDF_DIRLANGSTRING);
+    assert_eq!(serialize_quad(&quad), nquad);
+
+    assert!(
+      try_parse_nquad("<http://example.org/s> <http://example.org/p> \"abc\"@ar--up .").is_err()
+    );
+    assert!(
+      try_parse_nquad("<http://example.org/s> <http://example.org/p> \"abc\"@ar-- .").is_err()
+    );
+  }
+
+  #[test]
+  fn parse_language_string_datatype_without_language_tag() {
+    let error = try_parse_nquad(
+      "_:s <http://example.org/p> \"x\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#langString> .",
+    )
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "<http://www.w3.org/1999/02/22-rdf-syntax-ns#langString> literal without a language tag \
+at line 1, column 33"
+    );
+    assert!(try_parse_nquad(
+      "_:s <http://example.org/p> \"x\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#dirLangString> ."
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn dataset_set_operations() {
+    let mut dataset = parse_nquads(concat!(
+      "_:a <http://example.org/p> \"x\" .\n",
+      "_:a <http://example.org/p> \"x\"^^<http://www.w3.org/2001/XMLSchema#string> .\n",
+      "_:a <http://example.org/p> \"x\" <http://example.org/g> .\n",
+      "_:a <http://example.org/p> <<( _:b <http://example.org/p> \"x\" )>> .\n",
+      "_:a <http://example.org/p> <<( _:b <http://example.org/p> \"x\" )>> .\n",
+    ));
+    let first = dataset.quads[0].clone();
+    let named = dataset.quads[2].clone();
+    let nested = dataset.quads[3].clone();
+
+    dataset.dedup();
+    assert_eq!(dataset.quads, vec![first.clone(), named.clone(), nested]);
+
+    assert!(dataset.contains(&first));
+    assert!(!dataset.insert(first.clone()));
+    assert_eq!(dataset.quads.len(), 3);
+
+    assert!(dataset.remove(&named));
+    assert!(!dataset.contains(&named));
+    assert!(!dataset.remove(&named));
+    assert!(dataset.insert(named.clone()));
+    assert_eq!(dataset.quads.last(), Some(&named));
+
+    let mut selected = dataset.clone();
+    selected.retain_graphs(std::slice::from_ref(&named.graph));
+    assert_eq!(selected.quads, vec![named.clone()]);
+    selected.move_to_default_graph();
+    assert!(dataset.contains(&selected.quads[0]));
+
+    // removal takes out every copy
+    dataset.quads.push(first.clone());
+    assert!(dataset.remove(&first));
+    assert!(!dataset.contains(&first));
+  }
 }
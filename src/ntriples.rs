@@ -0,0 +1,125 @@
+use crate::nquads::{self, Dataset, ParseError, QuadSerialize, TermType};
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedGraphError {
+  pub graph: String,
+}
+
+impl fmt::Display for NamedGraphError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "N-Triples cannot express quads in the named graph {}",
+      self.graph
+    )
+  }
+}
+
+impl Error for NamedGraphError {}
+
+pub fn parse_ntriples(dataset: &str) -> Dataset {
+  try_parse_ntriples(dataset).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Parses strict N-Triples; unlike `nquads::try_parse_nquads`, a statement
+// with a fourth term is rejected. All quads are in the default graph.
+pub fn try_parse_ntriples(dataset: &str) -> Result<Dataset, ParseError> {
+  nquads::parse_statements(dataset, false)
+}
+
+// Serializes the triple of `quad`, ignoring its graph name.
+pub fn serialize_triple<'a, T>(quad: &'a T) -> String
+where
+  T: QuadSerialize<'a>,
+{
+  let mut ntriple = String::with_capacity(nquads::DEFAULT_NQUAD_CAPACITY);
+
+  // append "subject predicate object .\n"
+  nquads::push_triple(
+    &mut ntriple,
+    quad.get_subject(),
+    quad.get_predicate(),
+    quad.get_object(),
+  );
+  ntriple.push(' ');
+  ntriple.push('.');
+  ntriple.push('\n');
+  ntriple
+}
+
+pub fn serialize_ntriples(dataset: &Dataset) -> Result<String, NamedGraphError> {
+  check_default_graph(dataset)?;
+
+  let mut ntriples = String::with_capacity(dataset.quads.len() * nquads::DEFAULT_NQUAD_CAPACITY);
+  for quad in &dataset.quads {
+    ntriples.push_str(&serialize_triple(quad));
+  }
+
+  Ok(ntriples)
+}
+
+// Returns an error naming the first named graph found in `dataset`, if any.
+pub fn check_default_graph(dataset: &Dataset) -> Result<(), NamedGraphError> {
+  match dataset
+    .quads
+    .iter()
+    .find(|quad| quad.graph.term_type != TermType::DefaultGraph)
+  {
+    Some(quad) => Err(NamedGraphError {
+      graph: if quad.graph.term_type == TermType::NamedNode {
+        format!("<{}>", quad.graph.value)
+      } else {
+        quad.graph.value.clone()
+      },
+    }),
+    None => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_and_serialize() {
+    let ntriples = "<http://example.org/s> <http://example.org/p> \"o\"@en .\n\
+_:b0 <http://example.org/p> <<( _:b1 <http://example.org/q> <http://example.org/o> )>> .\n";
+    let dataset = parse_ntriples(ntriples);
+    assert_eq!(dataset, nquads::parse_nquads(ntriples));
+    assert_eq!(serialize_ntriples(&dataset).unwrap(), ntriples);
+  }
+
+  #[test]
+  fn strict_mode_rejects_graph_names() {
+    let error = try_parse_ntriples(
+      "<http://example.org/s> <http://example.org/p> <http://example.org/o> <http://example.org/g> .",
+    )
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "N-Triples statements cannot have a graph name at line 1, column 70"
+    );
+    assert!(try_parse_ntriples("_:s <http://example.org/p> _:o _:g .").is_err());
+  }
+
+  #[test]
+  fn serialize_rejects_named_graphs() {
+    let dataset = nquads::parse_nquads(
+      "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n\
+<http://example.org/s> <http://example.org/p> <http://example.org/o> _:g .\n",
+    );
+    assert_eq!(
+      serialize_ntriples(&dataset),
+      Err(NamedGraphError {
+        graph: String::from("_:g")
+      })
+    );
+    assert_eq!(
+      serialize_triple(&dataset.quads[1]),
+      "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n"
+    );
+  }
+}
@@ -56,14 +56,12 @@ impl Iterator for Permuter<'_> {
     // get largest mobile element k
     // (mobile: element is greater than the one it is looking at)
     let mut k: Option<PermutationElement> = None;
-    let mut k_is_none = true;
     let mut pos = 0;
     let length = current.len();
     for (i, permutator_element) in current.iter().enumerate() {
       let element = permutator_element.value;
       let left = permutator_element.direction;
-      k_is_none = k.is_none();
-      if (k_is_none || element > k.unwrap().value)
+      if (k.is_none() || element > k.unwrap().value)
         && ((left && i > 0 && element > current[i - 1].value)
           || (!left && i < (length - 1) && element > current[i + 1].value))
       {
@@ -72,19 +70,19 @@ impl Iterator for Permuter<'_> {
       }
     }
 
-    // no more permutations
-    if k_is_none {
-      self.done = true;
-    } else {
-      let k = k.unwrap();
-      // swap k and the element it is looking at
-      let swap = if k.direction { pos - 1 } else { pos + 1 };
-      current.swap(pos, swap);
+    match k {
+      // no more permutations
+      None => self.done = true,
+      Some(k) => {
+        // swap k and the element it is looking at
+        let swap = if k.direction { pos - 1 } else { pos + 1 };
+        current.swap(pos, swap);
 
-      // reverse the direction of all elements larger than k
-      for permutator_element in current.iter_mut() {
-        if permutator_element.value > k.value {
-          permutator_element.direction = !permutator_element.direction;
+        // reverse the direction of all elements larger than k
+        for permutator_element in current.iter_mut() {
+          if permutator_element.value > k.value {
+            permutator_element.direction = !permutator_element.direction;
+          }
         }
       }
     }
@@ -92,3 +90,30 @@ impl Iterator for Permuter<'_> {
     Some(rval)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn permutations(values: &[&str]) -> Vec<Vec<String>> {
+    let mut list = values.to_vec();
+    let mut elements = Permuter::permutation_elements(&mut list);
+    let mut element_refs: PermutationElementRefs = elements.iter_mut().collect();
+    Permuter::new(&mut element_refs).collect()
+  }
+
+  #[test]
+  fn every_permutation_once() {
+    assert_eq!(permutations(&["a"]), vec![vec!["a"]]);
+    assert_eq!(
+      permutations(&["b", "a"]),
+      vec![vec!["a", "b"], vec!["b", "a"]]
+    );
+
+    let mut all = permutations(&["c", "a", "d", "b"]);
+    assert_eq!(all.len(), 24);
+    all.sort();
+    all.dedup();
+    assert_eq!(all.len(), 24);
+  }
+}
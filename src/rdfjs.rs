@@ -0,0 +1,501 @@
+// serde support for the data model, using the JSON shape of the RDF/JS data
+// model (https://rdf.js.org/data-model-spec/). Every term is an object with a
+// `termType` and a `value`:
+//
+//   {"termType": "NamedNode", "value": "http://example.org/s"}
+//   {"termType": "BlankNode", "value": "b0"}
+//   {"termType": "DefaultGraph", "value": ""}
+//   {"termType": "Literal", "value": "chat", "language": "fr",
+//    "datatype": {"termType": "NamedNode",
+//                 "value": "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString"}}
+//
+// Blank node values do not include the `_:` prefix. Literals always carry
+// `language` ("" when there is none) and `datatype`, and the base direction
+// of a directional language-tagged string is written as `"direction": "ltr"`
+// or `"rtl"`. A quad is
+//
+//   {"termType": "Quad", "value": "", "subject": ..., "predicate": ...,
+//    "object": ..., "graph": ...}
+//
+// and a triple term is a quad in the default graph. A dataset is an array of
+// quads. When deserializing, `language` and `direction` may be left out and a
+// missing `datatype` is xsd:string, rdf:langString or rdf:dirLangString as
+// implied by the other fields.
+
+use crate::nquads::{
+  Dataset, Graph, Object, Predicate, Quad, Subject, Term, TermType, Triple, RDF_DIRLANGSTRING,
+  RDF_LANGSTRING, XSD_STRING,
+};
+
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::{Error as _, Serialize, SerializeStruct, Serializer};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TermJson {
+  term_type: String,
+  value: String,
+  #[serde(default)]
+  language: Option<String>,
+  #[serde(default)]
+  datatype: Option<Box<TermJson>>,
+  #[serde(default)]
+  direction: Option<String>,
+  #[serde(default)]
+  subject: Option<Box<TermJson>>,
+  #[serde(default)]
+  predicate: Option<Box<TermJson>>,
+  #[serde(default)]
+  object: Option<Box<TermJson>>,
+  #[serde(default)]
+  graph: Option<Box<TermJson>>,
+}
+
+// Absent fields are left out of human-readable formats such as JSON. Binary
+// formats such as bincode read every field of a struct in order, so there
+// they are written as none.
+impl Serialize for TermJson {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let skip_absent = serializer.is_human_readable();
+    let mut term = serializer.serialize_struct("TermJson", 9)?;
+    term.serialize_field("termType", &self.term_type)?;
+    term.serialize_field("value", &self.value)?;
+    serialize_optional(&mut term, "language", &self.language, skip_absent)?;
+    serialize_optional(&mut term, "datatype", &self.datatype, skip_absent)?;
+    serialize_optional(&mut term, "direction", &self.direction, skip_absent)?;
+    serialize_optional(&mut term, "subject", &self.subject, skip_absent)?;
+    serialize_optional(&mut term, "predicate", &self.predicate, skip_absent)?;
+    serialize_optional(&mut term, "object", &self.object, skip_absent)?;
+    serialize_optional(&mut term, "graph", &self.graph, skip_absent)?;
+    term.end()
+  }
+}
+
+fn serialize_optional<S: SerializeStruct, T: Serialize>(
+  term: &mut S,
+  key: &'static str,
+  value: &Option<T>,
+  skip_absent: bool,
+) -> Result<(), S::Error> {
+  match value {
+    None if skip_absent => term.skip_field(key),
+    _ => term.serialize_field(key, value),
+  }
+}
+
+impl TermJson {
+  fn new(term_type: &str, value: &str) -> TermJson {
+    TermJson {
+      term_type: term_type.to_string(),
+      value: value.to_string(),
+      language: None,
+      datatype: None,
+      direction: None,
+      subject: None,
+      predicate: None,
+      object: None,
+      graph: None,
+    }
+  }
+
+  fn node(term_type: &TermType, value: &str) -> Result<TermJson, String> {
+    match term_type {
+      TermType::NamedNode => Ok(TermJson::new("NamedNode", value)),
+      TermType::BlankNode => Ok(TermJson::new(
+        "BlankNode",
+        value.strip_prefix("_:").unwrap_or(value),
+      )),
+      TermType::DefaultGraph => Ok(TermJson::new("DefaultGraph", "")),
+      _ => Err(format!("a {:?} term cannot be written here", term_type)),
+    }
+  }
+
+  fn quad(
+    subject: &Subject,
+    predicate: &Predicate,
+    object: &Object,
+    graph: TermJson,
+  ) -> Result<TermJson, String> {
+    let mut quad = TermJson::new("Quad", "");
+    quad.subject = Some(Box::new(TermJson::node(
+      &subject.term_type,
+      &subject.value,
+    )?));
+    quad.predicate = Some(Box::new(TermJson::node(
+      &predicate.term_type,
+      &predicate.value,
+    )?));
+    quad.object = Some(Box::new(TermJson::object(object)?));
+    quad.graph = Some(Box::new(graph));
+    Ok(quad)
+  }
+
+  fn object(object: &Object) -> Result<TermJson, String> {
+    match object.term_type {
+      TermType::Literal => {
+        let mut literal = TermJson::new("Literal", &object.value);
+        literal.language = Some(object.language.clone().unwrap_or_default());
+        let datatype = match &object.datatype {
+          Some(datatype) => datatype.as_str(),
+          None => implied_datatype(object.language.is_some(), object.direction.is_some()),
+        };
+        literal.datatype = Some(Box::new(TermJson::new("NamedNode", datatype)));
+        literal.direction = object.direction.clone();
+        Ok(literal)
+      }
+      TermType::Triple => match &object.triple {
+        Some(triple) => TermJson::quad(
+          &triple.subject,
+          &triple.predicate,
+          &triple.object,
+          TermJson::new("DefaultGraph", ""),
+        ),
+        None => Err(String::from("a triple term without a triple")),
+      },
+      _ => TermJson::node(&object.term_type, &object.value),
+    }
+  }
+
+  // Returns the type and value of a node term, accepting only `allowed`
+  // term types.
+  fn into_node(self, allowed: &[&str]) -> Result<(TermType, String), String> {
+    if !allowed.contains(&self.term_type.as_str()) {
+      return Err(format!(
+        "expected a {} term, found {}",
+        allowed.join(" or "),
+        self.term_type
+      ));
+    }
+    match self.term_type.as_str() {
+      "NamedNode" => Ok((TermType::NamedNode, self.value)),
+      "BlankNode" => Ok((TermType::BlankNode, format!("_:{}", self.value))),
+      _ => Ok((TermType::DefaultGraph, String::from("@default"))),
+    }
+  }
+
+  fn into_subject(self) -> Result<Subject, String> {
+    let (term_type, value) = self.into_node(&["NamedNode", "BlankNode"])?;
+    Ok(Subject { term_type, value })
+  }
+
+  fn into_predicate(self) -> Result<Predicate, String> {
+    let (term_type, value) = self.into_node(&["NamedNode"])?;
+    Ok(Predicate { term_type, value })
+  }
+
+  fn into_graph(self) -> Result<Graph, String> {
+    let (term_type, value) = self.into_node(&["NamedNode", "BlankNode", "DefaultGraph"])?;
+    Ok(Graph { term_type, value })
+  }
+
+  fn into_object(self) -> Result<Object, String> {
+    let mut object = Object::new();
+    match self.term_type.as_str() {
+      "Literal" => {
+        object.term_type = TermType::Literal;
+        object.value = self.value;
+        object.language = self.language.filter(|language| !language.is_empty());
+        object.direction = self.direction.filter(|direction| !direction.is_empty());
+        object.datatype = Some(match self.datatype {
+          Some(datatype) => datatype.into_node(&["NamedNode"])?.1,
+          None => {
+            implied_datatype(object.language.is_some(), object.direction.is_some()).to_string()
+          }
+        });
+      }
+      "Quad" => {
+        let (subject, predicate, triple_object, graph) = self.into_parts()?;
+        if graph.term_type != TermType::DefaultGraph {
+          return Err(String::from("a triple term must be in the default graph"));
+        }
+        object.term_type = TermType::Triple;
+        object.triple = Some(Box::new(Triple {
+          subject,
+          predicate,
+          object: triple_object,
+        }));
+      }
+      _ => {
+        let (term_type, value) = self.into_node(&["NamedNode", "BlankNode"])?;
+        object.term_type = term_type;
+        object.value = value;
+      }
+    }
+    Ok(object)
+  }
+
+  fn into_parts(self) -> Result<(Subject, Predicate, Object, Graph), String> {
+    if self.term_type != "Quad" {
+      return Err(format!("expected a Quad, found {}", self.term_type));
+    }
+    let missing = |name: &str| format!("a Quad needs a {}", name);
+    Ok((
+      self
+        .subject
+        .ok_or_else(|| missing("subject"))?
+        .into_subject()?,
+      self
+        .predicate
+        .ok_or_else(|| missing("predicate"))?
+        .into_predicate()?,
+      self
+        .object
+        .ok_or_else(|| missing("object"))?
+        .into_object()?,
+      match self.graph {
+        Some(graph) => graph.into_graph()?,
+        None => Graph {
+          term_type: TermType::DefaultGraph,
+          value: String::from("@default"),
+        },
+      },
+    ))
+  }
+}
+
+fn implied_datatype(language: bool, direction: bool) -> &'static str {
+  match (language, direction) {
+    (_, true) => RDF_DIRLANGSTRING,
+    (true, false) => RDF_LANGSTRING,
+    (false, false) => XSD_STRING,
+  }
+}
+
+fn serialize<S: Serializer>(
+  term: Result<TermJson, String>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  term.map_err(S::Error::custom)?.serialize(serializer)
+}
+
+fn deserialize<'de, D, T, F>(deserializer: D, convert: F) -> Result<T, D::Error>
+where
+  D: Deserializer<'de>,
+  F: FnOnce(TermJson) -> Result<T, String>,
+{
+  convert(TermJson::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+impl Serialize for Subject {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize(TermJson::node(&self.term_type, &self.value), serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Subject {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Subject, D::Error> {
+    deserialize(deserializer, TermJson::into_subject)
+  }
+}
+
+impl Serialize for Predicate {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize(TermJson::node(&self.term_type, &self.value), serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Predicate {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Predicate, D::Error> {
+    deserialize(deserializer, TermJson::into_predicate)
+  }
+}
+
+impl Serialize for Object {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize(TermJson::object(self), serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Object {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Object, D::Error> {
+    deserialize(deserializer, TermJson::into_object)
+  }
+}
+
+impl Serialize for Graph {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize(TermJson::node(&self.term_type, &self.value), serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Graph {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Graph, D::Error> {
+    deserialize(deserializer, TermJson::into_graph)
+  }
+}
+
+impl Serialize for Triple {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let graph = TermJson::new("DefaultGraph", "");
+    serialize(
+      TermJson::quad(&self.subject, &self.predicate, &self.object, graph),
+      serializer,
+    )
+  }
+}
+
+impl<'de> Deserialize<'de> for Triple {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Triple, D::Error> {
+    deserialize(deserializer, |term| match term.into_object()? {
+      Object {
+        triple: Some(triple),
+        ..
+      } => Ok(*triple),
+      _ => Err(String::from("expected a Quad")),
+    })
+  }
+}
+
+impl Serialize for Quad {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let quad = TermJson::node(&self.graph.term_type, &self.graph.value)
+      .and_then(|graph| TermJson::quad(&self.subject, &self.predicate, &self.object, graph));
+    serialize(quad, serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Quad {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Quad, D::Error> {
+    deserialize(deserializer, |term| {
+      let (subject, predicate, object, graph) = term.into_parts()?;
+      Ok(Quad {
+        subject,
+        predicate,
+        object,
+        graph,
+      })
+    })
+  }
+}
+
+impl Serialize for Dataset {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.quads.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Dataset {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Dataset, D::Error> {
+    Ok(Dataset {
+      quads: Vec::deserialize(deserializer)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::nquads::{self, Dataset, Quad};
+  use serde_json::json;
+
+  #[test]
+  fn json_shape() {
+    let dataset = nquads::parse_nquads(concat!(
+      "_:b0 <http://example.org/p> \"chat\"@fr <http://example.org/g> .\n",
+      "<http://example.org/s> <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+    ));
+    assert_eq!(
+      serde_json::to_value(&dataset).unwrap(),
+      json!([
+        {
+          "termType": "Quad",
+          "value": "",
+          "subject": {"termType": "BlankNode", "value": "b0"},
+          "predicate": {"termType": "NamedNode", "value": "http://example.org/p"},
+          "object": {
+            "termType": "Literal",
+            "value": "chat",
+            "language": "fr",
+            "datatype": {
+              "termType": "NamedNode",
+              "value": "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString"
+            }
+          },
+          "graph": {"termType": "NamedNode", "value": "http://example.org/g"}
+        },
+        {
+          "termType": "Quad",
+          "value": "",
+          "subject": {"termType": "NamedNode", "value": "http://example.org/s"},
+          "predicate": {"termType": "NamedNode", "value": "http://example.org/p"},
+          "object": {
+            "termType": "Literal",
+            "value": "1",
+            "language": "",
+            "datatype": {
+              "termType": "NamedNode",
+              "value": "http://www.w3.org/2001/XMLSchema#integer"
+            }
+          },
+          "graph": {"termType": "DefaultGraph", "value": ""}
+        }
+      ])
+    );
+  }
+
+  #[test]
+  fn round_trip() {
+    let dataset = nquads::parse_nquads(concat!(
+      "<http://example.org/s> <http://example.org/p> \"v\" .\n",
+      "<http://example.org/s> <http://example.org/p> \"v\"@ar--rtl _:g .\n",
+      "_:b0 <http://example.org/p> <<( _:b1 <http://example.org/p> \"x\"@en )>> .\n",
+    ));
+    let json = serde_json::to_string(&dataset).unwrap();
+    assert_eq!(serde_json::from_str::<Dataset>(&json).unwrap(), dataset);
+  }
+
+  #[test]
+  fn optional_literal_fields() {
+    let quad: Quad = serde_json::from_value(json!({
+      "termType": "Quad",
+      "value": "",
+      "subject": {"termType": "NamedNode", "value": "http://example.org/s"},
+      "predicate": {"termType": "NamedNode", "value": "http://example.org/p"},
+      "object": {"termType": "Literal", "value": "hi", "language": "en"}
+    }))
+    .unwrap();
+    assert_eq!(
+      quad,
+      nquads::parse_nquad("<http://example.org/s> <http://example.org/p> \"hi\"@en .")
+    );
+
+    let error = serde_json::from_value::<Quad>(json!({
+      "termType": "Quad",
+      "value": "",
+      "subject": {"termType": "Literal", "value": "x"},
+      "predicate": {"termType": "NamedNode", "value": "http://example.org/p"},
+      "object": {"termType": "NamedNode", "value": "http://example.org/o"}
+    }))
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "expected a NamedNode or BlankNode term, found Literal"
+    );
+  }
+
+  #[test]
+  fn predicate_must_be_named_node() {
+    let error = serde_json::from_value::<Quad>(json!({
+      "termType": "Quad",
+      "value": "",
+      "subject": {"termType": "NamedNode", "value": "http://example.org/s"},
+      "predicate": {"termType": "BlankNode", "value": "p"},
+      "object": {"termType": "NamedNode", "value": "http://example.org/o"}
+    }))
+    .unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "expected a NamedNode term, found BlankNode"
+    );
+  }
+
+  #[test]
+  fn bincode_round_trip() {
+    let dataset = nquads::parse_nquads(concat!(
+      "<http://example.org/s> <http://example.org/p> \"v\" .\n",
+      "<http://example.org/s> <http://example.org/p> \"v\"@ar--rtl _:g .\n",
+      "_:b0 <http://example.org/p> <<( _:b1 <http://example.org/p> \"x\"@en )>> .\n",
+    ));
+    let encoded = bincode::serialize(&dataset).unwrap();
+    assert_eq!(bincode::deserialize::<Dataset>(&encoded).unwrap(), dataset);
+  }
+}
@@ -0,0 +1,768 @@
+use crate::iri;
+use crate::lexer::{is_pn_chars, is_pn_chars_u};
+use crate::nquads::{
+  Dataset, Object, ParseError, Predicate, Quad, Subject, RDF_FIRST, RDF_LANGSTRING, RDF_NIL,
+  RDF_REST, RDF_TYPE, XSD_STRING,
+};
+use crate::turtle::{
+  blank_node, default_graph, named_node, object_to_subject, rdf_predicate, subject_to_object,
+  typed_literal,
+};
+
+use roxmltree::{Document, Node, NodeType, ParsingOptions};
+use std::collections::{HashMap, HashSet};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+const RDF_XML_LITERAL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral";
+
+// names in the rdf namespace that are part of the syntax rather than
+// vocabulary, and names that were removed from the language
+const CORE_SYNTAX_TERMS: [&str; 7] = [
+  "RDF",
+  "ID",
+  "about",
+  "parseType",
+  "resource",
+  "nodeID",
+  "datatype",
+];
+const OLD_TERMS: [&str; 3] = ["aboutEach", "aboutEachPrefix", "bagID"];
+
+// unqualified attributes read as if they were in the rdf namespace
+const UNQUALIFIED_RDF_ATTRIBUTES: [&str; 5] = ["ID", "about", "resource", "parseType", "type"];
+
+pub fn parse_rdfxml(input: &str, base: Option<&str>) -> Dataset {
+  try_parse_rdfxml(input, base).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Parses an RDF/XML document into the default graph of a dataset, following
+// the grammar of https://www.w3.org/TR/rdf-syntax-grammar/. Relative IRIs are
+// resolved against `xml:base` or `base`; `rdf:nodeID` labels are scoped to
+// the document and renamed to `_:b0`, `_:b1`, ...
+pub fn try_parse_rdfxml(input: &str, base: Option<&str>) -> Result<Dataset, ParseError> {
+  let options = ParsingOptions {
+    allow_dtd: true,
+    ..ParsingOptions::default()
+  };
+  let document = Document::parse_with_options(input, options).map_err(|e| {
+    let position = e.pos();
+    ParseError {
+      line: position.row as usize,
+      column: position.col as usize,
+      message: e
+        .to_string()
+        .trim_end_matches(&format!(" at {}", position))
+        .to_string(),
+    }
+  })?;
+
+  let mut parser = Parser {
+    document: &document,
+    ids: HashSet::new(),
+    blank_nodes: HashMap::new(),
+    blank_node_counter: 0,
+    quads: Vec::new(),
+  };
+  let scope = Scope {
+    base: base.map(|base| base.to_string()),
+    language: None,
+  };
+  let root = document.root_element();
+  if rdf_name(&parser.element_iri(root)?) == Some("RDF") {
+    let scope = parser.scope(root, &scope)?;
+    for child in parser.element_children(root)? {
+      parser.node_element(child, &scope)?;
+    }
+  } else {
+    parser.node_element(root, &scope)?;
+  }
+
+  Ok(Dataset {
+    quads: parser.quads,
+  })
+}
+
+// The base IRI and language in effect for an element.
+#[derive(Clone)]
+struct Scope {
+  base: Option<String>,
+  language: Option<String>,
+}
+
+// The attributes of a node or property element that are part of the syntax,
+// and the property attributes that abbreviate further triples.
+#[derive(Default)]
+struct Attributes<'a> {
+  id: Option<&'a str>,
+  node_id: Option<&'a str>,
+  about: Option<&'a str>,
+  resource: Option<&'a str>,
+  parse_type: Option<&'a str>,
+  datatype: Option<&'a str>,
+  properties: Vec<(String, &'a str)>,
+}
+
+struct Parser<'a, 'input> {
+  document: &'a Document<'input>,
+  // IRIs already introduced by `rdf:ID`, which must be unique
+  ids: HashSet<String>,
+  blank_nodes: HashMap<String, String>,
+  blank_node_counter: usize,
+  quads: Vec<Quad>,
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+  // https://www.w3.org/TR/rdf-syntax-grammar/#nodeElement
+  fn node_element(&mut self, element: Node, parent: &Scope) -> Result<Subject, ParseError> {
+    let scope = self.scope(element, parent)?;
+    let iri = self.element_iri(element)?;
+    match rdf_name(&iri) {
+      Some(name) if is_syntax_term(name) || name == "li" => {
+        return Err(self.error(
+          element,
+          &format!("rdf:{} cannot be used as a node element", name),
+        ))
+      }
+      _ => (),
+    }
+
+    let attributes = self.attributes(element)?;
+    for (name, present) in [
+      ("resource", attributes.resource.is_some()),
+      ("parseType", attributes.parse_type.is_some()),
+      ("datatype", attributes.datatype.is_some()),
+    ] {
+      if present {
+        return Err(self.error(
+          element,
+          &format!("rdf:{} is not allowed on a node element", name),
+        ));
+      }
+    }
+
+    let subject = match (attributes.id, attributes.node_id, attributes.about) {
+      (Some(id), None, None) => object_to_subject(named_node(self.id(element, &scope, id)?)),
+      (None, Some(node_id), None) => object_to_subject(blank_node(self.node_id(element, node_id)?)),
+      (None, None, Some(about)) => {
+        object_to_subject(named_node(self.resolve(element, &scope, about)?))
+      }
+      (None, None, None) => object_to_subject(blank_node(self.fresh_blank_node())),
+      _ => {
+        return Err(self.error(
+          element,
+          "only one of rdf:ID, rdf:nodeID and rdf:about may be used",
+        ))
+      }
+    };
+
+    if iri != format!("{}Description", RDF) {
+      self.emit(&subject, rdf_predicate(RDF_TYPE), named_node(iri));
+    }
+    self.property_attributes(element, &scope, &subject, &attributes.properties)?;
+
+    let mut li = 1;
+    for child in self.element_children(element)? {
+      self.property_element(child, &subject, &scope, &mut li)?;
+    }
+
+    Ok(subject)
+  }
+
+  // https://www.w3.org/TR/rdf-syntax-grammar/#propertyElt
+  fn property_element(
+    &mut self,
+    element: Node,
+    subject: &Subject,
+    parent: &Scope,
+    li: &mut usize,
+  ) -> Result<(), ParseError> {
+    let scope = self.scope(element, parent)?;
+    let mut iri = self.element_iri(element)?;
+    match rdf_name(&iri) {
+      Some("li") => {
+        iri = format!("{}_{}", RDF, li);
+        *li += 1;
+      }
+      Some(name) if is_syntax_term(name) || name == "Description" => {
+        return Err(self.error(
+          element,
+          &format!("rdf:{} cannot be used as a property element", name),
+        ))
+      }
+      _ => (),
+    }
+    let predicate = rdf_predicate(&iri);
+
+    let attributes = self.attributes(element)?;
+    if attributes.about.is_some() {
+      return Err(self.error(element, "rdf:about is not allowed on a property element"));
+    }
+    let has_object_attributes = attributes.resource.is_some()
+      || attributes.node_id.is_some()
+      || !attributes.properties.is_empty();
+
+    let children: Vec<Node> = element
+      .children()
+      .filter(|child| child.is_element())
+      .collect();
+    let text: String = element
+      .children()
+      .filter(|child| child.is_text())
+      .filter_map(|child| child.text())
+      .collect();
+
+    let object = if let Some(parse_type) = attributes.parse_type {
+      if has_object_attributes || attributes.datatype.is_some() {
+        return Err(self.error(
+          element,
+          "rdf:parseType cannot be combined with rdf:resource, rdf:nodeID, rdf:datatype or property attributes",
+        ));
+      }
+      match parse_type {
+        "Resource" => {
+          let node = blank_node(self.fresh_blank_node());
+          let mut li = 1;
+          for child in self.element_children(element)? {
+            self.property_element(child, &object_to_subject(node.clone()), &scope, &mut li)?;
+          }
+          node
+        }
+        "Collection" => {
+          let mut items = Vec::new();
+          for child in self.element_children(element)? {
+            items.push(self.node_element(child, &scope)?);
+          }
+          self.collection(items)
+        }
+        // "Literal" and any unknown parse type
+        _ => typed_literal(xml_literal(self.document, element), RDF_XML_LITERAL),
+      }
+    } else if !children.is_empty() {
+      if children.len() > 1 {
+        return Err(self.error(
+          children[1],
+          "a property element can only contain one node element",
+        ));
+      }
+      if !text.trim().is_empty() {
+        return Err(self.error(element, "a property element cannot mix text and elements"));
+      }
+      if has_object_attributes || attributes.datatype.is_some() {
+        return Err(self.error(
+          element,
+          "a property element containing a node element only allows rdf:ID",
+        ));
+      }
+      subject_to_object(self.node_element(children[0], &scope)?)
+    } else if element.has_children() || attributes.datatype.is_some() {
+      if has_object_attributes {
+        return Err(self.error(
+          element,
+          "a literal property element only allows rdf:ID and rdf:datatype",
+        ));
+      }
+      match attributes.datatype {
+        Some(datatype) => typed_literal(text, &self.resolve(element, &scope, datatype)?),
+        None => literal(text, &scope),
+      }
+    } else if !has_object_attributes {
+      literal(String::new(), &scope)
+    } else {
+      let node = match (attributes.resource, attributes.node_id) {
+        (Some(resource), None) => named_node(self.resolve(element, &scope, resource)?),
+        (None, Some(node_id)) => blank_node(self.node_id(element, node_id)?),
+        (None, None) => blank_node(self.fresh_blank_node()),
+        _ => {
+          return Err(self.error(
+            element,
+            "only one of rdf:resource and rdf:nodeID may be used",
+          ))
+        }
+      };
+      self.property_attributes(
+        element,
+        &scope,
+        &object_to_subject(node.clone()),
+        &attributes.properties,
+      )?;
+      node
+    };
+
+    // rdf:ID on a property element reifies the triple
+    if let Some(id) = attributes.id {
+      let statement = object_to_subject(named_node(self.id(element, &scope, id)?));
+      let rdf = |name: &str| rdf_predicate(&format!("{}{}", RDF, name));
+      self.emit(
+        &statement,
+        rdf_predicate(RDF_TYPE),
+        named_node(format!("{}Statement", RDF)),
+      );
+      self.emit(
+        &statement,
+        rdf("subject"),
+        subject_to_object(subject.clone()),
+      );
+      self.emit(&statement, rdf("predicate"), named_node(iri));
+      self.emit(&statement, rdf("object"), object.clone());
+    }
+    self.emit(subject, predicate, object);
+
+    Ok(())
+  }
+
+  // Emits the triples abbreviated as property attributes; `rdf:type` takes an
+  // IRI, every other property a literal.
+  fn property_attributes(
+    &mut self,
+    element: Node,
+    scope: &Scope,
+    subject: &Subject,
+    properties: &[(String, &str)],
+  ) -> Result<(), ParseError> {
+    for (iri, value) in properties {
+      let object = if iri == RDF_TYPE {
+        named_node(self.resolve(element, scope, value)?)
+      } else {
+        literal(value.to_string(), scope)
+      };
+      self.emit(subject, rdf_predicate(iri), object);
+    }
+    Ok(())
+  }
+
+  // Builds an rdf:first/rdf:rest list of `items`, returning its head.
+  fn collection(&mut self, items: Vec<Subject>) -> Object {
+    let mut head = named_node(RDF_NIL.to_string());
+    for item in items.into_iter().rev() {
+      let node = object_to_subject(blank_node(self.fresh_blank_node()));
+      self.emit(&node, rdf_predicate(RDF_FIRST), subject_to_object(item));
+      self.emit(&node, rdf_predicate(RDF_REST), head);
+      head = subject_to_object(node);
+    }
+    head
+  }
+
+  fn attributes<'n>(&self, element: Node<'n, 'input>) -> Result<Attributes<'n>, ParseError> {
+    let mut attributes = Attributes::default();
+    for attribute in element.attributes() {
+      let iri = match attribute.namespace() {
+        // xml:lang and xml:base are read by `scope`, other xml attributes
+        // carry no triples
+        Some(XML) => continue,
+        Some(namespace) => format!("{}{}", namespace, attribute.name()),
+        None if attribute.name().to_ascii_lowercase().starts_with("xml") => continue,
+        None if UNQUALIFIED_RDF_ATTRIBUTES.contains(&attribute.name()) => {
+          format!("{}{}", RDF, attribute.name())
+        }
+        None => {
+          return Err(self.error(
+            element,
+            &format!("attribute {} has no namespace", attribute.name()),
+          ))
+        }
+      };
+
+      let value = attribute.value();
+      match rdf_name(&iri) {
+        Some("ID") => attributes.id = Some(value),
+        Some("nodeID") => attributes.node_id = Some(value),
+        Some("about") => attributes.about = Some(value),
+        Some("resource") => attributes.resource = Some(value),
+        Some("parseType") => attributes.parse_type = Some(value),
+        Some("datatype") => attributes.datatype = Some(value),
+        Some(name) if is_syntax_term(name) || name == "li" || name == "Description" => {
+          return Err(self.error(
+            element,
+            &format!("rdf:{} cannot be used as an attribute", name),
+          ))
+        }
+        _ => attributes.properties.push((iri, value)),
+      }
+    }
+    Ok(attributes)
+  }
+
+  // Returns the child elements of `element`, which may otherwise only
+  // contain whitespace, comments and processing instructions.
+  fn element_children<'n>(
+    &self,
+    element: Node<'n, 'input>,
+  ) -> Result<Vec<Node<'n, 'input>>, ParseError> {
+    let mut children = Vec::new();
+    for child in element.children() {
+      match child.node_type() {
+        NodeType::Element => children.push(child),
+        NodeType::Text if !child.text().unwrap_or("").trim().is_empty() => {
+          return Err(self.error(child, "unexpected text content"))
+        }
+        _ => (),
+      }
+    }
+    Ok(children)
+  }
+
+  fn element_iri(&self, element: Node) -> Result<String, ParseError> {
+    let name = element.tag_name();
+    match name.namespace() {
+      Some(namespace) => Ok(format!("{}{}", namespace, name.name())),
+      None => Err(self.error(
+        element,
+        &format!("element {} has no namespace", name.name()),
+      )),
+    }
+  }
+
+  fn scope(&self, element: Node, parent: &Scope) -> Result<Scope, ParseError> {
+    let mut scope = parent.clone();
+    if let Some(base) = element.attribute((XML, "base")) {
+      scope.base = Some(self.resolve(element, parent, base)?);
+    }
+    if let Some(language) = element.attribute((XML, "lang")) {
+      scope.language = match language {
+        "" => None,
+        language => Some(language.to_string()),
+      };
+    }
+    Ok(scope)
+  }
+
+  fn resolve(&self, element: Node, scope: &Scope, value: &str) -> Result<String, ParseError> {
+    if iri::has_scheme(value) {
+      return Ok(value.to_string());
+    }
+    match &scope.base {
+      Some(base) => Ok(iri::resolve(base, value)),
+      None => Err(self.error(
+        element,
+        &format!("relative IRI <{}> without a base IRI", value),
+      )),
+    }
+  }
+
+  // Resolves an `rdf:ID` to `base#id`, which may only be introduced once.
+  fn id(&mut self, element: Node, scope: &Scope, id: &str) -> Result<String, ParseError> {
+    if !is_nc_name(id) {
+      return Err(self.error(element, &format!("rdf:ID \"{}\" is not an XML name", id)));
+    }
+    let iri = self.resolve(element, scope, &format!("#{}", id))?;
+    if !self.ids.insert(iri.clone()) {
+      return Err(self.error(
+        element,
+        &format!("rdf:ID \"{}\" is used more than once", id),
+      ));
+    }
+    Ok(iri)
+  }
+
+  fn node_id(&mut self, element: Node, node_id: &str) -> Result<String, ParseError> {
+    if !is_nc_name(node_id) {
+      return Err(self.error(
+        element,
+        &format!("rdf:nodeID \"{}\" is not an XML name", node_id),
+      ));
+    }
+    if let Some(id) = self.blank_nodes.get(node_id) {
+      return Ok(id.clone());
+    }
+    let id = self.fresh_blank_node();
+    self.blank_nodes.insert(node_id.to_string(), id.clone());
+    Ok(id)
+  }
+
+  fn fresh_blank_node(&mut self) -> String {
+    let id = format!("_:b{}", self.blank_node_counter);
+    self.blank_node_counter += 1;
+    id
+  }
+
+  fn emit(&mut self, subject: &Subject, predicate: Predicate, object: Object) {
+    self.quads.push(Quad {
+      subject: subject.clone(),
+      predicate,
+      object,
+      graph: default_graph(),
+    });
+  }
+
+  fn error(&self, node: Node, message: &str) -> ParseError {
+    let position = self.document.text_pos_at(node.range().start);
+    ParseError {
+      line: position.row as usize,
+      column: position.col as usize,
+      message: message.to_string(),
+    }
+  }
+}
+
+fn rdf_name(iri: &str) -> Option<&str> {
+  iri.strip_prefix(RDF)
+}
+
+fn is_syntax_term(name: &str) -> bool {
+  CORE_SYNTAX_TERMS.contains(&name) || OLD_TERMS.contains(&name)
+}
+
+// https://www.w3.org/TR/xml-names/#NT-NCName
+fn is_nc_name(value: &str) -> bool {
+  let mut chars = value.chars();
+  chars.next().is_some_and(is_pn_chars_u) && chars.all(|c| is_pn_chars(c) || c == '.')
+}
+
+fn literal(value: String, scope: &Scope) -> Object {
+  match &scope.language {
+    Some(language) => {
+      let mut literal = typed_literal(value, RDF_LANGSTRING);
+      literal.language = Some(language.clone());
+      literal
+    }
+    None => typed_literal(value, XSD_STRING),
+  }
+}
+
+// Serializes the content of `element` with exclusive XML canonicalization
+// (https://www.w3.org/TR/xml-exc-c14n/), keeping comments, as the lexical
+// form of an rdf:XMLLiteral.
+fn xml_literal(document: &Document, element: Node) -> String {
+  let mut output = String::new();
+  for child in element.children() {
+    write_canonical(document, child, &HashMap::new(), &mut output);
+  }
+  output
+}
+
+// `rendered` maps the prefixes declared on output ancestors to their
+// namespaces; "" is the default namespace.
+fn write_canonical(
+  document: &Document,
+  node: Node,
+  rendered: &HashMap<String, String>,
+  output: &mut String,
+) {
+  match node.node_type() {
+    NodeType::Text => {
+      for c in node.text().unwrap_or("").chars() {
+        match c {
+          '&' => output.push_str("&amp;"),
+          '<' => output.push_str("&lt;"),
+          '>' => output.push_str("&gt;"),
+          '\r' => output.push_str("&#xD;"),
+          c => output.push(c),
+        }
+      }
+    }
+    NodeType::Comment => {
+      output.push_str("<!--");
+      output.push_str(node.text().unwrap_or(""));
+      output.push_str("-->");
+    }
+    NodeType::PI => {
+      if let Some(pi) = node.pi() {
+        output.push_str("<?");
+        output.push_str(pi.target);
+        if let Some(value) = pi.value {
+          output.push(' ');
+          output.push_str(value);
+        }
+        output.push_str("?>");
+      }
+    }
+    NodeType::Element => {
+      let input = document.input_text();
+      let name: String = input[node.range().start + 1..]
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>')
+        .collect();
+
+      // declare the namespaces visibly used by the element and its
+      // attributes that are not already in scope in the output
+      let mut prefixes = vec![name.split_once(':').map_or("", |(prefix, _)| prefix)];
+      let mut attributes = Vec::new();
+      for attribute in node.attributes() {
+        let qname = &input[attribute.range_qname()];
+        if let Some((prefix, _)) = qname.split_once(':') {
+          prefixes.push(prefix);
+        }
+        attributes.push((
+          attribute.namespace().unwrap_or(""),
+          attribute.name(),
+          qname,
+          attribute.value(),
+        ));
+      }
+      prefixes.sort_unstable();
+      prefixes.dedup();
+
+      let mut rendered = rendered.clone();
+      let mut declarations = String::new();
+      for prefix in prefixes.into_iter().filter(|prefix| *prefix != "xml") {
+        let namespace = node
+          .lookup_namespace_uri(Some(prefix).filter(|prefix| !prefix.is_empty()))
+          .unwrap_or("");
+        if rendered.get(prefix).map_or("", |n| n.as_str()) == namespace {
+          continue;
+        }
+        rendered.insert(prefix.to_string(), namespace.to_string());
+        match prefix {
+          "" => declarations.push_str(" xmlns=\""),
+          prefix => {
+            declarations.push_str(" xmlns:");
+            declarations.push_str(prefix);
+            declarations.push_str("=\"");
+          }
+        }
+        escape_attribute(namespace, &mut declarations);
+        declarations.push('"');
+      }
+
+      output.push('<');
+      output.push_str(&name);
+      output.push_str(&declarations);
+      attributes.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+      for (_, _, qname, value) in attributes {
+        output.push(' ');
+        output.push_str(qname);
+        output.push_str("=\"");
+        escape_attribute(value, output);
+        output.push('"');
+      }
+      output.push('>');
+      for child in node.children() {
+        write_canonical(document, child, &rendered, output);
+      }
+      output.push_str("</");
+      output.push_str(&name);
+      output.push('>');
+    }
+    NodeType::Root => (),
+  }
+}
+
+fn escape_attribute(value: &str, output: &mut String) {
+  for c in value.chars() {
+    match c {
+      '&' => output.push_str("&amp;"),
+      '<' => output.push_str("&lt;"),
+      '"' => output.push_str("&quot;"),
+      '\t' => output.push_str("&#x9;"),
+      '\n' => output.push_str("&#xA;"),
+      '\r' => output.push_str("&#xD;"),
+      c => output.push(c),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads;
+
+  fn assert_isomorphic(rdfxml: &str, expected: &str) {
+    let dataset = parse_rdfxml(rdfxml, Some("http://example.org/base/doc.rdf"));
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      crate::canonize(&nquads::parse_nquads(expected), "URDNA2015").unwrap()
+    );
+  }
+
+  #[test]
+  fn node_and_property_elements() {
+    assert_isomorphic(
+      concat!(
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"\n",
+        "         xmlns:ex=\"http://example.org/\" xml:lang=\"en\">\n",
+        "  <ex:Book rdf:about=\"book\" ex:title=\"Title\">\n",
+        "    <ex:author rdf:nodeID=\"a\" ex:name=\"Ann\"/>\n",
+        "    <ex:pages rdf:datatype=\"http://www.w3.org/2001/XMLSchema#integer\">12</ex:pages>\n",
+        "    <ex:note xml:lang=\"\">plain</ex:note>\n",
+        "  </ex:Book>\n",
+        "  <rdf:Description rdf:nodeID=\"a\">\n",
+        "    <ex:knows><ex:Person/></ex:knows>\n",
+        "  </rdf:Description>\n",
+        "</rdf:RDF>\n"
+      ),
+      concat!(
+        "<http://example.org/base/book> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Book> .\n",
+        "<http://example.org/base/book> <http://example.org/title> \"Title\"@en .\n",
+        "<http://example.org/base/book> <http://example.org/author> _:a .\n",
+        "_:a <http://example.org/name> \"Ann\"@en .\n",
+        "<http://example.org/base/book> <http://example.org/pages> \"12\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+        "<http://example.org/base/book> <http://example.org/note> \"plain\" .\n",
+        "_:a <http://example.org/knows> _:p .\n",
+        "_:p <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Person> .\n"
+      ),
+    );
+  }
+
+  #[test]
+  fn parse_types() {
+    assert_isomorphic(
+      concat!(
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"\n",
+        "         xmlns:ex=\"http://example.org/\">\n",
+        "  <rdf:Description rdf:about=\"http://example.org/s\">\n",
+        "    <ex:r rdf:parseType=\"Resource\"><ex:p>v</ex:p></ex:r>\n",
+        "    <ex:c rdf:parseType=\"Collection\">\n",
+        "      <rdf:Description rdf:about=\"http://example.org/a\"/>\n",
+        "      <rdf:Description rdf:about=\"http://example.org/b\"/>\n",
+        "    </ex:c>\n",
+        "    <ex:x rdf:parseType=\"Literal\"><ex:b a=\"1\">bold &amp; <i xmlns=\"http://www.w3.org/1999/xhtml\">it</i></ex:b></ex:x>\n",
+        "  </rdf:Description>\n",
+        "</rdf:RDF>\n"
+      ),
+      concat!(
+        "<http://example.org/s> <http://example.org/r> _:r .\n",
+        "_:r <http://example.org/p> \"v\" .\n",
+        "<http://example.org/s> <http://example.org/c> _:l0 .\n",
+        "_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://example.org/a> .\n",
+        "_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:l1 .\n",
+        "_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://example.org/b> .\n",
+        "_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n",
+        "<http://example.org/s> <http://example.org/x> \"<ex:b xmlns:ex=\\\"http://example.org/\\\" a=\\\"1\\\">bold &amp; <i xmlns=\\\"http://www.w3.org/1999/xhtml\\\">it</i></ex:b>\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral> .\n"
+      ),
+    );
+  }
+
+  #[test]
+  fn errors() {
+    let error = |input: &str| try_parse_rdfxml(input, Some("http://example.org/")).unwrap_err();
+    let rdf = |body: &str| {
+      format!(
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:ex=\"http://example.org/\">\n{}\n</rdf:RDF>",
+        body
+      )
+    };
+
+    assert_eq!(
+      error(&rdf("<rdf:Description rdf:ID=\"1a\"/>")).to_string(),
+      "rdf:ID \"1a\" is not an XML name at line 2, column 1"
+    );
+    assert_eq!(
+      error(&rdf(
+        "<rdf:Description rdf:ID=\"a\"/><rdf:Description rdf:ID=\"a\"/>"
+      ))
+      .message,
+      "rdf:ID \"a\" is used more than once"
+    );
+    assert_eq!(
+      error(&rdf("<rdf:Description rdf:about=\"a\" rdf:nodeID=\"a\"/>")).message,
+      "only one of rdf:ID, rdf:nodeID and rdf:about may be used"
+    );
+    assert_eq!(
+      error(&rdf("<rdf:Description rdf:aboutEach=\"a\"/>")).message,
+      "rdf:aboutEach cannot be used as an attribute"
+    );
+    assert_eq!(
+      error(&rdf(
+        "<rdf:Description><ex:p><ex:A/><ex:B/></ex:p></rdf:Description>"
+      ))
+      .message,
+      "a property element can only contain one node element"
+    );
+    assert_eq!(
+      error(&rdf("<rdf:Description>text</rdf:Description>")).message,
+      "unexpected text content"
+    );
+    assert_eq!(error("<rdf:RDF").line, 1);
+  }
+}
@@ -0,0 +1,141 @@
+// Random blank node relabeling, for testing that code working with datasets
+// does not depend on the blank node labels or quad order of its input.
+//
+// The canonical form of a dataset must not change when its blank nodes are
+// renamed or its quads are reordered. `assert_canonize_invariant` checks this
+// for one random relabeling and reordering; with a seeded generator (e.g. the
+// `TestRng` of a proptest case) a failure is reproducible.
+
+use crate::canonize;
+use crate::nquads::{Dataset, TermType};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngExt};
+use std::collections::HashMap;
+
+// Label prefixes to choose from, including the one of canonical labels.
+const PREFIXES: [&str; 5] = ["b", "n", "x", "genid-", "c14n"];
+
+// Renames every blank node, including those nested inside triple terms, with
+// a random bijection to fresh labels.
+pub fn relabel_blank_nodes<R>(dataset: &mut Dataset, rng: &mut R)
+where
+  R: Rng + ?Sized,
+{
+  let mut labels: HashMap<String, usize> = HashMap::new();
+  for quad in &dataset.quads {
+    let mut ids = quad.object.nested_blank_nodes();
+    for (term_type, value) in &[
+      (quad.subject.term_type, &quad.subject.value),
+      (quad.object.term_type, &quad.object.value),
+      (quad.graph.term_type, &quad.graph.value),
+    ] {
+      if *term_type == TermType::BlankNode {
+        ids.push(value);
+      }
+    }
+    for id in ids {
+      let next = labels.len();
+      labels.entry(id.to_string()).or_insert(next);
+    }
+  }
+
+  let mut numbers: Vec<usize> = (0..labels.len()).collect();
+  numbers.shuffle(rng);
+  let prefix = PREFIXES[rng.random_range(0..PREFIXES.len())];
+  let mut relabel = |value: &str| format!("_:{}{}", prefix, numbers[labels[value]]);
+
+  for quad in &mut dataset.quads {
+    if quad.subject.term_type == TermType::BlankNode {
+      quad.subject.value = relabel(&quad.subject.value);
+    }
+    if quad.object.term_type == TermType::BlankNode || quad.object.triple.is_some() {
+      quad.object = quad.object.map_blank_nodes(&mut relabel);
+    }
+    if quad.graph.term_type == TermType::BlankNode {
+      quad.graph.value = relabel(&quad.graph.value);
+    }
+  }
+}
+
+pub fn shuffle_quads<R>(dataset: &mut Dataset, rng: &mut R)
+where
+  R: Rng + ?Sized,
+{
+  dataset.quads.shuffle(rng);
+}
+
+// Panics unless `dataset` canonicalizes to the same N-Quads after relabeling
+// its blank nodes and shuffling its quads.
+pub fn assert_canonize_invariant<R>(dataset: &Dataset, rng: &mut R)
+where
+  R: Rng + ?Sized,
+{
+  let mut relabeled = dataset.clone();
+  relabel_blank_nodes(&mut relabeled, rng);
+  shuffle_quads(&mut relabeled, rng);
+
+  let expected = canonize(dataset, "URDNA2015").unwrap();
+  let actual = canonize(&relabeled, "URDNA2015").unwrap();
+  assert!(
+    expected == actual,
+    "canonical form changed under relabeling\ninput:\n{}relabeled:\n{}expected:\n{}actual:\n{}",
+    serialize_in_order(dataset),
+    serialize_in_order(&relabeled),
+    expected,
+    actual
+  );
+}
+
+// The input quads as N-Quads, in their original order.
+fn serialize_in_order(dataset: &Dataset) -> String {
+  dataset
+    .quads
+    .iter()
+    .map(crate::nquads::serialize_quad)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads::parse_nquads;
+  use proptest::test_runner::{RngAlgorithm, TestRng};
+
+  const DATASET: &str = concat!(
+    "_:a <http://example.org/p> _:b _:g .\n",
+    "_:b <http://example.org/p> <<( _:a <http://example.org/q> _:c )>> .\n",
+    "_:c <http://example.org/p> \"c\" _:g .\n",
+    "<http://example.org/s> <http://example.org/p> _:a .\n",
+  );
+
+  #[test]
+  fn relabel_is_a_bijection() {
+    let dataset = parse_nquads(DATASET);
+    let mut rng = TestRng::deterministic_rng(RngAlgorithm::ChaCha);
+    let mut relabeled = dataset.clone();
+    relabel_blank_nodes(&mut relabeled, &mut rng);
+
+    let serialized = serialize_in_order(&relabeled);
+    for label in &["_:a ", "_:b ", "_:c ", "_:g "] {
+      assert!(!serialized.contains(label));
+    }
+    let mut labels: Vec<&str> = serialized
+      .split_whitespace()
+      .filter(|term| term.starts_with("_:"))
+      .collect();
+    assert_eq!(labels.len(), 9);
+    labels.sort_unstable();
+    labels.dedup();
+    assert_eq!(labels.len(), 4);
+    // the same seed gives the same labels
+    let mut again = dataset.clone();
+    relabel_blank_nodes(
+      &mut again,
+      &mut TestRng::deterministic_rng(RngAlgorithm::ChaCha),
+    );
+    assert_eq!(again, relabeled);
+
+    assert_canonize_invariant(&dataset, &mut rng);
+  }
+}
@@ -0,0 +1,149 @@
+// Destinations for canonical N-Quads.
+//
+// The canonicalizer hands every canonical quad to a `QuadSink` as one N-Quads
+// line (terminated by "\n"), in canonical order. Concatenating the lines gives
+// the canonical N-Quads document, but a sink is free to stream them to a file
+// or a hasher instead of building that document in memory.
+
+use crate::message_digest::MessageDigest;
+
+use sha2::Sha256;
+use std::io;
+
+pub trait QuadSink {
+  fn write_quad(&mut self, nquad: &str) -> io::Result<()>;
+}
+
+// Appends the lines, building the canonical N-Quads document.
+impl QuadSink for String {
+  fn write_quad(&mut self, nquad: &str) -> io::Result<()> {
+    self.push_str(nquad);
+    Ok(())
+  }
+}
+
+// Calls the closure with each line.
+impl<F> QuadSink for F
+where
+  F: FnMut(&str),
+{
+  fn write_quad(&mut self, nquad: &str) -> io::Result<()> {
+    self(nquad);
+    Ok(())
+  }
+}
+
+// Writes the lines to an `io::Write`. Wrap the writer in a `BufWriter` when
+// every write is a system call.
+#[derive(Debug)]
+pub struct WriteSink<W: io::Write> {
+  writer: W,
+}
+
+impl<W: io::Write> WriteSink<W> {
+  pub fn new(writer: W) -> WriteSink<W> {
+    WriteSink { writer }
+  }
+
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+}
+
+impl<W: io::Write> QuadSink for WriteSink<W> {
+  fn write_quad(&mut self, nquad: &str) -> io::Result<()> {
+    self.writer.write_all(nquad.as_bytes())
+  }
+}
+
+// Hashes the lines. `finish` returns the hex-encoded SHA-256 of the canonical
+// N-Quads document.
+#[derive(Clone, Debug, Default)]
+pub struct HashSink {
+  md: MessageDigest<Sha256>,
+}
+
+impl HashSink {
+  pub fn new() -> HashSink {
+    HashSink {
+      md: MessageDigest::new(),
+    }
+  }
+
+  pub fn finish(self) -> String {
+    MessageDigest::digest(self.md)
+  }
+}
+
+impl QuadSink for HashSink {
+  fn write_quad(&mut self, nquad: &str) -> io::Result<()> {
+    self.md.update(nquad);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads::parse_nquads;
+  use crate::{canonize, canonize_into};
+
+  const INPUT: &str = concat!(
+    "_:x <http://example.org/p> _:y .\n",
+    "_:y <http://example.org/p> \"y\" <http://example.org/g> .\n",
+    "<http://example.org/s> <http://example.org/p> _:x .\n",
+  );
+
+  #[test]
+  fn sinks_receive_canonical_order() {
+    let dataset = parse_nquads(INPUT);
+    let expected = canonize(&dataset, "URDNA2015").unwrap();
+
+    let mut lines = Vec::new();
+    let mut callback = |nquad: &str| lines.push(nquad.to_string());
+    canonize_into(&dataset, "URDNA2015", &mut callback)
+      .unwrap()
+      .unwrap();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(lines.concat(), expected);
+
+    let mut sink = WriteSink::new(Vec::new());
+    canonize_into(&dataset, "URDNA2015", &mut sink)
+      .unwrap()
+      .unwrap();
+    assert_eq!(sink.into_inner(), expected.as_bytes());
+
+    let mut sink = HashSink::new();
+    canonize_into(&dataset, "URDNA2015", &mut sink)
+      .unwrap()
+      .unwrap();
+    let mut md: MessageDigest<Sha256> = MessageDigest::new();
+    md.update(&expected);
+    assert_eq!(sink.finish(), MessageDigest::digest(md));
+
+    assert!(canonize_into(&dataset, "URGNA2012", &mut String::new()).is_none());
+  }
+
+  struct FullWriter;
+
+  impl io::Write for FullWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+      Err(io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn write_errors_are_returned() {
+    let dataset = parse_nquads(INPUT);
+    let mut sink = WriteSink::new(FullWriter);
+    let error = canonize_into(&dataset, "URDNA2015", &mut sink)
+      .unwrap()
+      .unwrap_err();
+    assert_eq!(error.to_string(), "disk full");
+  }
+}
@@ -0,0 +1,190 @@
+// Skolemization replaces the blank nodes of a dataset with IRIs minted for
+// them, so it can be stored where blank node labels are not kept stable.
+// De-skolemization turns those IRIs back into blank nodes.
+// https://www.w3.org/TR/rdf11-concepts/#section-skolemization
+//
+// The blank node `_:label` becomes `<scheme://authority/.well-known/genid/label>`
+// for the authority of the configured base. Skolemizing the output of
+// `canonize` keeps the canonical labels in the IRIs, so the de-skolemized
+// dataset canonicalizes to the same N-Quads and hash.
+
+use crate::iri;
+use crate::lexer::is_blank_node_label;
+use crate::nquads::{Dataset, Object, TermType};
+
+use std::error::Error;
+use std::fmt;
+
+pub const GENID_PATH: &str = "/.well-known/genid/";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkolemError {
+  pub base: String,
+}
+
+impl fmt::Display for SkolemError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "\"{}\" is not an absolute IRI", self.base)
+  }
+}
+
+impl Error for SkolemError {}
+
+// Returns the IRI that Skolem IRIs minted under `base` start with.
+pub fn genid_prefix(base: &str) -> Result<String, SkolemError> {
+  if !iri::has_scheme(base) {
+    return Err(SkolemError {
+      base: base.to_string(),
+    });
+  }
+  Ok(iri::resolve(base, GENID_PATH))
+}
+
+// Replaces every blank node, including those nested inside triple terms, with
+// its Skolem IRI under `base`.
+pub fn skolemize(dataset: &mut Dataset, base: &str) -> Result<(), SkolemError> {
+  let prefix = genid_prefix(base)?;
+  rewrite_dataset(dataset, &mut |term_type, value| {
+    if *term_type == TermType::BlankNode {
+      *term_type = TermType::NamedNode;
+      *value = format!("{}{}", prefix, value.trim_start_matches("_:"));
+    }
+  });
+  Ok(())
+}
+
+// Replaces every Skolem IRI minted under `base` with the blank node it was
+// minted for. Other IRIs are kept, including Skolem IRIs of other authorities
+// and those whose suffix is not a valid blank node label, e.g. `genid/a/b`.
+pub fn deskolemize(dataset: &mut Dataset, base: &str) -> Result<(), SkolemError> {
+  let prefix = genid_prefix(base)?;
+  rewrite_dataset(dataset, &mut |term_type, value| {
+    if *term_type != TermType::NamedNode {
+      return;
+    }
+    if let Some(label) = value
+      .strip_prefix(&prefix)
+      .filter(|label| is_blank_node_label(label))
+    {
+      *term_type = TermType::BlankNode;
+      *value = format!("_:{}", label);
+    }
+  });
+  Ok(())
+}
+
+// Calls `f` with the type and value of every subject, object and graph name,
+// including the terms nested inside triple terms. Predicates are always IRIs
+// and are not visited.
+fn rewrite_dataset<F>(dataset: &mut Dataset, f: &mut F)
+where
+  F: FnMut(&mut TermType, &mut String),
+{
+  for quad in &mut dataset.quads {
+    f(&mut quad.subject.term_type, &mut quad.subject.value);
+    rewrite_object(&mut quad.object, f);
+    f(&mut quad.graph.term_type, &mut quad.graph.value);
+  }
+}
+
+fn rewrite_object<F>(object: &mut Object, f: &mut F)
+where
+  F: FnMut(&mut TermType, &mut String),
+{
+  f(&mut object.term_type, &mut object.value);
+  if let Some(triple) = &mut object.triple {
+    f(&mut triple.subject.term_type, &mut triple.subject.value);
+    rewrite_object(&mut triple.object, f);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::canonize;
+  use crate::nquads::{parse_nquads, try_parse_nquads};
+
+  #[test]
+  fn skolemize_canonical_blank_nodes() {
+    let dataset = parse_nquads(concat!(
+      "_:x <http://example.org/says> <<( _:y <http://example.org/p> \"o\" )>> _:g .\n",
+      "_:y <http://example.org/p> <http://example.org/o> .\n",
+    ));
+    let canonical = parse_nquads(&canonize(&dataset, "URDNA2015").unwrap());
+
+    let mut skolemized = canonical.clone();
+    skolemize(&mut skolemized, "https://example.com/some/path").unwrap();
+    let serialized = canonize(&skolemized, "URDNA2015").unwrap();
+    assert!(!serialized.contains("_:"));
+    assert_eq!(
+      serialized,
+      concat!(
+        "<https://example.com/.well-known/genid/c14n0> <http://example.org/p> <http://example.org/o> .\n",
+        "<https://example.com/.well-known/genid/c14n2> <http://example.org/says> ",
+        "<<( <https://example.com/.well-known/genid/c14n0> <http://example.org/p> \"o\" )>> ",
+        "<https://example.com/.well-known/genid/c14n1> .\n",
+      )
+    );
+
+    // the stored dataset round-trips to the same canonical form
+    let mut stored = parse_nquads(&serialized);
+    deskolemize(&mut stored, "https://example.com/").unwrap();
+    assert_eq!(
+      stored,
+      parse_nquads(&canonize(&canonical, "URDNA2015").unwrap())
+    );
+    assert_eq!(
+      canonize(&stored, "URDNA2015"),
+      canonize(&dataset, "URDNA2015")
+    );
+  }
+
+  #[test]
+  fn deskolemize_only_matching_iris() {
+    let mut dataset = parse_nquads(concat!(
+      "<https://example.com/.well-known/genid/a> <http://example.org/p> <https://example.org/.well-known/genid/b> .\n",
+      "<https://example.com/.well-known/genid/> <http://example.org/p> \"https://example.com/.well-known/genid/c\" .\n",
+    ));
+    deskolemize(&mut dataset, "https://example.com").unwrap();
+    assert_eq!(
+      canonize(&dataset, "URDNA2015").unwrap(),
+      concat!(
+        "<https://example.com/.well-known/genid/> <http://example.org/p> \"https://example.com/.well-known/genid/c\" .\n",
+        "_:c14n0 <http://example.org/p> <https://example.org/.well-known/genid/b> .\n",
+      )
+    );
+  }
+
+  #[test]
+  fn deskolemize_only_valid_labels() {
+    let mut dataset = parse_nquads(concat!(
+      "<https://example.com/.well-known/genid/a.b> <http://example.org/p> <https://example.com/.well-known/genid/a/b%20c> .\n",
+      "<https://example.com/.well-known/genid/a.> <http://example.org/p> <https://example.com/.well-known/genid/-a> .\n",
+    ));
+    deskolemize(&mut dataset, "https://example.com").unwrap();
+    let serialized = canonize(&dataset, "URDNA2015").unwrap();
+    assert_eq!(
+      serialized,
+      concat!(
+        "<https://example.com/.well-known/genid/a.> <http://example.org/p> <https://example.com/.well-known/genid/-a> .\n",
+        "_:c14n0 <http://example.org/p> <https://example.com/.well-known/genid/a/b%20c> .\n",
+      )
+    );
+    assert!(try_parse_nquads(&serialized).is_ok());
+  }
+
+  #[test]
+  fn base_must_be_absolute() {
+    let mut dataset = parse_nquads("_:x <http://example.org/p> \"o\" .");
+    assert_eq!(
+      skolemize(&mut dataset, "example.com")
+        .unwrap_err()
+        .to_string(),
+      "\"example.com\" is not an absolute IRI"
+    );
+    assert_eq!(
+      genid_prefix("https://example.com/a/b?q#f"),
+      Ok(String::from("https://example.com/.well-known/genid/"))
+    );
+  }
+}
@@ -0,0 +1,460 @@
+use crate::lexer::{is_pn_chars, is_pn_chars_base, is_pn_chars_u};
+use crate::literal::{XSD_BOOLEAN, XSD_DECIMAL, XSD_DOUBLE, XSD_INTEGER};
+use crate::nquads::{
+  escape_string, Dataset, Graph, Object, ParseError, Subject, TermType, RDF_DIRLANGSTRING,
+  RDF_LANGSTRING, RDF_TYPE, XSD_STRING,
+};
+use crate::turtle::{default_graph, Parser};
+
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+pub fn parse_trig(input: &str, base: Option<&str>) -> Dataset {
+  try_parse_trig(input, base).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Parses a TriG document into a dataset. Triples inside `{ }` blocks are
+// placed in the named graph labelling the block; a graph may be named by an
+// IRI, a blank node label or `[]`. Blank node labels are scoped to the whole
+// document, so the same label in two graphs is the same node.
+pub fn try_parse_trig(input: &str, base: Option<&str>) -> Result<Dataset, ParseError> {
+  let mut parser = Parser::new(input, base);
+  parser.skip_whitespace();
+  while !parser.is_at_end() {
+    if !parser.directive()? {
+      block(&mut parser)?;
+    }
+    parser.skip_whitespace();
+  }
+
+  Ok(parser.into_dataset())
+}
+
+// block ::= triplesOrGraph | wrappedGraph | triples2 |
+//           "GRAPH" labelOrSubject wrappedGraph
+fn block(parser: &mut Parser) -> Result<(), ParseError> {
+  if parser.eat_keyword("GRAPH", true) {
+    let label = parser.label_or_subject()?;
+    return wrapped_graph(parser, Some(label));
+  }
+
+  if parser.peek() == Some('{') {
+    return wrapped_graph(parser, None);
+  }
+
+  // triplesOrGraph ::= labelOrSubject (wrappedGraph | predicateObjectList '.')
+  if parser.at_label_or_subject() {
+    let subject = parser.label_or_subject()?;
+    parser.skip_whitespace();
+    if parser.peek() == Some('{') {
+      return wrapped_graph(parser, Some(subject));
+    }
+    parser.predicate_object_list(&subject)?;
+    return parser.expect('.');
+  }
+
+  parser.triples()?;
+  parser.expect('.')
+}
+
+// wrappedGraph ::= '{' triplesBlock? '}'
+fn wrapped_graph(parser: &mut Parser, label: Option<Subject>) -> Result<(), ParseError> {
+  parser.expect('{')?;
+  parser.graph = match label {
+    Some(label) => Graph {
+      term_type: label.term_type,
+      value: label.value,
+    },
+    None => default_graph(),
+  };
+
+  // triplesBlock ::= triples ('.' triplesBlock?)?
+  loop {
+    parser.skip_whitespace();
+    if parser.peek() == Some('}') {
+      break;
+    }
+    parser.triples()?;
+    parser.skip_whitespace();
+    if parser.peek() != Some('.') {
+      break;
+    }
+    parser.expect('.')?;
+  }
+  parser.expect('}')?;
+
+  parser.graph = default_graph();
+  Ok(())
+}
+
+// Serializes `dataset` as TriG for human review. IRIs in one of the given
+// `(prefix, namespace)` pairs are written as prefixed names and only the
+// prefixes that are used are declared; pairs whose prefix is not a valid
+// PN_PREFIX are ignored. Quads are grouped by graph and subject, and every
+// level is sorted by code point, so the same dataset always produces the same
+// text.
+// Blank node labels are kept, so the output can be read next to the canonical
+// N-Quads it was produced from.
+pub fn serialize_trig(dataset: &Dataset, prefixes: &[(&str, &str)]) -> String {
+  let mut writer = Writer {
+    prefixes,
+    used: BTreeSet::new(),
+  };
+
+  // render every quad first, so the prefixes in use are known
+  let mut statements: Vec<[String; 4]> = dataset
+    .quads
+    .iter()
+    .map(|quad| {
+      let graph = match quad.graph.term_type {
+        TermType::DefaultGraph => String::new(),
+        term_type => writer.node(term_type, &quad.graph.value),
+      };
+      [
+        graph,
+        writer.node(quad.subject.term_type, &quad.subject.value),
+        writer.predicate(&quad.predicate.value),
+        writer.object(&quad.object),
+      ]
+    })
+    .collect();
+  statements.sort_by(|a, b| {
+    a.iter()
+      .zip(b.iter())
+      .enumerate()
+      .map(|(i, (a, b))| compare_terms(a, b, i == 2))
+      .find(|ordering| *ordering != Ordering::Equal)
+      .unwrap_or(Ordering::Equal)
+  });
+  statements.dedup();
+
+  let mut output = String::new();
+  let mut declared: Vec<&(&str, &str)> = writer.used.iter().map(|i| &prefixes[*i]).collect();
+  declared.sort_by(|a, b| a.0.cmp(b.0));
+  for (prefix, namespace) in &declared {
+    output.push_str(&format!("@prefix {}: <{}> .\n", prefix, namespace));
+  }
+
+  let mut previous: Option<&[String; 4]> = None;
+  for statement in &statements {
+    let [graph, subject, predicate, object] = statement;
+    let indent = if graph.is_empty() { "" } else { "  " };
+    match previous {
+      Some([g, s, p, _]) if g == graph && s == subject && p == predicate => {
+        output.push_str(", ");
+      }
+      Some([g, s, _, _]) if g == graph && s == subject => {
+        output.push_str(&format!(" ;\n{}  {} ", indent, predicate));
+      }
+      _ => {
+        let new_graph = previous.is_none_or(|[g, _, _, _]| g != graph);
+        if let Some([g, _, _, _]) = previous {
+          output.push_str(" .\n");
+          if new_graph && !g.is_empty() {
+            output.push_str("}\n");
+          }
+        }
+        if new_graph {
+          if !output.is_empty() {
+            output.push('\n');
+          }
+          if !graph.is_empty() {
+            output.push_str(&format!("{} {{\n", graph));
+          }
+        }
+        output.push_str(&format!("{}{} {} ", indent, subject, predicate));
+      }
+    }
+    output.push_str(object);
+    previous = Some(statement);
+  }
+  match previous {
+    Some([g, _, _, _]) if !g.is_empty() => output.push_str(" .\n}\n"),
+    Some(_) => output.push_str(" .\n"),
+    None => {}
+  }
+
+  output
+}
+
+// Orders rendered terms, putting the default graph (rendered as "") first and,
+// for predicates, `a` before any other.
+fn compare_terms(a: &str, b: &str, predicate: bool) -> Ordering {
+  if predicate && (a == "a" || b == "a") {
+    return (b == "a").cmp(&(a == "a"));
+  }
+  a.cmp(b)
+}
+
+struct Writer<'a> {
+  prefixes: &'a [(&'a str, &'a str)],
+  // indexes into `prefixes` of the namespaces that have been used
+  used: BTreeSet<usize>,
+}
+
+impl<'a> Writer<'a> {
+  fn iri(&mut self, iri: &str) -> String {
+    // prefer the longest namespace that leaves a valid local name
+    let compacted = self
+      .prefixes
+      .iter()
+      .enumerate()
+      .filter(|(_, (prefix, namespace))| {
+        is_pn_prefix(prefix) && iri.starts_with(namespace) && is_pn_local(&iri[namespace.len()..])
+      })
+      .max_by_key(|(_, (_, namespace))| namespace.len());
+
+    match compacted {
+      Some((i, (prefix, namespace))) => {
+        self.used.insert(i);
+        format!("{}:{}", prefix, &iri[namespace.len()..])
+      }
+      None => format!("<{}>", iri),
+    }
+  }
+
+  fn node(&mut self, term_type: TermType, value: &str) -> String {
+    match term_type {
+      TermType::NamedNode => self.iri(value),
+      _ => value.to_string(),
+    }
+  }
+
+  fn predicate(&mut self, value: &str) -> String {
+    if value == RDF_TYPE {
+      return String::from("a");
+    }
+    self.iri(value)
+  }
+
+  fn object(&mut self, o: &Object) -> String {
+    match o.term_type {
+      TermType::Literal => self.literal(o),
+      TermType::Triple => {
+        let triple = o.triple.as_ref().expect("triple term without a triple");
+        format!(
+          "<<( {} {} {} )>>",
+          self.node(triple.subject.term_type, &triple.subject.value),
+          self.predicate(&triple.predicate.value),
+          self.object(&triple.object)
+        )
+      }
+      term_type => self.node(term_type, &o.value),
+    }
+  }
+
+  fn literal(&mut self, o: &Object) -> String {
+    lazy_static! {
+      static ref INTEGER: Regex = Regex::new(r"^[+-]?[0-9]+$").unwrap();
+      static ref DECIMAL: Regex = Regex::new(r"^[+-]?[0-9]*\.[0-9]+$").unwrap();
+      static ref DOUBLE: Regex =
+        Regex::new(r"^[+-]?([0-9]+\.[0-9]*|\.?[0-9]+)[eE][+-]?[0-9]+$").unwrap();
+    }
+
+    let datatype = o.datatype.as_deref().unwrap_or(XSD_STRING);
+    let shorthand = match datatype {
+      XSD_INTEGER => INTEGER.is_match(&o.value),
+      XSD_DECIMAL => DECIMAL.is_match(&o.value),
+      XSD_DOUBLE => DOUBLE.is_match(&o.value),
+      XSD_BOOLEAN => o.value == "true" || o.value == "false",
+      _ => false,
+    };
+    if shorthand {
+      return o.value.clone();
+    }
+
+    let mut literal = format!("\"{}\"", escape_string(&o.value));
+    if datatype == RDF_LANGSTRING || datatype == RDF_DIRLANGSTRING {
+      if let Some(language) = &o.language {
+        literal.push('@');
+        literal.push_str(language);
+      }
+      if let Some(direction) = &o.direction {
+        literal.push_str("--");
+        literal.push_str(direction);
+      }
+    } else if datatype != XSD_STRING {
+      literal.push_str("^^");
+      literal.push_str(&self.iri(datatype));
+    }
+    literal
+  }
+}
+
+// https://www.w3.org/TR/turtle/#grammar-production-PN_LOCAL, without escapes
+// https://www.w3.org/TR/turtle/#grammar-production-PN_PREFIX, or "" for the
+// default prefix
+fn is_pn_prefix(prefix: &str) -> bool {
+  let mut chars = prefix.chars();
+  match chars.next() {
+    None => return true,
+    Some(c) if is_pn_chars_base(c) => {}
+    Some(_) => return false,
+  }
+  chars.all(|c| is_pn_chars(c) || c == '.') && !prefix.ends_with('.')
+}
+
+fn is_pn_local(local: &str) -> bool {
+  let mut chars = local.chars();
+  match chars.next() {
+    None => return true,
+    Some(c) if is_pn_chars_u(c) || c == ':' || c.is_ascii_digit() => {}
+    Some(_) => return false,
+  }
+  chars.all(|c| is_pn_chars(c) || c == ':' || c == '.') && !local.ends_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads;
+
+  fn assert_isomorphic(trig: &str, expected: &str) {
+    let dataset = parse_trig(trig, Some("http://example.org/doc.trig"));
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      crate::canonize(&nquads::parse_nquads(expected), "URDNA2015").unwrap()
+    );
+  }
+
+  #[test]
+  fn graph_blocks() {
+    assert_isomorphic(
+      "@prefix : <http://example.org/> .\n\
+:s :p :o .\n\
+:g1 { :s :p :o1 . :s :q :o2 }\n\
+GRAPH <g2> { :s :p [ :q :o ] }\n\
+{ :s :p :o3 . }\n",
+      "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n\
+<http://example.org/s> <http://example.org/p> <http://example.org/o1> <http://example.org/g1> .\n\
+<http://example.org/s> <http://example.org/q> <http://example.org/o2> <http://example.org/g1> .\n\
+<http://example.org/s> <http://example.org/p> _:b <http://example.org/g2> .\n\
+_:b <http://example.org/q> <http://example.org/o> <http://example.org/g2> .\n\
+<http://example.org/s> <http://example.org/p> <http://example.org/o3> .\n",
+    );
+  }
+
+  #[test]
+  fn blank_node_graph_names() {
+    let dataset = parse_trig(
+      "PREFIX : <http://example.org/>\n\
+_:g { :s :p _:x }\n\
+[] { :s :p :o }\n\
+_:x :in _:g .\n",
+      None,
+    );
+    assert_eq!(dataset.quads.len(), 3);
+    assert_eq!(
+      dataset.quads[0].graph.term_type,
+      nquads::TermType::BlankNode
+    );
+    assert_eq!(
+      dataset.quads[1].graph.term_type,
+      nquads::TermType::BlankNode
+    );
+    assert_ne!(dataset.quads[0].graph.value, dataset.quads[1].graph.value);
+    // labels are shared between graphs and the default graph
+    assert_eq!(
+      dataset.quads[2].subject.value,
+      dataset.quads[0].object.value
+    );
+    assert_eq!(dataset.quads[2].object.value, dataset.quads[0].graph.value);
+    assert_eq!(
+      dataset.quads[2].graph.term_type,
+      nquads::TermType::DefaultGraph
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert!(try_parse_trig("<http://example.org/g> { <s> <p> <o> }", None).is_err());
+    assert!(try_parse_trig("<http://example.org/g> { ", None).is_err());
+    assert!(try_parse_trig("GRAPH { }", None).is_err());
+    assert!(try_parse_trig("{ <http://example.org/g> { } }", None).is_err());
+    assert!(try_parse_trig("( ) { }", None).is_err());
+    let error =
+      try_parse_trig("{\n<http://a/s> <http://a/p> <http://a/o> . . }", None).unwrap_err();
+    assert_eq!(error.line, 2);
+  }
+
+  #[test]
+  fn serialize_groups_and_compacts() {
+    let dataset = nquads::parse_nquads(
+      "_:c14n10 <http://example.org/p> \"b\" .\n\
+_:c14n2 <http://example.org/p> <http://example.org/o> _:c14n0 .\n\
+<http://example.org/s> <http://example.org/p> \"x\"@en .\n\
+<http://example.org/s> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/C> .\n\
+<http://example.org/s> <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/q> \"01x\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/q> <http://other.example/a/b> .\n\
+<http://example.org/s> <http://example.org/p> \"x\"@en .\n\
+_:c14n2 <http://example.org/p> <http://example.org/o> <http://example.org/g> .\n",
+    );
+    let prefixes = [
+      ("ex", "http://example.org/"),
+      ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+      ("unused", "http://unused.example/"),
+    ];
+    assert_eq!(
+      serialize_trig(&dataset, &prefixes),
+      concat!(
+        "@prefix ex: <http://example.org/> .\n",
+        "@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n",
+        "\n",
+        "_:c14n10 ex:p \"b\" .\n",
+        "ex:s a ex:C ;\n",
+        "  ex:p \"x\"@en, 1 ;\n",
+        "  ex:q \"01x\"^^xsd:integer, <http://other.example/a/b> .\n",
+        "\n",
+        "_:c14n0 {\n",
+        "  _:c14n2 ex:p ex:o .\n",
+        "}\n",
+        "\n",
+        "ex:g {\n",
+        "  _:c14n2 ex:p ex:o .\n",
+        "}\n"
+      )
+    );
+  }
+
+  #[test]
+  fn serialize_round_trips() {
+    let input = "@prefix : <http://example.org/> .\n\
+:s :p ( 1 2.5 -3e4 true ) , \"multi\\nline \\\"quoted\\\"\"@en--ltr ;\n\
+  :q [ :r <http://example.org/a.> ], <<( :s :p _:x )>> .\n\
+_:g { _:x :p :o . :s :p \"a\", \"b\"^^:dt }\n";
+    let dataset = parse_trig(input, None);
+    let serialized = serialize_trig(&dataset, &[("", "http://example.org/")]);
+    assert_eq!(
+      crate::canonize(&parse_trig(&serialized, None), "URDNA2015").unwrap(),
+      crate::canonize(&dataset, "URDNA2015").unwrap()
+    );
+    assert_eq!(
+      serialized,
+      serialize_trig(&dataset, &[("", "http://example.org/")])
+    );
+  }
+
+  #[test]
+  fn serialize_ignores_invalid_prefixes() {
+    let dataset = nquads::parse_nquads(
+      "<http://example.org/s> <http://example.org/p> <http://other.example/o> .\n",
+    );
+    let prefixes = [
+      ("_", "http://example.org/"),
+      ("1a", "http://example.org/"),
+      ("a.", "http://other.example/"),
+      ("o", "http://other.example/"),
+    ];
+    let serialized = serialize_trig(&dataset, &prefixes);
+    assert_eq!(
+      serialized,
+      "@prefix o: <http://other.example/> .\n\n<http://example.org/s> <http://example.org/p> o:o .\n"
+    );
+    assert_eq!(
+      crate::canonize(&parse_trig(&serialized, None), "URDNA2015").unwrap(),
+      crate::canonize(&dataset, "URDNA2015").unwrap()
+    );
+  }
+}
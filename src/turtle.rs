@@ -0,0 +1,932 @@
+use crate::iri;
+use crate::lexer::{is_pn_chars, is_pn_chars_base, is_pn_chars_u};
+use crate::literal::{XSD_BOOLEAN, XSD_DECIMAL, XSD_DOUBLE, XSD_INTEGER};
+use crate::nquads::{
+  Dataset, Graph, Object, ParseError, Predicate, Quad, Subject, Term, TermType, Triple,
+  RDF_DIRLANGSTRING, RDF_FIRST, RDF_LANGSTRING, RDF_NIL, RDF_REST, RDF_TYPE, XSD_STRING,
+};
+
+use std::collections::HashMap;
+
+pub fn parse_turtle(input: &str, base: Option<&str>) -> Dataset {
+  try_parse_turtle(input, base).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Parses a Turtle document into the default graph of a dataset. Relative IRIs
+// are resolved against `base` (or an `@base` directive); blank node labels are
+// scoped to the document and renamed to `_:b0`, `_:b1`, ...
+pub fn try_parse_turtle(input: &str, base: Option<&str>) -> Result<Dataset, ParseError> {
+  let mut parser = Parser::new(input, base);
+  parser.skip_whitespace();
+  while !parser.is_at_end() {
+    if !parser.directive()? {
+      parser.triples()?;
+      parser.expect('.')?;
+    }
+    parser.skip_whitespace();
+  }
+
+  Ok(parser.into_dataset())
+}
+
+// A recursive descent parser for the Turtle grammar. The TriG parser drives
+// the same parser and switches `graph` between graph blocks.
+pub(crate) struct Parser<'a> {
+  input: &'a str,
+  position: usize,
+  base: Option<String>,
+  prefixes: HashMap<String, String>,
+  blank_nodes: HashMap<String, String>,
+  blank_node_counter: usize,
+  pub graph: Graph,
+  quads: Vec<Quad>,
+}
+
+impl<'a> Parser<'a> {
+  pub fn new(input: &'a str, base: Option<&str>) -> Parser<'a> {
+    Parser {
+      input,
+      position: 0,
+      base: base.map(|base| base.to_string()),
+      prefixes: HashMap::new(),
+      blank_nodes: HashMap::new(),
+      blank_node_counter: 0,
+      graph: default_graph(),
+      quads: Vec::new(),
+    }
+  }
+
+  pub fn into_dataset(self) -> Dataset {
+    Dataset { quads: self.quads }
+  }
+
+  pub fn is_at_end(&self) -> bool {
+    self.position >= self.input.len()
+  }
+
+  pub fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  pub fn error(&self, message: &str) -> ParseError {
+    let consumed = &self.input[..self.position];
+    let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+    ParseError {
+      line: consumed.matches('\n').count() + 1,
+      column: consumed[line_start..].chars().count() + 1,
+      message: message.to_string(),
+    }
+  }
+
+  pub fn skip_whitespace(&mut self) {
+    while let Some(c) = self.peek() {
+      match c {
+        ' ' | '\t' | '\r' | '\n' => self.position += 1,
+        '#' => {
+          self.position = self
+            .rest()
+            .find('\n')
+            .map_or(self.input.len(), |end| self.position + end)
+        }
+        _ => break,
+      }
+    }
+  }
+
+  pub fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+    self.skip_whitespace();
+    if self.peek() != Some(expected) {
+      return Err(self.unexpected(&format!("'{}'", expected)));
+    }
+    self.position += expected.len_utf8();
+    Ok(())
+  }
+
+  // Returns true if the next token is `keyword`, matched case-insensitively
+  // when `ignore_case` is set, and is not the start of a prefixed name.
+  pub fn at_keyword(&self, keyword: &str, ignore_case: bool) -> bool {
+    let rest = self.rest();
+    let candidate = match rest.get(..keyword.len()) {
+      Some(candidate) => candidate,
+      None => return false,
+    };
+    let matches = if ignore_case {
+      candidate.eq_ignore_ascii_case(keyword)
+    } else {
+      candidate == keyword
+    };
+
+    matches
+      && !rest[keyword.len()..]
+        .chars()
+        .next()
+        .is_some_and(|c| is_pn_chars(c) || c == ':')
+  }
+
+  // Consumes `keyword` if the input continues with it.
+  pub fn eat_keyword(&mut self, keyword: &str, ignore_case: bool) -> bool {
+    if !self.at_keyword(keyword, ignore_case) {
+      return false;
+    }
+    self.position += keyword.len();
+    true
+  }
+
+  // prefixID | base | sparqlPrefix | sparqlBase
+  pub fn directive(&mut self) -> Result<bool, ParseError> {
+    if self.eat_keyword("@prefix", false) {
+      self.prefix_declaration()?;
+      self.expect('.')?;
+    } else if self.eat_keyword("@base", false) {
+      self.base_declaration()?;
+      self.expect('.')?;
+    } else if self.eat_keyword("PREFIX", true) {
+      self.prefix_declaration()?;
+    } else if self.eat_keyword("BASE", true) {
+      self.base_declaration()?;
+    } else {
+      return Ok(false);
+    }
+
+    Ok(true)
+  }
+
+  fn prefix_declaration(&mut self) -> Result<(), ParseError> {
+    self.skip_whitespace();
+    let prefix = self.prefix_name()?;
+    self.expect(':')?;
+    self.skip_whitespace();
+    let namespace = self.iri_ref()?;
+    self.prefixes.insert(prefix, namespace);
+    Ok(())
+  }
+
+  fn base_declaration(&mut self) -> Result<(), ParseError> {
+    self.skip_whitespace();
+    let base = self.iri_ref()?;
+    self.base = Some(base);
+    Ok(())
+  }
+
+  // subject predicateObjectList | blankNodePropertyList predicateObjectList?
+  pub fn triples(&mut self) -> Result<(), ParseError> {
+    self.skip_whitespace();
+    if self.peek() == Some('[') {
+      let subject = self.blank_node_property_list()?;
+      self.skip_whitespace();
+      // the predicate object list is optional after a property list
+      if matches!(self.peek(), Some('.') | Some('}') | None) {
+        return Ok(());
+      }
+      return self.predicate_object_list(&subject);
+    }
+
+    let subject = self.subject()?;
+    self.predicate_object_list(&subject)
+  }
+
+  fn subject(&mut self) -> Result<Subject, ParseError> {
+    self.skip_whitespace();
+    let object = match self.peek() {
+      Some('<') if self.rest().starts_with("<<") => {
+        return Err(self.error("triple terms are only allowed in object position"))
+      }
+      Some('<') => named_node(self.iri_ref()?),
+      Some('_') => blank_node(self.blank_node_label()?),
+      Some('(') => self.collection()?,
+      Some(c) if c == ':' || is_pn_chars_base(c) => named_node(self.prefixed_name()?),
+      _ => return Err(self.unexpected("subject")),
+    };
+
+    Ok(object_to_subject(object))
+  }
+
+  // Returns true if the input continues with an IRI, a blank node label or
+  // `[]`, any of which can name a graph in TriG.
+  pub fn at_label_or_subject(&self) -> bool {
+    match self.peek() {
+      Some('<') => !self.rest().starts_with("<<"),
+      Some('_') => true,
+      Some('[') => self.at_anon(),
+      Some(c) => c == ':' || is_pn_chars_base(c),
+      None => false,
+    }
+  }
+
+  // labelOrSubject ::= iri | BlankNode
+  pub fn label_or_subject(&mut self) -> Result<Subject, ParseError> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('[') if self.at_anon() => {
+        self.position += self.rest().find(']').unwrap() + 1;
+        Ok(object_to_subject(blank_node(self.fresh_blank_node())))
+      }
+      Some('[') | Some('(') => Err(self.unexpected("IRI or blank node")),
+      _ => self.subject(),
+    }
+  }
+
+  // ANON ::= '[' WS* ']'
+  fn at_anon(&self) -> bool {
+    let rest = self.rest();
+    rest.starts_with('[')
+      && rest[1..]
+        .trim_start_matches([' ', '\t', '\r', '\n'])
+        .starts_with(']')
+  }
+
+  // verb objectList (';' (verb objectList)?)*
+  pub fn predicate_object_list(&mut self, subject: &Subject) -> Result<(), ParseError> {
+    loop {
+      let predicate = self.verb()?;
+      self.object_list(subject, &predicate)?;
+
+      self.skip_whitespace();
+      if self.peek() != Some(';') {
+        return Ok(());
+      }
+      while self.peek() == Some(';') {
+        self.position += 1;
+        self.skip_whitespace();
+      }
+      if matches!(self.peek(), Some('.') | Some(']') | Some('}') | None) {
+        return Ok(());
+      }
+    }
+  }
+
+  fn object_list(&mut self, subject: &Subject, predicate: &Predicate) -> Result<(), ParseError> {
+    loop {
+      let object = self.object()?;
+      self.emit(subject.clone(), predicate.clone(), object);
+
+      self.skip_whitespace();
+      if self.peek() != Some(',') {
+        return Ok(());
+      }
+      self.position += 1;
+    }
+  }
+
+  fn verb(&mut self) -> Result<Predicate, ParseError> {
+    self.skip_whitespace();
+    let value = if self.eat_keyword("a", false) {
+      RDF_TYPE.to_string()
+    } else {
+      match self.peek() {
+        Some('<') => self.iri_ref()?,
+        Some(c) if c == ':' || is_pn_chars_base(c) => self.prefixed_name()?,
+        _ => return Err(self.unexpected("predicate")),
+      }
+    };
+
+    Ok(Predicate {
+      term_type: TermType::NamedNode,
+      value,
+    })
+  }
+
+  fn object(&mut self) -> Result<Object, ParseError> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('<') if self.rest().starts_with("<<(") => self.triple_term(),
+      Some('<') => Ok(named_node(self.iri_ref()?)),
+      Some('_') => Ok(blank_node(self.blank_node_label()?)),
+      Some('[') => Ok(subject_to_object(self.blank_node_property_list()?)),
+      Some('(') => self.collection(),
+      Some('"') | Some('\'') => self.rdf_literal(),
+      Some(c) if c.is_ascii_digit() || c == '+' || c == '-' || c == '.' => self.numeric_literal(),
+      Some(_) if self.at_keyword("true", false) || self.at_keyword("false", false) => {
+        let value = if self.at_keyword("true", false) {
+          "true"
+        } else {
+          "false"
+        };
+        self.position += value.len();
+        Ok(typed_literal(value.to_string(), XSD_BOOLEAN))
+      }
+      Some(c) if c == ':' || is_pn_chars_base(c) => Ok(named_node(self.prefixed_name()?)),
+      _ => Err(self.unexpected("object")),
+    }
+  }
+
+  // '<<(' ttSubject predicate ttObject ')>>'
+  fn triple_term(&mut self) -> Result<Object, ParseError> {
+    self.position += "<<(".len();
+    self.skip_whitespace();
+    let subject = match self.peek() {
+      Some('<') if !self.rest().starts_with("<<") => named_node(self.iri_ref()?),
+      Some('_') => blank_node(self.blank_node_label()?),
+      Some(c) if c == ':' || is_pn_chars_base(c) => named_node(self.prefixed_name()?),
+      _ => return Err(self.unexpected("IRI or blank node subject")),
+    };
+    let predicate = self.verb()?;
+    self.skip_whitespace();
+    let object = match self.peek() {
+      Some('[') | Some('(') => return Err(self.unexpected("triple term object")),
+      _ => self.object()?,
+    };
+    self.skip_whitespace();
+    if !self.rest().starts_with(")>>") {
+      return Err(self.unexpected("')>>'"));
+    }
+    self.position += ")>>".len();
+
+    let mut triple_term = Object::new();
+    triple_term.term_type = TermType::Triple;
+    triple_term.triple = Some(Box::new(Triple {
+      subject: object_to_subject(subject),
+      predicate,
+      object,
+    }));
+    Ok(triple_term)
+  }
+
+  // '[' predicateObjectList? ']'
+  fn blank_node_property_list(&mut self) -> Result<Subject, ParseError> {
+    self.position += 1;
+    let subject = object_to_subject(blank_node(self.fresh_blank_node()));
+    self.skip_whitespace();
+    if self.peek() != Some(']') {
+      self.predicate_object_list(&subject)?;
+    }
+    self.expect(']')?;
+    Ok(subject)
+  }
+
+  // '(' object* ')'
+  fn collection(&mut self) -> Result<Object, ParseError> {
+    self.position += 1;
+    let mut items = Vec::new();
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        Some(')') => break,
+        None => return Err(self.unexpected("')'")),
+        _ => items.push(self.object()?),
+      }
+    }
+    self.position += 1;
+
+    if items.is_empty() {
+      return Ok(named_node(RDF_NIL.to_string()));
+    }
+
+    let nodes: Vec<String> = items.iter().map(|_| self.fresh_blank_node()).collect();
+    let first = rdf_predicate(RDF_FIRST);
+    let rest = rdf_predicate(RDF_REST);
+    for (i, item) in items.into_iter().enumerate() {
+      let node = object_to_subject(blank_node(nodes[i].clone()));
+      self.emit(node.clone(), first.clone(), item);
+      let next = match nodes.get(i + 1) {
+        Some(next) => blank_node(next.clone()),
+        None => named_node(RDF_NIL.to_string()),
+      };
+      self.emit(node, rest.clone(), next);
+    }
+
+    Ok(blank_node(nodes[0].clone()))
+  }
+
+  // String (LANGTAG | '^^' iri)?
+  fn rdf_literal(&mut self) -> Result<Object, ParseError> {
+    let value = self.string()?;
+    if self.peek() == Some('@') {
+      let (language, direction) = self.lang_tag()?;
+      let mut literal = typed_literal(
+        value,
+        if direction.is_some() {
+          RDF_DIRLANGSTRING
+        } else {
+          RDF_LANGSTRING
+        },
+      );
+      literal.language = Some(language);
+      literal.direction = direction;
+      return Ok(literal);
+    }
+    if self.rest().starts_with("^^") {
+      self.position += 2;
+      let datatype = match self.peek() {
+        Some('<') => self.iri_ref()?,
+        Some(c) if c == ':' || is_pn_chars_base(c) => self.prefixed_name()?,
+        _ => return Err(self.unexpected("datatype IRI")),
+      };
+      return Ok(typed_literal(value, &datatype));
+    }
+
+    Ok(typed_literal(value, XSD_STRING))
+  }
+
+  // INTEGER | DECIMAL | DOUBLE
+  fn numeric_literal(&mut self) -> Result<Object, ParseError> {
+    let start = self.position;
+    if matches!(self.peek(), Some('+') | Some('-')) {
+      self.position += 1;
+    }
+    let integer_digits = self.skip_digits();
+    let mut fraction_digits = 0;
+    let mut datatype = XSD_INTEGER;
+
+    // a '.' only belongs to the number if digits or an exponent follow it
+    let rest = self.rest();
+    let mut after_dot = rest.chars().skip(1);
+    if rest.starts_with('.') {
+      let next = after_dot.next();
+      let exponent = matches!(next, Some('e') | Some('E')) && integer_digits > 0;
+      if next.is_some_and(|c| c.is_ascii_digit()) || exponent {
+        self.position += 1;
+        fraction_digits = self.skip_digits();
+        datatype = XSD_DECIMAL;
+      }
+    }
+    if integer_digits == 0 && fraction_digits == 0 {
+      self.position = start;
+      return Err(self.unexpected("number"));
+    }
+
+    if matches!(self.peek(), Some('e') | Some('E')) {
+      self.position += 1;
+      if matches!(self.peek(), Some('+') | Some('-')) {
+        self.position += 1;
+      }
+      if self.skip_digits() == 0 {
+        return Err(self.unexpected("exponent digits"));
+      }
+      datatype = XSD_DOUBLE;
+    }
+
+    let value = self.input[start..self.position].to_string();
+    Ok(typed_literal(value, datatype))
+  }
+
+  fn skip_digits(&mut self) -> usize {
+    let digits = self
+      .rest()
+      .bytes()
+      .take_while(|c| c.is_ascii_digit())
+      .count();
+    self.position += digits;
+    digits
+  }
+
+  // https://www.w3.org/TR/turtle/#grammar-production-IRIREF
+  pub fn iri_ref(&mut self) -> Result<String, ParseError> {
+    if self.peek() != Some('<') {
+      return Err(self.unexpected("IRI"));
+    }
+    let start = self.position;
+    self.position += 1;
+    let mut value = String::with_capacity(crate::nquads::DEFAULT_TERM_CAPACITY);
+    loop {
+      match self.peek() {
+        Some('>') => {
+          self.position += 1;
+          break;
+        }
+        Some('\\') => {
+          self.position += 1;
+          let c = self.uchar()?;
+          if !is_iri_char(c) {
+            return Err(self.error("invalid escaped character in IRI"));
+          }
+          value.push(c);
+        }
+        Some(c) if is_iri_char(c) => {
+          self.position += c.len_utf8();
+          value.push(c);
+        }
+        Some(_) => return Err(self.error("invalid character in IRI")),
+        None => {
+          self.position = start;
+          return Err(self.error("unterminated IRI"));
+        }
+      }
+    }
+
+    self.resolve(value, start)
+  }
+
+  fn resolve(&mut self, value: String, start: usize) -> Result<String, ParseError> {
+    if iri::has_scheme(&value) {
+      return Ok(value);
+    }
+    match &self.base {
+      Some(base) => Ok(iri::resolve(base, &value)),
+      None => {
+        self.position = start;
+        Err(self.error(&format!("relative IRI <{}> without a base IRI", value)))
+      }
+    }
+  }
+
+  // PNAME_NS PN_LOCAL?
+  pub fn prefixed_name(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    let prefix = self.prefix_name()?;
+    if self.peek() != Some(':') {
+      return Err(self.unexpected("':'"));
+    }
+    self.position += 1;
+    let local = self.local_name()?;
+
+    match self.prefixes.get(&prefix) {
+      Some(namespace) => Ok(format!("{}{}", namespace, local)),
+      None => {
+        self.position = start;
+        Err(self.error(&format!("undefined prefix \"{}:\"", prefix)))
+      }
+    }
+  }
+
+  // PN_PREFIX ::= PN_CHARS_BASE ((PN_CHARS | '.')* PN_CHARS)?
+  fn prefix_name(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    match self.peek() {
+      Some(':') => return Ok(String::new()),
+      Some(c) if is_pn_chars_base(c) => self.position += c.len_utf8(),
+      _ => return Err(self.unexpected("prefix name")),
+    }
+    while let Some(c) = self.peek() {
+      if !is_pn_chars(c) && c != '.' {
+        break;
+      }
+      self.position += c.len_utf8();
+    }
+    if self.input[..self.position].ends_with('.') {
+      return Err(self.error("prefix names cannot end with '.'"));
+    }
+
+    Ok(self.input[start..self.position].to_string())
+  }
+
+  // PN_LOCAL, with escapes removed; trailing '.' is left for the statement
+  fn local_name(&mut self) -> Result<String, ParseError> {
+    let mut local = String::new();
+    // length of `local` and input position at the last non-'.' character
+    let mut committed = (0, self.position);
+    let mut first = true;
+    while let Some(c) = self.peek() {
+      let valid = if first {
+        is_pn_chars_u(c) || c == ':' || c.is_ascii_digit()
+      } else {
+        is_pn_chars(c) || c == ':' || c == '.'
+      };
+      if c == '%' {
+        let hex = self.rest().get(1..3).unwrap_or("");
+        if hex.len() != 2 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+          return Err(self.error("invalid percent encoding"));
+        }
+        local.push_str(&self.input[self.position..self.position + 3]);
+        self.position += 3;
+      } else if c == '\\' {
+        match self.rest().chars().nth(1) {
+          Some(escaped) if "_~.-!$&'()*+,;=/?#@%".contains(escaped) => {
+            local.push(escaped);
+            self.position += 2;
+          }
+          _ => return Err(self.error("invalid escape in local name")),
+        }
+      } else if valid {
+        local.push(c);
+        self.position += c.len_utf8();
+        if c == '.' {
+          first = false;
+          continue;
+        }
+      } else {
+        break;
+      }
+      first = false;
+      committed = (local.len(), self.position);
+    }
+    local.truncate(committed.0);
+    self.position = committed.1;
+
+    Ok(local)
+  }
+
+  // https://www.w3.org/TR/turtle/#grammar-production-BLANK_NODE_LABEL
+  fn blank_node_label(&mut self) -> Result<String, ParseError> {
+    if !self.rest().starts_with("_:") {
+      return Err(self.unexpected("blank node"));
+    }
+    self.position += 2;
+    let start = self.position;
+    match self.peek() {
+      Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => self.position += c.len_utf8(),
+      _ => return Err(self.error("invalid blank node label")),
+    }
+    while let Some(c) = self.peek() {
+      if !is_pn_chars(c) && c != '.' {
+        break;
+      }
+      self.position += c.len_utf8();
+    }
+    while self.input[..self.position].ends_with('.') {
+      self.position -= 1;
+    }
+
+    let label = self.input[start..self.position].to_string();
+    if let Some(id) = self.blank_nodes.get(&label) {
+      return Ok(id.clone());
+    }
+    let id = self.fresh_blank_node();
+    self.blank_nodes.insert(label, id.clone());
+    Ok(id)
+  }
+
+  pub fn fresh_blank_node(&mut self) -> String {
+    let id = format!("_:b{}", self.blank_node_counter);
+    self.blank_node_counter += 1;
+    id
+  }
+
+  // STRING_LITERAL_QUOTE | STRING_LITERAL_SINGLE_QUOTE |
+  // STRING_LITERAL_LONG_SINGLE_QUOTE | STRING_LITERAL_LONG_QUOTE
+  fn string(&mut self) -> Result<String, ParseError> {
+    let start = self.position;
+    let quote = self.peek().unwrap();
+    let long_quote: String = std::iter::repeat_n(quote, 3).collect();
+    let long = self.rest().starts_with(&long_quote);
+    self.position += if long { 3 } else { 1 };
+
+    let mut value = String::with_capacity(crate::nquads::DEFAULT_TERM_CAPACITY);
+    loop {
+      match self.peek() {
+        Some(c) if c == quote && !long => {
+          self.position += 1;
+          break;
+        }
+        Some(c) if c == quote && self.rest().starts_with(&long_quote) => {
+          // a long string may end with up to two extra quotes
+          let quotes = self.rest().chars().take_while(|c| *c == quote).count();
+          for _ in 0..(quotes.min(5) - 3) {
+            value.push(quote);
+          }
+          self.position += quotes.min(5);
+          break;
+        }
+        Some('\\') => {
+          self.position += 1;
+          let c = self.echar_or_uchar()?;
+          value.push(c);
+        }
+        Some('\n') | Some('\r') if !long => {
+          return Err(self.error("line breaks must be escaped in short strings"))
+        }
+        Some(c) => {
+          self.position += c.len_utf8();
+          value.push(c);
+        }
+        None => {
+          self.position = start;
+          return Err(self.error("unterminated string"));
+        }
+      }
+    }
+
+    Ok(value)
+  }
+
+  // LANGTAG with an optional RDF 1.2 base direction
+  fn lang_tag(&mut self) -> Result<(String, Option<String>), ParseError> {
+    self.position += 1;
+    let start = self.position;
+    let primary = self
+      .rest()
+      .bytes()
+      .take_while(|c| c.is_ascii_alphabetic())
+      .count();
+    if primary == 0 {
+      return Err(self.error("invalid language tag"));
+    }
+    self.position += primary;
+    while self.peek() == Some('-') && !self.rest().starts_with("--") {
+      self.position += 1;
+      let subtag = self
+        .rest()
+        .bytes()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .count();
+      if subtag == 0 {
+        return Err(self.error("invalid language tag"));
+      }
+      self.position += subtag;
+    }
+    let language = self.input[start..self.position].to_string();
+
+    if !self.rest().starts_with("--") {
+      return Ok((language, None));
+    }
+    self.position += 2;
+    if self.at_keyword("ltr", false) || self.at_keyword("rtl", false) {
+      let direction = self.input[self.position..self.position + 3].to_string();
+      self.position += 3;
+      return Ok((language, Some(direction)));
+    }
+
+    Err(self.error("base direction must be 'ltr' or 'rtl'"))
+  }
+
+  fn echar_or_uchar(&mut self) -> Result<char, ParseError> {
+    let escaped = match self.peek() {
+      Some('t') => '\t',
+      Some('b') => '\x08',
+      Some('n') => '\n',
+      Some('r') => '\r',
+      Some('f') => '\x0C',
+      Some('"') => '"',
+      Some('\'') => '\'',
+      Some('\\') => '\\',
+      _ => return self.uchar(),
+    };
+    self.position += 1;
+
+    Ok(escaped)
+  }
+
+  // decodes `\uXXXX` or `\UXXXXXXXX`; the backslash is already consumed
+  fn uchar(&mut self) -> Result<char, ParseError> {
+    let length = match self.peek() {
+      Some('u') => 4,
+      Some('U') => 8,
+      _ => return Err(self.error("invalid escape sequence")),
+    };
+    let hex = self.rest().get(1..=length).unwrap_or("");
+    if hex.len() != length || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+      return Err(self.error("invalid unicode escape sequence"));
+    }
+    let c = u32::from_str_radix(hex, 16)
+      .ok()
+      .and_then(std::char::from_u32)
+      .ok_or_else(|| self.error("invalid unicode code point"))?;
+    self.position += length + 1;
+
+    Ok(c)
+  }
+
+  fn emit(&mut self, subject: Subject, predicate: Predicate, object: Object) {
+    self.quads.push(Quad {
+      subject,
+      predicate,
+      object,
+      graph: self.graph.clone(),
+    });
+  }
+
+  fn unexpected(&self, expected: &str) -> ParseError {
+    match self.peek() {
+      Some(c) => self.error(&format!(
+        "expected {}, found '{}'",
+        expected,
+        c.escape_default()
+      )),
+      None => self.error(&format!("expected {}, found end of input", expected)),
+    }
+  }
+
+  fn rest(&self) -> &'a str {
+    &self.input[self.position..]
+  }
+}
+
+pub(crate) fn default_graph() -> Graph {
+  Graph {
+    term_type: TermType::DefaultGraph,
+    value: String::from("@default"),
+  }
+}
+
+fn is_iri_char(c: char) -> bool {
+  !matches!(
+    c,
+    '\x00'..=' ' | '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\'
+  )
+}
+
+pub(crate) fn named_node(value: String) -> Object {
+  let mut object = Object::new();
+  object.term_type = TermType::NamedNode;
+  object.value = value;
+  object
+}
+
+pub(crate) fn blank_node(value: String) -> Object {
+  let mut object = Object::new();
+  object.term_type = TermType::BlankNode;
+  object.value = value;
+  object
+}
+
+pub(crate) fn typed_literal(value: String, datatype: &str) -> Object {
+  let mut object = Object::new();
+  object.term_type = TermType::Literal;
+  object.value = value;
+  object.datatype = Some(datatype.to_string());
+  object
+}
+
+pub(crate) fn rdf_predicate(value: &str) -> Predicate {
+  Predicate {
+    term_type: TermType::NamedNode,
+    value: value.to_string(),
+  }
+}
+
+pub(crate) fn object_to_subject(object: Object) -> Subject {
+  Subject {
+    term_type: object.term_type,
+    value: object.value,
+  }
+}
+
+pub(crate) fn subject_to_object(subject: Subject) -> Object {
+  let mut object = Object::new();
+  object.term_type = subject.term_type;
+  object.value = subject.value;
+  object
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::nquads;
+
+  fn assert_isomorphic(turtle: &str, expected: &str) {
+    let dataset = parse_turtle(turtle, Some("http://example.org/base/doc.ttl"));
+    assert_eq!(
+      crate::canonize(&dataset, "URDNA2015").unwrap(),
+      crate::canonize(&nquads::parse_nquads(expected), "URDNA2015").unwrap()
+    );
+  }
+
+  #[test]
+  fn prefixes_and_base() {
+    assert_isomorphic(
+      "@prefix ex: <http://example.org/ns#> .\n\
+PREFIX : <../vocab/>\n\
+@base <http://example.org/other/> .\n\
+<s> a ex:Type ; :p <#o>, ex:esc\\~aped ; ex:last.part <o2>. # comment\n",
+      "<http://example.org/other/s> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/ns#Type> .\n\
+<http://example.org/other/s> <http://example.org/vocab/p> <http://example.org/other/#o> .\n\
+<http://example.org/other/s> <http://example.org/vocab/p> <http://example.org/ns#esc~aped> .\n\
+<http://example.org/other/s> <http://example.org/ns#last.part> <http://example.org/other/o2> .\n",
+    );
+  }
+
+  #[test]
+  fn blank_nodes_and_collections() {
+    assert_isomorphic(
+      "@prefix : <http://example.org/> .\n\
+[ :name \"a\" ] :knows _:x, [ :name \"b\" ] .\n\
+_:x :list ( 1 :o ( ) ) .\n\
+[] :empty () .\n",
+      "_:a <http://example.org/name> \"a\" .\n\
+_:a <http://example.org/knows> _:x .\n\
+_:a <http://example.org/knows> _:b .\n\
+_:b <http://example.org/name> \"b\" .\n\
+_:x <http://example.org/list> _:l0 .\n\
+_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+_:l0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:l1 .\n\
+_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://example.org/o> .\n\
+_:l1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:l2 .\n\
+_:l2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n\
+_:l2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n\
+_:e <http://example.org/empty> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .\n",
+    );
+  }
+
+  #[test]
+  fn literals() {
+    assert_isomorphic(
+      "@prefix : <http://example.org/> .\n\
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\
+:s :p 1, -2.5, .5e3, true, false, 'single', \"chat\"@fr, \"\"\"long \"quoted\"\n\
+text\"\"\", '1'^^xsd:integer, \"x\\u00E9\\n\"^^<http://example.org/dt>, \"abc\"@ar--rtl .\n\
+:s :q 1.\n",
+      "<http://example.org/s> <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/p> \"-2.5\"^^<http://www.w3.org/2001/XMLSchema#decimal> .\n\
+<http://example.org/s> <http://example.org/p> \".5e3\"^^<http://www.w3.org/2001/XMLSchema#double> .\n\
+<http://example.org/s> <http://example.org/p> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .\n\
+<http://example.org/s> <http://example.org/p> \"false\"^^<http://www.w3.org/2001/XMLSchema#boolean> .\n\
+<http://example.org/s> <http://example.org/p> \"single\" .\n\
+<http://example.org/s> <http://example.org/p> \"chat\"@fr .\n\
+<http://example.org/s> <http://example.org/p> \"long \\\"quoted\\\"\\ntext\" .\n\
+<http://example.org/s> <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+<http://example.org/s> <http://example.org/p> \"x\\u00E9\\n\"^^<http://example.org/dt> .\n\
+<http://example.org/s> <http://example.org/p> \"abc\"@ar--rtl .\n\
+<http://example.org/s> <http://example.org/q> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+    );
+  }
+
+  #[test]
+  fn errors() {
+    let error = try_parse_turtle("@prefix : <http://example.org/> .\n:s :p", None).unwrap_err();
+    assert_eq!(error.line, 2);
+    assert_eq!(error.message, "expected object, found end of input");
+    assert!(try_parse_turtle("<s> <p> <o> .", None).is_err());
+    assert!(try_parse_turtle("ex:s ex:p ex:o .", None).is_err());
+    assert!(try_parse_turtle("@prefix : <http://example.org/> .\n:s :p :o", None).is_err());
+  }
+}
@@ -1,12 +1,13 @@
 use crate::identifier_issuer::IdentifierIssuer;
 use crate::message_digest::MessageDigest;
 use crate::nquads;
-use crate::nquads::{Dataset, Quad, Term, TermType};
+use crate::nquads::{Quad, QuadSerialize, Term, TermType};
 use crate::permuter::Permuter;
+use crate::sink::QuadSink;
 
-use lexical_sort::natural_lexical_cmp;
 use sha2::Sha256;
 use std::collections::HashMap;
+use std::io;
 
 // A Hex Encoded SHA2 Digest will always be 64 characters
 // '<' + result.hash.len() + '>'
@@ -19,7 +20,7 @@ const NAME: &str = "URDNA2015";
 const HASH_ALGORITHM: &str = "sha256";
 
 type Hash = String;
-type BlankNodeInfoMap<'a> = HashMap<String, BlankNodeInfo<'a>>;
+type BlankNodeInfoMap<'a, Q> = HashMap<String, BlankNodeInfo<'a, Q>>;
 type HashBlankNodeMap = HashMap<String, Vec<String>>;
 type HashToRelatedMap = HashMap<String, Vec<String>>;
 
@@ -30,21 +31,24 @@ struct HashNDegreeResult {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-struct BlankNodeInfo<'a> {
-  pub quads: Vec<&'a Quad>,
+struct BlankNodeInfo<'a, Q> {
+  pub quads: Vec<&'a Q>,
   hash: Option<Hash>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct URDNA2015<'a> {
+pub struct URDNA2015<'a, Q> {
   name: String,
-  blank_node_info: BlankNodeInfoMap<'a>,
+  blank_node_info: BlankNodeInfoMap<'a, Q>,
   canonical_issuer: IdentifierIssuer,
   hash_algorithm: String,
 }
 
-impl<'b> URDNA2015<'b> {
-  pub fn new<'a>() -> URDNA2015<'a> {
+impl<'b, Q> URDNA2015<'b, Q>
+where
+  Q: for<'q> QuadSerialize<'q>,
+{
+  pub fn new<'a>() -> URDNA2015<'a, Q> {
     URDNA2015 {
       name: String::from(NAME),
       blank_node_info: BlankNodeInfoMap::new(),
@@ -53,18 +57,74 @@ impl<'b> URDNA2015<'b> {
     }
   }
 
+  // Runs the normalization algorithm and returns the canonical N-Quads.
+  pub fn main(&mut self, quads: &'b [Q]) -> String {
+    let mut normalized = String::new();
+    // writing to a `String` cannot fail
+    self.main_into(quads, &mut normalized).unwrap();
+    normalized
+  }
+
+  // Runs the normalization algorithm and writes the canonical N-Quads to
+  // `sink` one quad at a time, in canonical order.
+  pub fn main_into<K>(&mut self, quads: &'b [Q], sink: &mut K) -> io::Result<()>
+  where
+    K: QuadSink,
+  {
+    self.issue_canonical_ids(quads);
+    self.write_normalized(quads, sink)
+  }
+
+  // Runs the normalization algorithm and returns the quads of the normalized
+  // dataset, relabeled with their canonical blank node identifiers, together
+  // with the canonical N-Quads. Both are in canonical order.
+  pub fn main_quads(&mut self, quads: &'b [Q]) -> (Vec<Quad>, String) {
+    self.issue_canonical_ids(quads);
+
+    // 7) For each quad, quad, in input dataset:
+    // 7.1) Create a copy, quad copy, of quad and replace any existing blank
+    // node identifiers using the canonical identifiers previously issued by
+    // canonical issuer.
+    // 7.2) Add quad copy to the normalized dataset.
+    let mut normalized: Vec<(String, Quad)> = quads
+      .iter()
+      .map(|quad| {
+        let quad_copy = self.relabel(quad);
+        (nquads::serialize_quad(&quad_copy), quad_copy)
+      })
+      .collect();
+
+    // sort normalized output
+    normalized.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    // 8) Return the normalized dataset.
+    let mut serialized = String::new();
+    let mut normalized_quads = Vec::with_capacity(normalized.len());
+    for (nquad, quad) in normalized {
+      serialized.push_str(&nquad);
+      normalized_quads.push(quad);
+    }
+    (normalized_quads, serialized)
+  }
+
   // 4.4) Normalization Algorithm
-  pub fn main(&mut self, dataset: &'b Dataset) -> String {
-    let quads = &dataset.quads;
+  // Note: Steps 1) to 6) issue the canonical identifiers; step 7) is either
+  // `write_normalized` or `main_quads`.
+  fn issue_canonical_ids(&mut self, quads: &'b [Q]) {
     // 1) Create the normalization state.
     // 2) For every quad in input dataset:
     for quad in quads {
       // 2.1) For each blank node that occurs in the quad, add a reference
       // to the quad using the blank node identifier in the blank node to
       // quads map, creating a new entry if necessary.
-      self.add_blank_node_quad_info(&quad, &quad.subject);
-      self.add_blank_node_quad_info(&quad, &quad.object);
-      self.add_blank_node_quad_info(&quad, &quad.graph);
+      self.add_blank_node_quad_info(quad, quad.get_subject());
+      self.add_blank_node_quad_info(quad, quad.get_object());
+      self.add_blank_node_quad_info(quad, quad.get_graph());
+      // Note: Blank nodes nested inside a triple term are components of the
+      // quad as well, so they reference it in the same way.
+      for id in quad.get_object().nested_blank_nodes() {
+        self.add_blank_node_id_quad_info(quad, id);
+      }
     }
 
     // 3) Create a list of non-normalized blank node identifiers
@@ -129,7 +189,7 @@ impl<'b> URDNA2015<'b> {
       for id in id_list.iter() {
         // 6.2.1) If a canonical identifier has already been issued for
         // identifier, continue to the next identifier.
-        if self.canonical_issuer.has_id(&id) {
+        if self.canonical_issuer.has_id(id) {
           continue;
         }
 
@@ -144,13 +204,13 @@ impl<'b> URDNA2015<'b> {
 
         // 6.2.4) Run the Hash N-Degree Quads algorithm, passing
         // temporary issuer, and append the result to the hash path list.
-        let result = self.hash_n_degree_quads(&id, issuer);
+        let result = self.hash_n_degree_quads(id, issuer);
         hash_path_list.push(result);
       }
 
       // 6.3) For each result in the hash path list,
       // lexicographically-sorted by the hash in result:
-      hash_path_list.sort_by(|a, b| natural_lexical_cmp(&a.hash, &b.hash));
+      hash_path_list.sort_by(|a, b| a.hash.cmp(&b.hash));
       for result in hash_path_list {
         // 6.3.1) For each blank node identifier, existing identifier,
         // that was issued a temporary identifier by identifier issuer
@@ -168,7 +228,12 @@ impl<'b> URDNA2015<'b> {
     assigned canonical identifiers, which have been stored in the canonical
     issuer. Here each quad is updated by assigning each of its blank nodes
     its new identifier. */
+  }
 
+  fn write_normalized<K>(&self, quads: &[Q], sink: &mut K) -> io::Result<()>
+  where
+    K: QuadSink,
+  {
     // 7) For each quad, quad, in input dataset:
     let mut normalized = Vec::with_capacity(quads.len());
     for quad in quads {
@@ -179,12 +244,12 @@ impl<'b> URDNA2015<'b> {
       // subject
       let s: nquads::Subject;
       let mut subject: Option<&nquads::Subject> = None;
-      if Self::should_use_canonical_id(&quad.subject, &self.canonical_issuer) {
+      if Self::should_use_canonical_id(quad.get_subject()) {
         s = nquads::Subject {
-          term_type: quad.subject.term_type,
+          term_type: quad.get_subject().term_type,
           value: self
             .canonical_issuer
-            .get_existing_id(&quad.subject.value)
+            .get_existing_id(&quad.get_subject().value)
             .unwrap(),
         };
         subject = Some(&s);
@@ -193,28 +258,38 @@ impl<'b> URDNA2015<'b> {
       // object
       let o: nquads::Object;
       let mut object: Option<&nquads::Object> = None;
-      if Self::should_use_canonical_id(&quad.object, &self.canonical_issuer) {
+      if Self::should_use_canonical_id(quad.get_object()) {
         o = nquads::Object {
-          term_type: quad.object.term_type,
+          term_type: quad.get_object().term_type,
           value: self
             .canonical_issuer
-            .get_existing_id(&quad.object.value)
+            .get_existing_id(&quad.get_object().value)
             .unwrap(),
-          datatype: quad.object.get_datatype(),
-          language: quad.object.get_language(),
+          datatype: quad.get_object().get_datatype(),
+          language: quad.get_object().get_language(),
+          direction: quad.get_object().get_direction(),
+          triple: None,
         };
         object = Some(&o);
+      } else if !quad.get_object().nested_blank_nodes().is_empty() {
+        let issuer = &self.canonical_issuer;
+        o = quad.get_object().map_blank_nodes(&mut |value| {
+          issuer
+            .get_existing_id(value)
+            .unwrap_or_else(|| value.to_string())
+        });
+        object = Some(&o);
       }
 
       // graph
       let g: nquads::Graph;
       let mut graph: Option<&nquads::Graph> = None;
-      if Self::should_use_canonical_id(&quad.graph, &self.canonical_issuer) {
+      if Self::should_use_canonical_id(quad.get_graph()) {
         g = nquads::Graph {
-          term_type: quad.graph.term_type,
+          term_type: quad.get_graph().term_type,
           value: self
             .canonical_issuer
-            .get_existing_id(&quad.graph.value)
+            .get_existing_id(&quad.get_graph().value)
             .unwrap(),
         };
         graph = Some(&g);
@@ -226,10 +301,10 @@ impl<'b> URDNA2015<'b> {
         normalized.push(nquads::serialize_quad(quad));
       } else {
         let quad_copy = nquads::QuadRef {
-          subject: subject.or(Some(&quad.subject)).unwrap(),
-          predicate: &quad.predicate,
-          object: object.or(Some(&quad.object)).unwrap(),
-          graph: graph.or(Some(&quad.graph)).unwrap(),
+          subject: subject.unwrap_or(quad.get_subject()),
+          predicate: quad.get_predicate(),
+          object: object.unwrap_or(quad.get_object()),
+          graph: graph.unwrap_or(quad.get_graph()),
         };
         normalized.push(nquads::serialize_quad(&quad_copy));
       }
@@ -239,7 +314,50 @@ impl<'b> URDNA2015<'b> {
     normalized.sort_unstable();
 
     // 8) Return the normalized dataset.
-    normalized.join("")
+    for quad in &normalized {
+      sink.write_quad(quad)?;
+    }
+    Ok(())
+  }
+
+  // Returns a copy of quad with every blank node replaced by its canonical
+  // identifier. This is the quad copy of step 7.1), which `write_normalized`
+  // only creates when it has to.
+  fn relabel(&self, quad: &Q) -> Quad {
+    let issuer = &self.canonical_issuer;
+    let mut canonical_id = |value: &str| {
+      issuer
+        .get_existing_id(value)
+        .unwrap_or_else(|| value.to_string())
+    };
+
+    let subject = quad.get_subject();
+    let object = quad.get_object();
+    let graph = quad.get_graph();
+    Quad {
+      subject: nquads::Subject {
+        term_type: subject.term_type,
+        value: if Self::should_use_canonical_id(subject) {
+          canonical_id(&subject.value)
+        } else {
+          subject.value.clone()
+        },
+      },
+      predicate: quad.get_predicate().clone(),
+      object: if Self::should_use_canonical_id(object) || !object.nested_blank_nodes().is_empty() {
+        object.map_blank_nodes(&mut canonical_id)
+      } else {
+        object.clone()
+      },
+      graph: nquads::Graph {
+        term_type: graph.term_type,
+        value: if Self::should_use_canonical_id(graph) {
+          canonical_id(&graph.value)
+        } else {
+          graph.value.clone()
+        },
+      },
+    }
   }
 
   // 4.6) Hash First Degree Quads
@@ -250,7 +368,7 @@ impl<'b> URDNA2015<'b> {
     // identifier in the blank node to quads map.
     // Note: We get the list of quads first and use its length to preallocate the
     // capacity of `serialized_quads` to prevent future reallocation.
-    let mut info = self.blank_node_info.get_mut(id).unwrap();
+    let info = self.blank_node_info.get_mut(id).unwrap();
     let mut serialized_quads: Vec<String> = Vec::with_capacity(info.quads.len());
 
     for quad in &mut info.quads {
@@ -266,10 +384,10 @@ impl<'b> URDNA2015<'b> {
       // subject
       let s: nquads::Subject;
       let mut subject: Option<&nquads::Subject> = None;
-      if quad.subject.term_type == TermType::BlankNode {
+      if quad.get_subject().term_type == TermType::BlankNode {
         s = nquads::Subject {
           term_type: TermType::BlankNode,
-          value: if quad.subject.value == id {
+          value: if quad.get_subject().value == id {
             "_:a".to_string()
           } else {
             "_:z".to_string()
@@ -281,27 +399,39 @@ impl<'b> URDNA2015<'b> {
       // object
       let o: nquads::Object;
       let mut object: Option<&nquads::Object> = None;
-      if quad.object.term_type == TermType::BlankNode {
+      if quad.get_object().term_type == TermType::BlankNode {
         o = nquads::Object {
           term_type: TermType::BlankNode,
-          value: if quad.object.value == id {
+          value: if quad.get_object().value == id {
             "_:a".to_string()
           } else {
             "_:z".to_string()
           },
-          datatype: quad.object.get_datatype(),
-          language: quad.object.get_language(),
+          datatype: quad.get_object().get_datatype(),
+          language: quad.get_object().get_language(),
+          direction: quad.get_object().get_direction(),
+          triple: None,
         };
         object = Some(&o);
+      } else if !quad.get_object().nested_blank_nodes().is_empty() {
+        // blank nodes nested inside a triple term follow the same rule
+        o = quad.get_object().map_blank_nodes(&mut |value| {
+          if value == id {
+            "_:a".to_string()
+          } else {
+            "_:z".to_string()
+          }
+        });
+        object = Some(&o);
       }
 
       // graph
       let g: nquads::Graph;
       let mut graph: Option<&nquads::Graph> = None;
-      if quad.graph.term_type == TermType::BlankNode {
+      if quad.get_graph().term_type == TermType::BlankNode {
         g = nquads::Graph {
           term_type: TermType::BlankNode,
-          value: if quad.graph.value == id {
+          value: if quad.get_graph().value == id {
             "_:a".to_string()
           } else {
             "_:z".to_string()
@@ -315,10 +445,10 @@ impl<'b> URDNA2015<'b> {
         serialized_quads.push(nquads::serialize_quad(*quad));
       } else {
         let quad_copy = nquads::QuadRef {
-          subject: subject.or(Some(&quad.subject)).unwrap(),
-          predicate: &quad.predicate,
-          object: object.or(Some(&quad.object)).unwrap(),
-          graph: graph.or(Some(&quad.graph)).unwrap(),
+          subject: subject.unwrap_or(quad.get_subject()),
+          predicate: quad.get_predicate(),
+          object: object.unwrap_or(quad.get_object()),
+          graph: graph.unwrap_or(quad.get_graph()),
         };
         serialized_quads.push(nquads::serialize_quad(&quad_copy));
       }
@@ -331,7 +461,7 @@ impl<'b> URDNA2015<'b> {
     // through the hash algorithm.
     let mut md: MessageDigest<Sha256> = MessageDigest::new();
     for quad in &serialized_quads {
-      md.update(&quad);
+      md.update(quad);
     }
     let hex = MessageDigest::digest(md);
     info.hash = Some(hex.clone());
@@ -343,7 +473,7 @@ impl<'b> URDNA2015<'b> {
   fn hash_related_blank_node(
     &mut self,
     related: &str,
-    quad: &Quad,
+    quad: &Q,
     issuer: &mut IdentifierIssuer,
     position: &str,
   ) -> String {
@@ -433,13 +563,13 @@ impl<'b> URDNA2015<'b> {
         for related in permutation.iter() {
           // 5.4.4.1) If a canonical identifier has been issued for
           // related, append it to path.
-          if self.canonical_issuer.has_id(&related) {
+          if self.canonical_issuer.has_id(related) {
             path.push_str(&self.canonical_issuer.get_id(related))
           } else {
             // 5.4.4.2) Otherwise:
             // 5.4.4.2.1) If issuer copy has not issued an identifier for
             // related, append related to recursion list.
-            if !issuer_copy.has_id(&related) {
+            if !issuer_copy.has_id(related) {
               recursion_list.push(related)
             }
             // 5.4.4.2.2) Use the Issue Identifier algorithm, passing
@@ -523,12 +653,12 @@ impl<'b> URDNA2015<'b> {
   }
 
   // helper for getting a related predicate
-  fn get_related_predicate(&self, quad: &Quad) -> String {
+  fn get_related_predicate(&self, quad: &Q) -> String {
     let mut related_predicate = String::with_capacity(nquads::DEFAULT_TERM_CAPACITY);
 
     // append "<quad.predicate.value>"
     related_predicate.push('<');
-    related_predicate.push_str(quad.predicate.get_value());
+    related_predicate.push_str(quad.get_predicate().get_value());
     related_predicate.push('>');
 
     related_predicate
@@ -554,9 +684,35 @@ impl<'b> URDNA2015<'b> {
       // or graph name and it is a blank node that is not identified by
       // identifier:
       // steps 3.1.1 and 3.1.2 occur in helpers:
-      self.add_related_blank_node_hash(&quad, &quad.subject, "s", id, issuer, &mut hash_to_related);
-      self.add_related_blank_node_hash(&quad, &quad.object, "o", id, issuer, &mut hash_to_related);
-      self.add_related_blank_node_hash(&quad, &quad.graph, "g", id, issuer, &mut hash_to_related);
+      self.add_related_blank_node_hash(
+        quad,
+        quad.get_subject(),
+        "s",
+        id,
+        issuer,
+        &mut hash_to_related,
+      );
+      self.add_related_blank_node_hash(
+        quad,
+        quad.get_object(),
+        "o",
+        id,
+        issuer,
+        &mut hash_to_related,
+      );
+      self.add_related_blank_node_hash(
+        quad,
+        quad.get_graph(),
+        "g",
+        id,
+        issuer,
+        &mut hash_to_related,
+      );
+      // Note: Blank nodes nested inside a triple term are hashed with the
+      // position of the triple term itself, which is always the object.
+      for related in quad.get_object().nested_blank_nodes() {
+        self.add_related_blank_node_id_hash(quad, related, "o", id, issuer, &mut hash_to_related);
+      }
     }
 
     hash_to_related
@@ -569,12 +725,13 @@ impl<'b> URDNA2015<'b> {
 
     // 5.3.2) Add hash and identifier to hash to blank nodes map,
     // creating a new entry if necessary.
-    hash_to_blank_nodes.entry(hash)
+    hash_to_blank_nodes
+      .entry(hash)
       .and_modify(|e| e.push(id.to_string()))
       .or_insert(vec![id.to_string()]);
   }
 
-  fn add_blank_node_quad_info<'a, T>(&'a mut self, quad: &'b Quad, component: &T)
+  fn add_blank_node_quad_info<'a, T>(&'a mut self, quad: &'b Q, component: &T)
   where
     T: Term,
   {
@@ -582,15 +739,31 @@ impl<'b> URDNA2015<'b> {
       return;
     }
 
-    let id = component.get_value();
-    self.blank_node_info.entry(id.to_string())
-      .and_modify(|e| e.quads.push(quad))
-      .or_insert(BlankNodeInfo { quads: vec![quad], hash: None });
+    self.add_blank_node_id_quad_info(quad, component.get_value());
+  }
+
+  // Note: A quad is referenced once per blank node, even if the blank node
+  // occurs in several of its components, as in rdf-canonize. The components
+  // of a quad are added one after the other, so a repeated reference is
+  // always the last one.
+  fn add_blank_node_id_quad_info(&mut self, quad: &'b Q, id: &str) {
+    self
+      .blank_node_info
+      .entry(id.to_string())
+      .and_modify(|e| {
+        if !e.quads.last().is_some_and(|last| std::ptr::eq(*last, quad)) {
+          e.quads.push(quad);
+        }
+      })
+      .or_insert(BlankNodeInfo {
+        quads: vec![quad],
+        hash: None,
+      });
   }
 
   fn add_related_blank_node_hash<'a, T>(
     &mut self,
-    quad: &Quad,
+    quad: &Q,
     component: &'a T,
     position: &str,
     id: &str,
@@ -599,8 +772,29 @@ impl<'b> URDNA2015<'b> {
   ) where
     T: Term,
   {
-    let related = component.get_value();
-    if !(*component.get_term_type() == TermType::BlankNode && related != id) {
+    if *component.get_term_type() != TermType::BlankNode {
+      return;
+    }
+    self.add_related_blank_node_id_hash(
+      quad,
+      component.get_value(),
+      position,
+      id,
+      issuer,
+      hash_to_related,
+    );
+  }
+
+  fn add_related_blank_node_id_hash(
+    &mut self,
+    quad: &Q,
+    related: &str,
+    position: &str,
+    id: &str,
+    issuer: &mut IdentifierIssuer,
+    hash_to_related: &mut HashBlankNodeMap,
+  ) {
+    if related == id {
       return;
     }
     // 3.1.1) Set hash to the result of the Hash Related Blank Node
@@ -608,26 +802,26 @@ impl<'b> URDNA2015<'b> {
     // related, quad, path identifier issuer as issuer, and position as
     // either s, o, or g based on whether component is a subject, object,
     // graph name, respectively.
-    let hash = self.hash_related_blank_node(&related, quad, issuer, position);
+    let hash = self.hash_related_blank_node(related, quad, issuer, position);
 
     // 3.1.2) Add a mapping of hash to the blank node identifier for
     // component to hash to related blank nodes map, adding an entry as
     // necessary.
-    hash_to_related.entry(hash)
+    hash_to_related
+      .entry(hash)
       .and_modify(|e| e.push(related.to_string()))
       .or_insert(vec![related.to_string()]);
   }
 
-  fn should_use_canonical_id<T>(copy: &T, issuer: &IdentifierIssuer) -> bool
+  // Note: Every blank node has been issued a canonical identifier at this
+  // point, so any blank node is replaced, even one whose input label already
+  // starts with the canonical prefix. Keeping such a label would let it clash
+  // with the identifier issued to another blank node.
+  fn should_use_canonical_id<T>(copy: &T) -> bool
   where
     T: Term,
   {
-    if *copy.get_term_type() == TermType::BlankNode && !copy.get_value().starts_with(&issuer.prefix)
-    {
-      return true;
-    }
-
-    false
+    *copy.get_term_type() == TermType::BlankNode
   }
 }
 
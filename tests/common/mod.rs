@@ -0,0 +1,97 @@
+use rdf_canonize::canonize;
+use rdf_canonize::nquads::{Dataset, ParseError, RDF_FIRST, RDF_NIL, RDF_REST, RDF_TYPE};
+use rdf_canonize::turtle;
+
+use std::fs;
+use std::path::Path;
+
+const MF: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#";
+const RDFT: &str = "http://www.w3.org/ns/rdftest#";
+
+pub struct Test {
+  pub name: String,
+  pub kind: String,
+  pub action: String,
+  pub result: Option<String>,
+}
+
+fn object(dataset: &Dataset, subject: &str, predicate: &str) -> Option<String> {
+  dataset
+    .quads
+    .iter()
+    .find(|quad| quad.subject.value == subject && quad.predicate.value == predicate)
+    .map(|quad| quad.object.value.clone())
+}
+
+// Reads the `mf:entries` list of the test manifest in `dir`, whose documents
+// are resolved against `base`.
+pub fn read_manifest(dir: &Path, base: &str) -> Vec<Test> {
+  let root = format!("{}manifest.ttl", base);
+  let input = fs::read_to_string(dir.join("manifest.ttl")).unwrap();
+  let dataset = turtle::parse_turtle(&input, Some(&root));
+
+  let mut node = object(&dataset, &root, &format!("{}entries", MF)).unwrap();
+  let mut tests = Vec::new();
+  while node != RDF_NIL {
+    let entry = object(&dataset, &node, RDF_FIRST).unwrap();
+    let kind = object(&dataset, &entry, RDF_TYPE).unwrap();
+    tests.push(Test {
+      name: object(&dataset, &entry, &format!("{}name", MF)).unwrap(),
+      kind: kind.trim_start_matches(RDFT).to_string(),
+      action: object(&dataset, &entry, &format!("{}action", MF)).unwrap(),
+      result: object(&dataset, &entry, &format!("{}result", MF)),
+    });
+    node = object(&dataset, &node, RDF_REST).unwrap();
+  }
+
+  tests
+}
+
+// Runs every test in the manifest in `dir`. Test types are matched by their
+// suffix (`PositiveSyntax`, `NegativeSyntax`, `Eval`, `NegativeEval`) and
+// eval results are compared after URDNA2015 canonicalization, so blank node
+// labels in the expected output do not need to match the parser's.
+// Not every test binary that includes this module runs a whole manifest.
+#[allow(dead_code)]
+pub fn run_manifest<P, R>(dir: &Path, base: &str, parse: P, parse_result: R)
+where
+  P: Fn(&str, &str) -> Result<Dataset, ParseError>,
+  R: Fn(&str) -> Dataset,
+{
+  let tests = read_manifest(dir, base);
+  assert!(!tests.is_empty());
+
+  let read = |iri: &str| fs::read_to_string(dir.join(iri.trim_start_matches(base))).unwrap();
+  let canonical = |dataset: &Dataset| canonize(dataset, "URDNA2015").unwrap();
+
+  let mut failures = Vec::new();
+  for test in &tests {
+    let parsed = parse(&read(&test.action), &test.action);
+    let outcome = if test.kind.ends_with("PositiveSyntax") {
+      parsed.map(|_| ()).map_err(|e| e.to_string())
+    } else if test.kind.ends_with("NegativeSyntax") || test.kind.ends_with("NegativeEval") {
+      match parsed {
+        Ok(_) => Err(String::from("parsed a document that should be rejected")),
+        Err(_) => Ok(()),
+      }
+    } else if test.kind.ends_with("Eval") {
+      let expected = parse_result(&read(test.result.as_ref().unwrap()));
+      match parsed {
+        Ok(dataset) if canonical(&dataset) == canonical(&expected) => Ok(()),
+        Ok(dataset) => Err(format!(
+          "expected:\n{}actual:\n{}",
+          canonical(&expected),
+          canonical(&dataset)
+        )),
+        Err(e) => Err(e.to_string()),
+      }
+    } else {
+      Err(format!("unknown test type {}", test.kind))
+    };
+    if let Err(message) = outcome {
+      failures.push(format!("{}: {}", test.name, message));
+    }
+  }
+
+  assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
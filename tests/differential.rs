@@ -0,0 +1,77 @@
+use rdf_canonize::{canonize, nquads};
+
+use std::fs;
+use std::path::Path;
+
+// Implementations whose output is checked in, see tests/differential/README.md.
+const IMPLEMENTATIONS: [&str; 3] = ["reference-py", "rdf-canonize-js", "pyld"];
+
+// Compares the canonical N-Quads of every `<case>-in.nq` in the differential
+// corpus with each `<case>-<implementation>.nq` output checked in next to it.
+#[test]
+fn differential_corpus() {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/differential");
+  let mut inputs: Vec<String> = fs::read_dir(&dir)
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+    .filter(|name| name.ends_with("-in.nq"))
+    .collect();
+  inputs.sort();
+
+  let mut checked = 0;
+  let mut failures = Vec::new();
+  for input in &inputs {
+    let case = input.trim_end_matches("-in.nq");
+    let dataset = nquads::parse_nquads(&fs::read_to_string(dir.join(input)).unwrap());
+    let actual = canonize(&dataset, "URDNA2015").unwrap();
+
+    let mut found = false;
+    for implementation in &IMPLEMENTATIONS {
+      let output = dir.join(format!("{}-{}.nq", case, implementation));
+      let expected = match fs::read_to_string(output) {
+        Ok(expected) => expected,
+        Err(_) => continue,
+      };
+      if actual != expected {
+        failures.push(format!(
+          "{} differs from {}:\n{}",
+          case,
+          implementation,
+          diff(&expected, &actual)
+        ));
+      }
+      found = true;
+      checked += 1;
+    }
+    if !found {
+      failures.push(format!("{}: no expected output", case));
+    }
+  }
+
+  assert!(checked > 0);
+  assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+// A line diff of two canonical N-Quads documents. Both are sorted, so a merge
+// finds the lines only in the expected output ("-") and only in ours ("+").
+fn diff(expected: &str, actual: &str) -> String {
+  let mut expected = expected.lines().peekable();
+  let mut actual = actual.lines().peekable();
+  let mut output = String::new();
+  loop {
+    let line = match (expected.peek(), actual.peek()) {
+      (None, None) => break,
+      (Some(e), Some(a)) if e == a => {
+        let line = format!("  {}\n", e);
+        expected.next();
+        actual.next();
+        line
+      }
+      (Some(e), Some(a)) if e < a => format!("- {}\n", expected.next().unwrap()),
+      (Some(_), None) => format!("- {}\n", expected.next().unwrap()),
+      _ => format!("+ {}\n", actual.next().unwrap()),
+    };
+    output.push_str(&line);
+  }
+  output
+}
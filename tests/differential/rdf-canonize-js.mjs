@@ -0,0 +1,19 @@
+// Writes the output of rdf-canonize, the canonicalizer used by jsonld.js, to
+// `<case>-rdf-canonize-js.nq` for every `<case>-in.nq` next to this script.
+//
+//     npm install rdf-canonize && node tests/differential/rdf-canonize-js.mjs
+
+import canonize from 'rdf-canonize';
+import {readdir, readFile, writeFile} from 'node:fs/promises';
+
+const dir = new URL('.', import.meta.url);
+const inputs = (await readdir(dir)).filter(name => name.endsWith('-in.nq')).sort();
+for(const name of inputs) {
+  const input = await readFile(new URL(name, dir), 'utf8');
+  const output = await canonize.canonize(input, {
+    algorithm: 'URDNA2015',
+    inputFormat: 'application/n-quads'
+  });
+  const result = name.replace(/-in\.nq$/, '-rdf-canonize-js.nq');
+  await writeFile(new URL(result, dir), output);
+}
@@ -0,0 +1,248 @@
+#!/usr/bin/env python3
+# An independent URDNA2015 implementation, written from the specification
+# (https://www.w3.org/TR/rdf-canon/) using only the Python standard library.
+#
+# It reads every `*-in.nq` file next to it and writes the canonical N-Quads
+# to `<case>-reference-py.nq`. It supports the N-Quads used by this corpus:
+# IRIs, blank nodes and literals with a language tag or datatype, but not
+# triple terms.
+#
+#     python3 tests/differential/reference.py
+#
+# With `--pyld` it writes the output of pyld (pip install pyld) to
+# `<case>-pyld.nq` instead.
+
+import hashlib
+import itertools
+import pathlib
+import re
+import sys
+
+XSD_STRING = 'http://www.w3.org/2001/XMLSchema#string'
+
+TERM = re.compile(
+    r'\s*(?:<([^>]*)>|_:(\S+)|"((?:[^"\\]|\\.)*)"(?:@([A-Za-z0-9-]+)|\^\^<([^>]*)>)?)')
+
+UNESCAPE = {'t': '\t', 'b': '\b', 'n': '\n', 'r': '\r', 'f': '\f', '"': '"',
+            "'": "'", '\\': '\\'}
+
+
+def unescape(value):
+    def replace(match):
+        escape = match.group(1)
+        if escape[0] in 'uU':
+            return chr(int(escape[1:], 16))
+        return UNESCAPE[escape]
+    return re.sub(r'\\(u[0-9A-Fa-f]{4}|U[0-9A-Fa-f]{8}|.)', replace, value)
+
+
+# Terms are tuples: ('iri', value), ('blank', label) or
+# ('literal', value, datatype, language). The default graph is None.
+def parse(text):
+    quads = []
+    for line in text.splitlines():
+        line = line.strip()
+        if not line or line.startswith('#'):
+            continue
+        terms = []
+        rest = line
+        while not rest.startswith('.'):
+            match = TERM.match(rest)
+            if not match:
+                raise ValueError('cannot parse: ' + line)
+            iri, blank, literal, language, datatype = match.groups()
+            if iri is not None:
+                terms.append(('iri', iri))
+            elif blank is not None:
+                terms.append(('blank', blank))
+            else:
+                terms.append(('literal', unescape(literal), datatype or XSD_STRING, language))
+            rest = rest[match.end():].lstrip()
+        if len(terms) == 3:
+            terms.append(None)
+        quads.append(tuple(terms))
+    return quads
+
+
+def escape(value):
+    return (value.replace('\\', '\\\\').replace('"', '\\"')
+            .replace('\n', '\\n').replace('\r', '\\r'))
+
+
+def serialize_term(term):
+    if term[0] == 'iri':
+        return '<' + term[1] + '>'
+    if term[0] == 'blank':
+        return '_:' + term[1]
+    _, value, datatype, language = term
+    if language:
+        return '"' + escape(value) + '"@' + language
+    if datatype != XSD_STRING:
+        return '"' + escape(value) + '"^^<' + datatype + '>'
+    return '"' + escape(value) + '"'
+
+
+def serialize_quad(quad):
+    terms = [serialize_term(term) for term in quad if term is not None]
+    return ' '.join(terms) + ' .\n'
+
+
+def sha256(text):
+    return hashlib.sha256(text.encode('utf-8')).hexdigest()
+
+
+class IdentifierIssuer:
+    def __init__(self, prefix):
+        self.prefix = prefix
+        self.issued = {}
+
+    def get(self, label):
+        if label not in self.issued:
+            self.issued[label] = self.prefix + str(len(self.issued))
+        return self.issued[label]
+
+    def copy(self):
+        issuer = IdentifierIssuer(self.prefix)
+        issuer.issued = dict(self.issued)
+        return issuer
+
+
+def is_blank(term):
+    return term is not None and term[0] == 'blank'
+
+
+class URDNA2015:
+    def __init__(self, quads):
+        self.quads = quads
+        self.canonical = IdentifierIssuer('c14n')
+        # blank node label -> the quads it is a component of, each referenced
+        # once even if the blank node occurs in several components, as in
+        # rdf-canonize
+        self.blank_nodes = {}
+        for quad in quads:
+            labels = {term[1] for term in (quad[0], quad[2], quad[3]) if is_blank(term)}
+            for label in sorted(labels):
+                self.blank_nodes.setdefault(label, []).append(quad)
+        self.first_degree = {}
+
+    # 4.6 Hash First Degree Quads
+    def hash_first_degree(self, label):
+        def rename(term):
+            if is_blank(term):
+                return ('blank', 'a' if term[1] == label else 'z')
+            return term
+        nquads = sorted(serialize_quad(tuple(rename(term) for term in quad))
+                        for quad in self.blank_nodes[label])
+        return sha256(''.join(nquads))
+
+    # 4.7 Hash Related Blank Node
+    def hash_related(self, related, quad, issuer, position):
+        if related in self.canonical.issued:
+            identifier = '_:' + self.canonical.issued[related]
+        elif related in issuer.issued:
+            identifier = '_:' + issuer.issued[related]
+        else:
+            identifier = self.first_degree[related]
+        data = position
+        if position != 'g':
+            data += serialize_term(quad[1])
+        return sha256(data + identifier)
+
+    # 4.8 Hash N-Degree Quads
+    def hash_n_degree(self, label, issuer):
+        hash_to_related = {}
+        for quad in self.blank_nodes[label]:
+            for term, position in ((quad[0], 's'), (quad[2], 'o'), (quad[3], 'g')):
+                if is_blank(term) and term[1] != label:
+                    related_hash = self.hash_related(term[1], quad, issuer, position)
+                    hash_to_related.setdefault(related_hash, []).append(term[1])
+
+        data = ''
+        for related_hash in sorted(hash_to_related):
+            data += related_hash
+            chosen_path = ''
+            chosen_issuer = None
+            for permutation in itertools.permutations(hash_to_related[related_hash]):
+                issuer_copy = issuer.copy()
+                path = ''
+                recursion = []
+                for related in permutation:
+                    if related in self.canonical.issued:
+                        path += '_:' + self.canonical.issued[related]
+                    else:
+                        if related not in issuer_copy.issued:
+                            recursion.append(related)
+                        path += '_:' + issuer_copy.get(related)
+                for related in recursion:
+                    result_hash, issuer_copy = self.hash_n_degree(related, issuer_copy)
+                    path += '_:' + issuer_copy.get(related)
+                    path += '<' + result_hash + '>'
+                if chosen_issuer is None or path < chosen_path:
+                    chosen_path = path
+                    chosen_issuer = issuer_copy
+            data += chosen_path
+            issuer = chosen_issuer
+
+        return sha256(data), issuer
+
+    # 4.4 Canonicalization Algorithm
+    def canonicalize(self):
+        hash_to_labels = {}
+        for label in self.blank_nodes:
+            self.first_degree[label] = self.hash_first_degree(label)
+            hash_to_labels.setdefault(self.first_degree[label], []).append(label)
+
+        shared = []
+        for first_degree in sorted(hash_to_labels):
+            labels = hash_to_labels[first_degree]
+            if len(labels) == 1:
+                self.canonical.get(labels[0])
+            else:
+                shared.append(labels)
+
+        for labels in shared:
+            hash_path_list = []
+            for label in labels:
+                if label in self.canonical.issued:
+                    continue
+                issuer = IdentifierIssuer('b')
+                issuer.get(label)
+                hash_path_list.append(self.hash_n_degree(label, issuer))
+            # code point order, like every other sort in the algorithm
+            hash_path_list.sort(key=lambda result: result[0])
+            for _, issuer in hash_path_list:
+                for label in issuer.issued:
+                    self.canonical.get(label)
+
+        def relabel(term):
+            if is_blank(term):
+                return ('blank', self.canonical.issued[term[1]])
+            return term
+        nquads = {serialize_quad(tuple(relabel(term) for term in quad)) for quad in self.quads}
+        return ''.join(sorted(nquads))
+
+
+def canonicalize_pyld(text):
+    from pyld import jsonld
+    return jsonld.normalize(text, {
+        'algorithm': 'URDNA2015',
+        'inputFormat': 'application/n-quads',
+        'format': 'application/n-quads',
+    })
+
+
+def main():
+    directory = pathlib.Path(__file__).parent
+    pyld = '--pyld' in sys.argv[1:]
+    for source in sorted(directory.glob('*-in.nq')):
+        case = source.name[:-len('-in.nq')]
+        text = source.read_text('utf-8')
+        if pyld:
+            output, suffix = canonicalize_pyld(text), '-pyld.nq'
+        else:
+            output, suffix = URDNA2015(parse(text)).canonicalize(), '-reference-py.nq'
+        directory.joinpath(case + suffix).write_text(output, 'utf-8')
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,97 @@
+#![cfg(feature = "jsonld")]
+
+use rdf_canonize::canonize;
+use rdf_canonize::jsonld::{
+  self, DocumentLoader, JsonLdError, JsonLdOptions, RdfDirection, RemoteDocument,
+};
+use rdf_canonize::nquads::{self, Dataset};
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BASE: &str = "https://example.org/rdf-canonize-rs/tests/jsonld/";
+
+// Serves the documents under `BASE` from the test directory.
+struct FileLoader {
+  dir: PathBuf,
+}
+
+impl DocumentLoader for FileLoader {
+  fn load_document(&self, url: &str) -> Result<RemoteDocument, JsonLdError> {
+    let path = match url.strip_prefix(BASE) {
+      Some(path) => self.dir.join(path),
+      None => return Err(JsonLdError::new("loading document failed", url)),
+    };
+    let document = fs::read_to_string(&path)
+      .ok()
+      .and_then(|document| serde_json::from_str(&document).ok())
+      .ok_or_else(|| JsonLdError::new("loading document failed", url))?;
+    Ok(RemoteDocument {
+      document_url: url.to_string(),
+      document,
+    })
+  }
+}
+
+fn options(test: &Value, base: &str) -> JsonLdOptions {
+  let rdf_direction = match test.pointer("/option/rdfDirection").and_then(Value::as_str) {
+    Some("i18n-datatype") => Some(RdfDirection::I18nDatatype),
+    Some("compound-literal") => Some(RdfDirection::CompoundLiteral),
+    _ => None,
+  };
+  JsonLdOptions {
+    base: Some(base.to_string()),
+    rdf_direction,
+    ..JsonLdOptions::default()
+  }
+}
+
+// Runs the cases of the toRdf manifest. Positive cases are compared with the
+// expected N-Quads after URDNA2015 canonicalization; negative cases must fail
+// with the expected error code.
+#[test]
+fn to_rdf_manifest_tests() {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/jsonld");
+  let manifest: Value =
+    serde_json::from_str(&fs::read_to_string(dir.join("toRdf-manifest.jsonld")).unwrap()).unwrap();
+  let loader = FileLoader { dir: dir.clone() };
+  let canonical = |dataset: &Dataset| canonize(dataset, "URDNA2015").unwrap();
+
+  let tests = manifest["sequence"].as_array().unwrap();
+  assert!(!tests.is_empty());
+
+  let mut failures = Vec::new();
+  for test in tests {
+    let input = test["input"].as_str().unwrap();
+    let base = format!("{}{}", BASE, input);
+    let document = loader.load_document(&base).unwrap().document;
+    let result = jsonld::to_rdf(&document, &options(test, &base), &loader);
+
+    let outcome = match (test["expect"].as_str(), test["expectErrorCode"].as_str()) {
+      (Some(expect), _) => {
+        let expected = nquads::parse_nquads(&fs::read_to_string(dir.join(expect)).unwrap());
+        match result {
+          Ok(dataset) if canonical(&dataset) == canonical(&expected) => Ok(()),
+          Ok(dataset) => Err(format!(
+            "expected:\n{}actual:\n{}",
+            canonical(&expected),
+            canonical(&dataset)
+          )),
+          Err(e) => Err(e.to_string()),
+        }
+      }
+      (None, Some(code)) => match result {
+        Err(e) if e.code == code => Ok(()),
+        Err(e) => Err(format!("expected {}, got {}", code, e)),
+        Ok(_) => Err(format!("expected {}, got a dataset", code)),
+      },
+      (None, None) => Err(String::from("test has neither expect nor expectErrorCode")),
+    };
+    if let Err(message) = outcome {
+      failures.push(format!("{}: {}", test["name"].as_str().unwrap(), message));
+    }
+  }
+
+  assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
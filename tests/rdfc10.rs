@@ -0,0 +1,46 @@
+mod common;
+
+use rdf_canonize::{canonize, canonize_with_options, nquads, CanonizeOptions};
+
+use std::fs;
+use std::path::Path;
+
+const BASE: &str = "https://example.org/rdf-canonize-rs/tests/rdfc10/";
+
+// Runs the duplicate quad cases in tests/rdfc10. With `deduplicate_quads` set
+// the output must match the expected canonical N-Quads exactly, and must not
+// depend on how often a quad was repeated.
+#[test]
+fn rdfc10_duplicate_manifest_tests() {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/rdfc10");
+  let tests = common::read_manifest(&dir, BASE);
+  assert!(!tests.is_empty());
+
+  let read = |iri: &str| fs::read_to_string(dir.join(iri.trim_start_matches(BASE))).unwrap();
+  let options = CanonizeOptions {
+    deduplicate_quads: true,
+    ..CanonizeOptions::default()
+  };
+
+  let mut failures = Vec::new();
+  for test in &tests {
+    if !test.kind.ends_with("RDFC10EvalTest") {
+      failures.push(format!("{}: unknown test type {}", test.name, test.kind));
+      continue;
+    }
+    let mut dataset = nquads::parse_nquads(&read(&test.action));
+    let expected = read(test.result.as_ref().unwrap());
+    let actual = canonize_with_options(&dataset, "URDNA2015", &options).unwrap();
+    dataset.dedup();
+    if actual != expected {
+      failures.push(format!(
+        "{}: expected:\n{}actual:\n{}",
+        test.name, expected, actual
+      ));
+    } else if canonize(&dataset, "URDNA2015").unwrap() != expected {
+      failures.push(format!("{}: Dataset::dedup disagrees", test.name));
+    }
+  }
+
+  assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
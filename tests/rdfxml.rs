@@ -0,0 +1,17 @@
+#![cfg(feature = "rdfxml")]
+
+mod common;
+
+use rdf_canonize::{ntriples, rdfxml};
+
+use std::path::Path;
+
+#[test]
+fn rdfxml_manifest_tests() {
+  common::run_manifest(
+    &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/rdfxml"),
+    "https://example.org/rdf-canonize-rs/tests/rdfxml/",
+    |input, base| rdfxml::try_parse_rdfxml(input, Some(base)),
+    ntriples::parse_ntriples,
+  );
+}
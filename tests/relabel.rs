@@ -0,0 +1,131 @@
+#![cfg(feature = "rand")]
+
+use rdf_canonize::generate::{generate_seeded, Shape};
+use rdf_canonize::nquads::{self, Dataset};
+use rdf_canonize::relabel;
+
+use proptest::prelude::*;
+use proptest::test_runner::{RngAlgorithm, TestRng};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+fn node() -> impl Strategy<Value = String> {
+  prop_oneof![
+    (0..6u8).prop_map(|i| format!("_:b{}", i)),
+    (0..3u8).prop_map(|i| format!("<http://example.org/n{}>", i)),
+  ]
+}
+
+fn object() -> impl Strategy<Value = String> {
+  prop_oneof![
+    4 => node(),
+    1 => (0..2u8).prop_map(|i| format!("\"{}\"", i)),
+    1 => (node(), 0..2u8, node())
+      .prop_map(|(s, p, o)| format!("<<( {} <http://example.org/p{}> {} )>>", s, p, o)),
+  ]
+}
+
+fn graph() -> impl Strategy<Value = String> {
+  prop_oneof![
+    2 => Just(String::new()),
+    1 => (0..2u8).prop_map(|i| format!("<http://example.org/g{}>", i)),
+    1 => (0..2u8).prop_map(|i| format!("_:g{}", i)),
+  ]
+}
+
+// Small datasets with few distinct terms, so that blank nodes are often
+// connected to each other and share first degree hashes.
+fn dataset() -> impl Strategy<Value = Dataset> {
+  prop::collection::vec((node(), 0..3u8, object(), graph()), 0..12).prop_map(|quads| {
+    let nquads: String = quads
+      .iter()
+      .map(|(s, p, o, g)| format!("{} <http://example.org/p{}> {} {} .\n", s, p, o, g))
+      .collect();
+    nquads::parse_nquads(&nquads)
+  })
+}
+
+// Circulant graphs of blank nodes, where node i links to the next `step`
+// nodes. These include rings, `_:a p _:b . _:b p _:a`, regular graphs and
+// cliques. Every node has the same first degree hash, so the labels come
+// from N-degree hashing alone.
+fn symmetric_dataset() -> impl Strategy<Value = Dataset> {
+  (2..6usize, 1..4usize, any::<bool>()).prop_map(|(size, step, undirected)| {
+    let mut lines = BTreeSet::new();
+    for i in 0..size {
+      for d in 1..=step.min(size - 1) {
+        let j = (i + d) % size;
+        lines.insert(format!("_:b{} <http://example.org/p> _:b{} .\n", i, j));
+        if undirected {
+          lines.insert(format!("_:b{} <http://example.org/p> _:b{} .\n", j, i));
+        }
+      }
+    }
+    nquads::parse_nquads(&lines.into_iter().collect::<String>())
+  })
+}
+
+proptest! {
+  #[test]
+  fn canonize_is_label_invariant(dataset in dataset(), seed in any::<[u8; 32]>()) {
+    let mut rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed);
+    relabel::assert_canonize_invariant(&dataset, &mut rng);
+  }
+
+  #[test]
+  fn symmetric_canonize_is_label_invariant(
+    dataset in symmetric_dataset(),
+    seed in any::<[u8; 32]>(),
+  ) {
+    let mut rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed);
+    relabel::assert_canonize_invariant(&dataset, &mut rng);
+  }
+}
+
+// Runs the check with a few seeds on every N-Quads fixture of the test suites.
+#[test]
+fn fixtures_are_label_invariant() {
+  let tests = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+  let mut count = 0;
+  for dir in fs::read_dir(&tests).unwrap() {
+    let dir = dir.unwrap().path();
+    if !dir.is_dir() {
+      continue;
+    }
+    for file in fs::read_dir(&dir).unwrap() {
+      let file = file.unwrap().path();
+      if file.extension().and_then(|e| e.to_str()) != Some("nq") {
+        continue;
+      }
+      let dataset = nquads::parse_nquads(&fs::read_to_string(&file).unwrap());
+      let mut rng = TestRng::deterministic_rng(RngAlgorithm::ChaCha);
+      for _ in 0..4 {
+        relabel::assert_canonize_invariant(&dataset, &mut rng);
+      }
+      count += 1;
+    }
+  }
+  assert!(count > 0);
+}
+
+// Runs the check on generated datasets of every shape, sized so that the
+// symmetric ones still canonicalize quickly.
+#[test]
+fn generated_shapes_are_label_invariant() {
+  let shapes = [
+    (Shape::Chain, 12),
+    (Shape::Star, 12),
+    (Shape::Clique, 4),
+    (Shape::Regular(3), 8),
+    (Shape::Regular(4), 7),
+    (Shape::Credentials, 8),
+  ];
+  let mut rng = TestRng::deterministic_rng(RngAlgorithm::ChaCha);
+  for (shape, size) in &shapes {
+    for seed in 0..3 {
+      let dataset = generate_seeded(*shape, *size, seed).unwrap();
+      relabel::assert_canonize_invariant(&dataset, &mut rng);
+    }
+  }
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use rdf_canonize::{nquads, trig};
+
+use std::path::Path;
+
+#[test]
+fn trig_manifest_tests() {
+  common::run_manifest(
+    &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/trig"),
+    "https://example.org/rdf-canonize-rs/tests/trig/",
+    |input, base| trig::try_parse_trig(input, Some(base)),
+    nquads::parse_nquads,
+  );
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use rdf_canonize::{ntriples, turtle};
+
+use std::path::Path;
+
+#[test]
+fn turtle_manifest_tests() {
+  common::run_manifest(
+    &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/turtle"),
+    "https://example.org/rdf-canonize-rs/tests/turtle/",
+    |input, base| turtle::try_parse_turtle(input, Some(base)),
+    ntriples::parse_ntriples,
+  );
+}